@@ -144,7 +144,7 @@ export async function mergeConfig(
 
       // ignore it, because about_pake.html have be erased.
       // const filesToCopyBack = ['cli.js', 'about_pake.html'];
-      const filesToCopyBack = ['cli.js'];
+      const filesToCopyBack = ['cli.js', 'pake_preferences.html'];
       await Promise.all(
         filesToCopyBack.map((file) =>
           fsExtra.copy(path.join(distBakDir, file), path.join(distDir, file)),
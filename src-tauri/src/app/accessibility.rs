@@ -0,0 +1,42 @@
+use crate::util::{load_app_state, save_app_state};
+use serde::Serialize;
+use serde_json::Value;
+use tauri::{AppHandle, Manager};
+
+const GRAYSCALE_STATE_KEY: &str = "grayscale";
+const REDUCED_MOTION_STATE_KEY: &str = "reduced_motion";
+
+/// App-wide display modes, persisted and applied to every window by `inject/accessibility.js`.
+#[derive(Debug, Default, Clone, Copy, Serialize)]
+pub struct AccessibilityModes {
+    pub grayscale: bool,
+    pub reduced_motion: bool,
+}
+
+pub fn modes(app: &AppHandle) -> AccessibilityModes {
+    let enabled = |key| load_app_state(app, key).and_then(|v| v.as_bool()) == Some(true);
+    AccessibilityModes {
+        grayscale: enabled(GRAYSCALE_STATE_KEY),
+        reduced_motion: enabled(REDUCED_MOTION_STATE_KEY),
+    }
+}
+
+fn set_mode(app: &AppHandle, key: &str, enabled: bool) -> Result<(), String> {
+    save_app_state(app, key, Value::Bool(enabled))?;
+    let script = format!(
+        "window.pakeSetAccessibility?.({})",
+        serde_json::to_string(&modes(app)).unwrap()
+    );
+    for window in app.webview_windows().values() {
+        let _ = window.eval(&script);
+    }
+    Ok(())
+}
+
+pub fn set_grayscale(app: &AppHandle, enabled: bool) -> Result<(), String> {
+    set_mode(app, GRAYSCALE_STATE_KEY, enabled)
+}
+
+pub fn set_reduced_motion(app: &AppHandle, enabled: bool) -> Result<(), String> {
+    set_mode(app, REDUCED_MOTION_STATE_KEY, enabled)
+}
@@ -1,4 +1,8 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+const MIN_UI_SCALE: f64 = 0.5;
+const MAX_UI_SCALE: f64 = 3.0;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct WindowConfig {
@@ -22,26 +26,829 @@ pub struct WindowConfig {
     #[serde(default)]
     pub new_window: bool,
     pub start_to_tray: bool,
+    /// Keep the window hidden until the page first paints (or finishes loading)
+    /// instead of showing it after a fixed delay, so it never opens blank.
+    #[serde(default)]
+    pub show_on_first_paint: bool,
+    /// Virtual desktop (0-based) the window moves to once shown, on X11 and Windows.
+    #[serde(default)]
+    pub start_workspace: Option<u32>,
     #[serde(default)]
     pub force_internal_navigation: bool,
-    #[serde(default = "default_zoom")]
-    pub zoom: u32,
+    /// Initial zoom percent, either one number or per platform.
+    #[serde(default)]
+    pub zoom: ZoomConfig,
     #[serde(default)]
     pub min_width: f64,
     #[serde(default)]
     pub min_height: f64,
     #[serde(default)]
     pub ignore_certificate_errors: bool,
+    #[serde(default = "default_true")]
+    pub javascript_enabled: bool,
+    #[serde(default)]
+    pub inline_content_types: Vec<String>,
+    #[serde(default)]
+    pub confirm_external_navigation: bool,
+    /// What to do with links to schemes the webview can't load, like `mailto:` or `tel:`.
+    #[serde(default)]
+    pub external_protocols: ExternalProtocols,
+    /// Cookie names on the allowed host kept across `clear_cache_and_restart`,
+    /// typically the session cookie so a cache clear doesn't sign you out.
+    #[serde(default)]
+    pub preserve_cookies: Vec<String>,
+    /// Wayland app_id and X11 WM_CLASS, which should match the .desktop file's
+    /// `StartupWMClass` for taskbar grouping. Defaults to the bundle identifier.
+    #[serde(default)]
+    pub linux_app_id: Option<String>,
+    #[serde(default)]
+    pub titlebar_color: Option<String>,
+    #[serde(default)]
+    pub keepalive_url: Option<String>,
+    #[serde(default = "default_keepalive_interval_secs")]
+    pub keepalive_interval_secs: u64,
+    #[serde(default)]
+    pub keepalive_skip_when_hidden: bool,
+    #[serde(default)]
+    pub tray_fullscreen_behavior: TrayFullscreenBehavior,
+    #[serde(default)]
+    pub accept_invalid_certs: bool,
+    #[serde(default)]
+    pub invalid_cert_hosts: Vec<String>,
+    #[serde(default)]
+    pub focus_selector_on_show: Option<String>,
+    #[serde(default)]
+    pub start_maximized: bool,
+    #[serde(default)]
+    pub window_level: WindowLevel,
+    #[serde(default)]
+    pub visible_on_all_workspaces: bool,
+    #[serde(default = "default_true")]
+    pub remember_window_state: bool,
+    /// Remember a separate position and size for each monitor setup (e.g. docked
+    /// and laptop-only) and switch to it when the connected monitors change.
+    #[serde(default)]
+    pub per_monitor_state: bool,
+    #[serde(default)]
+    pub accent_color: Option<String>,
+    #[serde(default = "default_oauth_domains")]
+    pub oauth_domains: Vec<String>,
+    #[serde(default)]
+    pub oauth_in_browser: bool,
+    #[serde(default)]
+    pub max_width: f64,
+    #[serde(default)]
+    pub max_height: f64,
+    #[serde(default)]
+    pub platform_overrides: PlatformSpecific<WindowSizeOverride>,
+    #[serde(default)]
+    pub beforeunload: BeforeUnloadBehavior,
+    #[serde(default)]
+    pub content_block_rules: Option<String>,
+    /// Block ads and trackers with the bundled filter list, on top of
+    /// `content_block_rules`. `toggle_adblock` flips it at runtime.
+    #[serde(default)]
+    pub adblock: bool,
+    /// Global accelerator (e.g. `CmdOrCtrl+Alt+Left`) to snap position.
+    #[serde(default)]
+    pub snap_shortcuts: HashMap<String, SnapPosition>,
+    #[serde(default)]
+    pub dnd_queue_notifications: bool,
+    #[serde(default)]
+    pub har_capture: bool,
+    #[serde(default)]
+    pub initial_post: Option<InitialPost>,
+    /// Monitor name, or zero-based index, the window returns to when shown.
+    #[serde(default)]
+    pub pinned_monitor: Option<String>,
+    /// URL globs, or `/regex/`, checked before the host rules. Deny wins over allow.
+    #[serde(default)]
+    pub navigation_allow: Vec<String>,
+    #[serde(default)]
+    pub navigation_deny: Vec<String>,
+    /// Space/Shift+Space page and Home/End jump outside editable fields.
+    #[serde(default)]
+    pub scroll_shortcuts: bool,
+    /// Off by default so the platform's autofill and password prompts keep working.
+    #[serde(default)]
+    pub disable_password_manager: bool,
+    /// Width / height ratio kept while resizing, e.g. `1.7778` for 16:9.
+    #[serde(default)]
+    pub aspect_ratio: Option<f64>,
+    /// Ask where to save every download instead of saving to `download_dir`.
+    #[serde(default)]
+    pub download_prompt: bool,
+    /// Defaults to the system download directory.
+    #[serde(default)]
+    pub download_dir: Option<String>,
+    /// Reload the home URL after the page stops answering for this many seconds.
+    #[serde(default)]
+    pub hang_recovery_secs: Option<u64>,
+    /// Checkpoint window state every this many seconds, see `save_window_state`.
+    #[serde(default)]
+    pub autosave_state_secs: Option<u64>,
+    /// macOS: bring the window to the current Space when another launch or a
+    /// Dock click focuses it, instead of switching to the Space it was left on.
+    #[serde(default = "default_true")]
+    pub focus_on_active_space: bool,
+    #[serde(default)]
+    pub hide_on_close_mode: HideOnCloseMode,
+    /// On-demand scripts listed under a "Scripts" menu, unlike `custom.js` which runs at load.
+    #[serde(default)]
+    pub scripts_menu: Vec<ScriptMenuItem>,
+    /// Scripts run in every page of the main window, each at its own `run_at`.
+    #[serde(default)]
+    pub init_scripts: Vec<InitScript>,
+    /// Replay files dropped on the window into the page, for platforms where HTML drops don't arrive.
+    #[serde(default)]
+    pub enable_file_drop: bool,
+    #[serde(default)]
+    pub file_drop_max_bytes: Option<u64>,
+    /// Accepted extensions such as `pdf` or `.png`; empty accepts any.
+    #[serde(default)]
+    pub file_drop_extensions: Vec<String>,
+    /// Global JS function that receives the dropped paths instead of the page getting `File`s.
+    #[serde(default)]
+    pub file_drop_callback: Option<String>,
+    /// Prepended to the window title, e.g. `[Work]`. `set_title_prefix` overrides it per window.
+    #[serde(default)]
+    pub title_prefix: Option<String>,
+    /// Whether the window title stays as configured or follows the page's `<title>`.
+    #[serde(default)]
+    pub title_mode: TitleMode,
+    /// CSS selector or JS expression naming the signed-in account, used as the
+    /// title prefix when none is set. See `util::identity_script`.
+    #[serde(default)]
+    pub identity_selector: Option<String>,
+    /// Hex window and webview background shown until the page paints.
+    #[serde(default)]
+    pub background_color: Option<String>,
+    /// Site-specific snippets run by name through `run_named_action` or their shortcut.
+    #[serde(default)]
+    pub named_actions: HashMap<String, NamedAction>,
+    #[serde(default)]
+    pub permissions: PermissionsConfig,
+    /// Page access to the clipboard. Writes are allowed unless denied; reads ask
+    /// first under `Prompt`. See `inject/clipboard.js` for the native fallback.
+    #[serde(default)]
+    pub clipboard_access: PermissionPolicy,
+    /// Picture-in-picture size in logical pixels, see `enter_pip_mode`.
+    #[serde(default = "default_pip_width")]
+    pub pip_width: f64,
+    #[serde(default = "default_pip_height")]
+    pub pip_height: f64,
+    #[serde(default)]
+    pub pip_corner: PipCorner,
+    #[serde(default)]
+    pub pip_hide_decorations: bool,
+    /// Scale the whole UI, clamped to 0.5–3.0. See `view_state::restore_view_state`.
+    #[serde(default)]
+    pub ui_scale: Option<f64>,
+    /// In-page accelerators toggling `set_grayscale` and `set_reduced_motion`.
+    #[serde(default)]
+    pub grayscale_shortcut: Option<String>,
+    #[serde(default)]
+    pub reduced_motion_shortcut: Option<String>,
+    #[serde(default)]
+    pub scrollbar: ScrollbarStyle,
+    /// Hosts (e.g. an SSO provider) the startup navigation may pass through
+    /// before the first page finishes loading on the app's own host.
+    #[serde(default)]
+    pub follow_initial_redirects_to: Vec<String>,
+    /// URL opened for selected text from the context menu, `{selection}` being
+    /// replaced by the URL-encoded text, e.g. `https://translate.google.com/?text={selection}`.
+    #[serde(default)]
+    pub lookup_url_template: Option<String>,
+    /// Context menu label for the lookup item, "Look Up" by default.
+    #[serde(default)]
+    pub lookup_label: Option<String>,
+    /// Thickness in CSS pixels of the edge regions that resize an undecorated
+    /// window, 0 to disable. See `inject/resize.js`.
+    #[serde(default = "default_resize_handle_size")]
+    pub resize_handle_size: f64,
+    /// Animated wheel and keyboard scrolling, left to the platform when unset.
+    /// `false` also forces `scroll-behavior: auto` on pages that ask for smooth scrolling.
+    #[serde(default)]
+    pub smooth_scrolling: Option<bool>,
+    /// Render without GPU acceleration, for GPUs and drivers that garble the page.
+    /// Windows and Linux only.
+    #[serde(default)]
+    pub disable_gpu: bool,
+    /// Extra Chromium switches for WebView2 on Windows, e.g.
+    /// `--disable-features=msSmartScreenProtection`. Anything not starting with `--` is dropped.
+    #[serde(default)]
+    pub webview2_args: Option<String>,
+    /// Smallest font size in pixels the page may render, macOS and Linux only.
+    #[serde(default)]
+    pub min_font_size: Option<u32>,
+    /// CSS font family forced on page text, e.g. `"Inter", sans-serif`.
+    /// Code and icon fonts are left alone.
+    #[serde(default)]
+    pub font_family: Option<String>,
+    /// Play/pause, next and previous in the tray, also bound to the OS media keys.
+    #[serde(default)]
+    pub media_controls: bool,
+    /// Opacity (0.2–1.0) the window fades to while unfocused, opaque again on focus.
+    #[serde(default)]
+    pub dim_on_blur: Option<f64>,
+    /// macOS: two-finger swipe for back and forward. Turn off for form-heavy
+    /// sites where a stray swipe would navigate away and lose input.
+    #[serde(default = "default_true")]
+    pub allow_swipe_navigation: bool,
+    /// Log DNS, connect, TTFB, DOMContentLoaded and load times for every page load.
+    #[serde(default)]
+    pub log_load_timing: bool,
+    #[serde(default)]
+    pub js_dialogs: JsDialogs,
+    /// Alternate deployments of the app, e.g. staging, switched from the tray or
+    /// `switch_environment`. Their hosts count as the app's own.
+    #[serde(default)]
+    pub environments: Vec<Environment>,
+    /// Class put on `<html>` and `<body>` while the system appearance is dark,
+    /// for sites that ignore `prefers-color-scheme`. See `inject/theme_sync.js`.
+    #[serde(default)]
+    pub dark_mode_class: Option<String>,
+    /// Keep a `<meta name="color-scheme">` in the page matching the app theme,
+    /// for sites that theme form controls and scrollbars from it.
+    #[serde(default)]
+    pub color_scheme_meta: bool,
+    /// Selector clicked once it appears after each load, e.g. a cookie banner's
+    /// dismiss button. Missing or invalid selectors are ignored.
+    #[serde(default)]
+    pub auto_click_on_load: Option<String>,
+    /// Accent-colored bar along the top edge showing how far the page is scrolled.
+    /// `set_reading_progress` toggles it at runtime.
+    #[serde(default)]
+    pub reading_progress: bool,
+    /// Pages on the same host as `url` handed out in turn: the main window opens
+    /// the first, each new window the next. `cycle_urls` rotates one window through them.
+    #[serde(default)]
+    pub home_urls: Vec<String>,
+    /// Accelerators for built-in actions, replacing the defaults from
+    /// `ShortcutAction::default_accelerator`. An empty string unbinds the action.
+    #[serde(default)]
+    pub shortcuts: HashMap<ShortcutAction, String>,
+    /// Pattern pulling an unread count out of the page title, e.g. `^\((\d+)\)`
+    /// for "(3) Inbox". The first capture group is the count; no match is zero.
+    #[serde(default)]
+    pub unread_title_regex: Option<String>,
+    /// Translation service used by `toggle_translate`.
+    #[serde(default)]
+    pub translate: Option<TranslateConfig>,
+}
+
+/// Endpoint for in-place page translation. It is POSTed LibreTranslate-style JSON,
+/// `{"q": [...], "source": "auto", "target": target_lang, "format": "text"}`, and
+/// must answer `{"translatedText": [...]}` in the same order.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranslateConfig {
+    pub target_lang: String,
+    pub endpoint: String,
+}
+
+/// Built-in action that `shortcuts` can rebind.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShortcutAction {
+    Reload,
+    Back,
+    Forward,
+    Home,
+    ZoomIn,
+    ZoomOut,
+    ZoomReset,
+    ReaderMode,
+    Devtools,
+    NewWindow,
+    CopyUrl,
+    PasteAndMatchStyle,
+    Preferences,
+    ClearCacheRestart,
+    ScrollTop,
+    ScrollBottom,
+    /// Global shortcut that shows or hides the app, defaulting to `activation_shortcut`.
+    Activate,
+}
+
+impl ShortcutAction {
+    pub const ALL: [ShortcutAction; 17] = [
+        ShortcutAction::Reload,
+        ShortcutAction::Back,
+        ShortcutAction::Forward,
+        ShortcutAction::Home,
+        ShortcutAction::ZoomIn,
+        ShortcutAction::ZoomOut,
+        ShortcutAction::ZoomReset,
+        ShortcutAction::ReaderMode,
+        ShortcutAction::Devtools,
+        ShortcutAction::NewWindow,
+        ShortcutAction::CopyUrl,
+        ShortcutAction::PasteAndMatchStyle,
+        ShortcutAction::Preferences,
+        ShortcutAction::ClearCacheRestart,
+        ShortcutAction::ScrollTop,
+        ShortcutAction::ScrollBottom,
+        ShortcutAction::Activate,
+    ];
+
+    /// Accelerator used when `shortcuts` leaves the action out.
+    pub fn default_accelerator(self) -> Option<&'static str> {
+        match self {
+            ShortcutAction::Reload => Some("CmdOrCtrl+R"),
+            ShortcutAction::Back => Some("CmdOrCtrl+["),
+            ShortcutAction::Forward => Some("CmdOrCtrl+]"),
+            ShortcutAction::Home => Some("CmdOrCtrl+Shift+H"),
+            ShortcutAction::ZoomIn => Some("CmdOrCtrl+="),
+            ShortcutAction::ZoomOut => Some("CmdOrCtrl+-"),
+            ShortcutAction::ZoomReset => Some("CmdOrCtrl+0"),
+            ShortcutAction::ReaderMode => Some("CmdOrCtrl+Shift+R"),
+            ShortcutAction::Devtools => Some("CmdOrCtrl+Option+I"),
+            ShortcutAction::NewWindow => Some("CmdOrCtrl+N"),
+            ShortcutAction::CopyUrl => Some("CmdOrCtrl+L"),
+            ShortcutAction::PasteAndMatchStyle => Some("CmdOrCtrl+Shift+Option+V"),
+            ShortcutAction::Preferences => Some("CmdOrCtrl+,"),
+            ShortcutAction::ClearCacheRestart => Some("CmdOrCtrl+Shift+Backspace"),
+            ShortcutAction::ScrollTop => Some("CmdOrCtrl+ArrowUp"),
+            ShortcutAction::ScrollBottom => Some("CmdOrCtrl+ArrowDown"),
+            ShortcutAction::Activate => None,
+        }
+    }
+}
+
+// Lowercase, alias-free and modifier-order-free, so `Ctrl+Shift+K` and
+// `shift+control+k` compare equal
+fn normalize_accelerator(accelerator: &str) -> String {
+    let mut parts: Vec<&str> = accelerator
+        .split('+')
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .collect();
+    let key = parts.pop().unwrap_or_default().to_lowercase();
+    let mut modifiers: Vec<&str> = parts
+        .into_iter()
+        .map(|part| match part.to_lowercase().as_str() {
+            "cmdorctrl" | "commandorcontrol" => "cmdorctrl",
+            "cmd" | "command" | "meta" | "super" => "cmd",
+            "ctrl" | "control" => "ctrl",
+            "alt" | "option" => "alt",
+            "shift" => "shift",
+            _ => "?",
+        })
+        .collect();
+    modifiers.sort_unstable();
+    modifiers.dedup();
+    modifiers.push(&key);
+    modifiers.join("+")
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SnapPosition {
+    Left,
+    Right,
+    Top,
+    Bottom,
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+/// Window edge or corner dragged by `start_resize`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResizeEdge {
+    Left,
+    Right,
+    Top,
+    Bottom,
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+/// Handling of non-web links such as `mailto:`, `tel:`, `sms:` or an app's own scheme.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExternalProtocols {
+    /// Hand the link to the system's handler for the scheme.
+    #[default]
+    Allow,
+    /// Drop the navigation.
+    Deny,
+    /// Ask before handing the link to the system.
+    Prompt,
+}
+
+/// How page `alert`, `confirm` and `prompt` calls are shown. See `inject/dialogs.js`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JsDialogs {
+    /// The webview's own blocking dialogs.
+    #[default]
+    Native,
+    /// App-styled dialogs that don't block the page: `alert` goes through
+    /// `show_js_dialog` and `prompt` is drawn in the page. Code after `alert`
+    /// runs while it is open, and `prompt` returns a Promise, so only sites that
+    /// `await` it see the answer. `confirm` stays native since its answer is
+    /// read synchronously.
+    Custom,
+    /// Dismiss without showing anything: `confirm` gives false, `prompt` null.
+    Suppress,
+}
+
+/// Scrollbar look injected into every page. Styles live in a cascade layer,
+/// so any scrollbar rules the site sets itself still win.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScrollbarStyle {
+    /// Leave the webview's scrollbars alone.
+    #[default]
+    Default,
+    /// Thin scrollbars with a transparent track.
+    Overlay,
+    /// No visible scrollbars; wheel, touch and keyboard scrolling still work.
+    Hidden,
+    /// Thumb tinted with `accent_color`.
+    Themed,
+}
+
+/// Screen corner the picture-in-picture window sits in.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PipCorner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    #[default]
+    BottomRight,
+}
+
+/// Where the window title comes from. `title_prefix` is prepended in both modes.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TitleMode {
+    /// The configured `title`, or the app name.
+    #[default]
+    Fixed,
+    /// The page's own `<title>`, updated whenever the page retitles itself.
+    FollowPage,
+}
+
+/// How `beforeunload` handlers registered by the page are treated.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BeforeUnloadBehavior {
+    /// Leave the webview's native behavior untouched.
+    #[default]
+    Allow,
+    /// Drop every handler so leaving the page never prompts.
+    Suppress,
+    /// Replace the native prompt with an app-level confirm when quitting by closing the
+    /// main window or through the tray's Quit. `force_quit` and "Quit (Force)" skip it.
+    Confirm,
+}
+
+/// Per-platform size overrides; unset values fall back to the top-level ones.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct WindowSizeOverride {
+    pub width: Option<f64>,
+    pub height: Option<f64>,
+    pub min_width: Option<f64>,
+    pub min_height: Option<f64>,
+    pub max_width: Option<f64>,
+    pub max_height: Option<f64>,
+}
+
+/// Form POST performed instead of the initial GET. `url` defaults to the window
+/// URL and must stay on the same host.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InitialPost {
+    #[serde(default)]
+    pub url: Option<String>,
+    #[serde(default)]
+    pub body: String,
+    #[serde(default = "default_post_content_type")]
+    pub content_type: String,
+}
+
+/// Bookmarklet-style entry in the "Scripts" menu. `js` may keep a `javascript:` prefix.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScriptMenuItem {
+    pub label: String,
+    pub js: String,
+}
+
+/// Entry in `init_scripts`. `js` may keep a `javascript:` prefix like menu scripts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InitScript {
+    pub js: String,
+    #[serde(default)]
+    pub run_at: RunAt,
+}
+
+/// When an `init_scripts` entry runs in each page.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunAt {
+    /// Before any page script, when only `document.documentElement` exists.
+    /// Needed to patch globals the page uses while loading.
+    #[default]
+    DocumentStart,
+    /// Once the DOM is parsed (`DOMContentLoaded`), before images and frames finish.
+    DocumentEnd,
+    /// After the page has fully loaded, evaluated by the app when loading finishes.
+    Load,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Environment {
+    pub name: String,
+    pub url: String,
+}
+
+/// `shortcut` is an in-page accelerator such as `CmdOrCtrl+Shift+]`, only
+/// active while the window has focus.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NamedAction {
+    pub js: String,
+    #[serde(default)]
+    pub shortcut: Option<String>,
+}
+
+/// Effective window geometry for the current platform. Zero min/max means unset.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WindowSize {
+    pub width: f64,
+    pub height: f64,
+    pub min_width: f64,
+    pub min_height: f64,
+    pub max_width: f64,
+    pub max_height: f64,
+}
+
+/// Stacking level for overlay-style windows. Anything above `Normal` implies always-on-top.
+/// Linux window managers generally refuse to stack above fullscreen apps, so
+/// `AboveFullscreen` behaves like `Floating` there; Windows treats both as topmost.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WindowLevel {
+    #[default]
+    Normal,
+    Floating,
+    AboveFullscreen,
+}
+
+impl WindowConfig {
+    /// Accelerator bound to `action`: its `shortcuts` entry, else the default.
+    /// `None` when the action is unbound.
+    pub fn shortcut(&self, action: ShortcutAction) -> Option<String> {
+        let accelerator = match self.shortcuts.get(&action) {
+            Some(accelerator) => accelerator.clone(),
+            None if action == ShortcutAction::Activate => self.activation_shortcut.clone(),
+            None => action.default_accelerator()?.to_string(),
+        };
+        let accelerator = accelerator.trim();
+        (!accelerator.is_empty()).then(|| accelerator.to_string())
+    }
+
+    /// Every bound action with its accelerator.
+    pub fn resolved_shortcuts(&self) -> HashMap<ShortcutAction, String> {
+        ShortcutAction::ALL
+            .into_iter()
+            .filter_map(|action| {
+                self.shortcut(action)
+                    .map(|accelerator| (action, accelerator))
+            })
+            .collect()
+    }
+
+    /// Accelerators bound to more than one action, with the actions sharing them.
+    pub fn shortcut_conflicts(&self) -> Vec<(String, Vec<ShortcutAction>)> {
+        let mut bound: HashMap<String, (String, Vec<ShortcutAction>)> = HashMap::new();
+        for action in ShortcutAction::ALL {
+            if let Some(accelerator) = self.shortcut(action) {
+                bound
+                    .entry(normalize_accelerator(&accelerator))
+                    .or_insert_with(|| (accelerator, Vec::new()))
+                    .1
+                    .push(action);
+            }
+        }
+        let mut conflicts: Vec<_> = bound
+            .into_values()
+            .filter(|(_, actions)| actions.len() > 1)
+            .collect();
+        conflicts.sort_by(|a, b| a.1.cmp(&b.1));
+        conflicts
+    }
+
+    /// Sizes are multiplied by `ui_scale` so scaled content keeps the same room.
+    pub fn window_size(&self) -> WindowSize {
+        let overrides = self.platform_overrides.get();
+        let scale = self.ui_scale().unwrap_or(1.0);
+        WindowSize {
+            width: overrides.width.unwrap_or(self.width) * scale,
+            height: overrides.height.unwrap_or(self.height) * scale,
+            min_width: overrides.min_width.unwrap_or(self.min_width) * scale,
+            min_height: overrides.min_height.unwrap_or(self.min_height) * scale,
+            max_width: overrides.max_width.unwrap_or(self.max_width) * scale,
+            max_height: overrides.max_height.unwrap_or(self.max_height) * scale,
+        }
+    }
+
+    /// `ui_scale` clamped to a usable range, `None` when unset, invalid or 1.
+    pub fn ui_scale(&self) -> Option<f64> {
+        self.ui_scale
+            .filter(|scale| scale.is_finite() && *scale > 0.0)
+            .map(|scale| scale.clamp(MIN_UI_SCALE, MAX_UI_SCALE))
+            .filter(|scale| (scale - 1.0).abs() > f64::EPSILON)
+    }
+
+    /// Usable `aspect_ratio`, ignoring zero, negative and non-finite values.
+    pub fn aspect_ratio(&self) -> Option<f64> {
+        self.aspect_ratio
+            .filter(|ratio| ratio.is_finite() && *ratio > 0.0)
+    }
+
+    /// Whether `content_type` is listed in `inline_content_types`, where a
+    /// trailing `/*` matches any subtype. Parameters like `; charset` are ignored.
+    pub fn opens_inline(&self, content_type: &str) -> bool {
+        let content_type = content_type
+            .split(';')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_ascii_lowercase();
+        if content_type.is_empty() {
+            return false;
+        }
+        self.inline_content_types.iter().any(|pattern| {
+            let pattern = pattern.trim().to_ascii_lowercase();
+            match pattern.strip_suffix('*') {
+                Some(prefix) if prefix.ends_with('/') => content_type.starts_with(prefix),
+                _ => content_type == pattern,
+            }
+        })
+    }
+
+    /// Hosts allowed to present invalid certificates. Falls back to the app's own host
+    /// so enabling `accept_invalid_certs` never disables TLS checks globally.
+    pub fn invalid_cert_hosts(&self) -> Vec<String> {
+        if !self.accept_invalid_certs {
+            return Vec::new();
+        }
+        if !self.invalid_cert_hosts.is_empty() {
+            return self.invalid_cert_hosts.clone();
+        }
+        tauri::Url::parse(&self.url)
+            .ok()
+            .and_then(|u| u.host_str().map(str::to_string))
+            .into_iter()
+            .collect()
+    }
+}
+
+/// Which window closes hide to the tray when `hide_on_close` is on.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HideOnCloseMode {
+    /// Every window hides instead of closing.
+    #[default]
+    Always,
+    /// Extra windows close for real; only the last open one hides.
+    LastWindowOnly,
+}
+
+/// Answer to a site's permission request.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PermissionPolicy {
+    Allow,
+    Deny,
+    /// Ask the user, or leave it to the webview where it has its own prompt.
+    #[default]
+    Prompt,
+}
+
+/// Per-capability permission policies. Notifications default to `Allow`, which is
+/// how the built-in `Notification` bridge has always behaved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PermissionsConfig {
+    #[serde(default)]
+    pub camera: PermissionPolicy,
+    #[serde(default)]
+    pub microphone: PermissionPolicy,
+    #[serde(default)]
+    pub geolocation: PermissionPolicy,
+    #[serde(default = "default_notification_policy")]
+    pub notifications: PermissionPolicy,
+}
+
+impl Default for PermissionsConfig {
+    fn default() -> Self {
+        Self {
+            camera: PermissionPolicy::Prompt,
+            microphone: PermissionPolicy::Prompt,
+            geolocation: PermissionPolicy::Prompt,
+            notifications: default_notification_policy(),
+        }
+    }
+}
+
+fn default_notification_policy() -> PermissionPolicy {
+    PermissionPolicy::Allow
+}
+
+/// What a tray icon click does while the window is fullscreen.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrayFullscreenBehavior {
+    /// Hide the window like any other visible state.
+    #[default]
+    Hide,
+    /// Leave fullscreen first, then hide once the exit animation is done.
+    ExitFullscreen,
+    /// Bring the window's fullscreen Space to the front instead of hiding it.
+    /// macOS only switches Spaces when the window is not already focused.
+    ActivateSpace,
 }
 
 fn default_zoom() -> u32 {
     100
 }
 
+/// `zoom` as a single percent, or as `{ "macos": 110, "windows": 100, "linux": 105 }`
+/// since each webview renders the same site at a slightly different size.
+/// Platforms left out of the object use 100.
 #[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ZoomConfig {
+    All(u32),
+    PerPlatform(PlatformSpecific<Option<u32>>),
+}
+
+impl Default for ZoomConfig {
+    fn default() -> Self {
+        ZoomConfig::All(default_zoom())
+    }
+}
+
+impl ZoomConfig {
+    /// Zoom percent for the platform this build targets.
+    pub fn percent(&self) -> u32 {
+        match self {
+            ZoomConfig::All(zoom) => *zoom,
+            ZoomConfig::PerPlatform(zooms) => zooms.copied().unwrap_or_else(default_zoom),
+        }
+    }
+}
+
+fn default_oauth_domains() -> Vec<String> {
+    [
+        "accounts.google.com",
+        "login.microsoftonline.com",
+        "github.com",
+        "appleid.apple.com",
+        "facebook.com",
+        "twitter.com",
+    ]
+    .map(String::from)
+    .to_vec()
+}
+
+fn default_post_content_type() -> String {
+    "application/x-www-form-urlencoded".to_string()
+}
+
+fn default_keepalive_interval_secs() -> u64 {
+    300
+}
+
+fn default_pip_width() -> f64 {
+    400.0
+}
+
+fn default_pip_height() -> f64 {
+    225.0
+}
+
+fn default_resize_handle_size() -> f64 {
+    6.0
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct PlatformSpecific<T> {
+    #[serde(default)]
     pub macos: T,
+    #[serde(default)]
     pub linux: T,
+    #[serde(default)]
     pub windows: T,
 }
 
@@ -76,13 +883,313 @@ pub struct PakeConfig {
     pub user_agent: UserAgent,
     pub system_tray: FunctionON,
     pub system_tray_path: String,
+    /// macOS: draw the tray icon as a template image that follows the menu bar's
+    /// light or dark look. Unset means on when the icon is monochrome.
+    #[serde(default)]
+    pub tray_icon_template: Option<bool>,
     pub proxy_url: String,
     #[serde(default)]
     pub multi_instance: bool,
+    #[serde(default)]
+    pub updater_endpoint: Option<String>,
+    #[serde(default)]
+    pub updater_pubkey: Option<String>,
+    #[serde(default)]
+    pub check_updates_on_launch: bool,
+    #[serde(default)]
+    pub menu: MenuConfig,
+    /// Overrides where Pake's state files and, on Windows and Linux, webview
+    /// storage live. `~` and environment variables are expanded; relative paths
+    /// resolve against the executable's directory for portable installs.
+    #[serde(default)]
+    pub data_dir: Option<String>,
+    /// Appended to the bundle identifier and data directory name so two builds
+    /// of the same site keep separate state and single-instance locks.
+    #[serde(default)]
+    pub app_id_suffix: Option<String>,
+}
+
+/// Which standard macOS menus are shown. Hiding Edit also drops the Cmd+C/V/X
+/// key equivalents, since macOS routes them through that menu.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MenuConfig {
+    #[serde(default = "default_true")]
+    pub edit: bool,
+    #[serde(default = "default_true")]
+    pub view: bool,
+    #[serde(default = "default_true")]
+    pub window: bool,
+    #[serde(default = "default_true")]
+    pub help: bool,
+    /// Adds Help → Report Issue opening this URL.
+    #[serde(default)]
+    pub report_issue_url: Option<String>,
+}
+
+impl Default for MenuConfig {
+    fn default() -> Self {
+        Self {
+            edit: true,
+            view: true,
+            window: true,
+            help: true,
+            report_issue_url: None,
+        }
+    }
 }
 
 impl PakeConfig {
     pub fn show_system_tray(&self) -> bool {
         self.system_tray.copied()
     }
+
+    pub fn updates_enabled(&self) -> bool {
+        self.updater_endpoint.is_some() && self.updater_pubkey.is_some()
+    }
+}
+
+/// A `WindowConfig` with the required fields filled in and `fields` on top.
+#[cfg(test)]
+pub fn test_window_config(fields: serde_json::Value) -> WindowConfig {
+    let mut config = serde_json::json!({
+        "url": "https://example.com/",
+        "hide_title_bar": false,
+        "fullscreen": false,
+        "maximize": false,
+        "width": 1200.0,
+        "height": 780.0,
+        "resizable": true,
+        "url_type": "web",
+        "always_on_top": false,
+        "dark_mode": false,
+        "disabled_web_shortcuts": false,
+        "activation_shortcut": "",
+        "hide_on_close": false,
+        "incognito": false,
+        "title": null,
+        "enable_wasm": false,
+        "enable_drag_drop": false,
+        "start_to_tray": false
+    });
+    if let (Some(config), serde_json::Value::Object(fields)) = (config.as_object_mut(), fields) {
+        config.extend(fields);
+    }
+    serde_json::from_value(config).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const PLATFORMS: [&str; 3] = ["macos", "linux", "windows"];
+
+    fn current_platform() -> &'static str {
+        if cfg!(target_os = "macos") {
+            "macos"
+        } else if cfg!(target_os = "windows") {
+            "windows"
+        } else {
+            "linux"
+        }
+    }
+
+    #[test]
+    fn window_size_without_overrides_uses_top_level_values() {
+        let config = test_window_config(json!({ "min_width": 400.0, "max_width": 1600.0 }));
+        assert_eq!(
+            config.window_size(),
+            WindowSize {
+                width: 1200.0,
+                height: 780.0,
+                min_width: 400.0,
+                min_height: 0.0,
+                max_width: 1600.0,
+                max_height: 0.0,
+            }
+        );
+    }
+
+    #[test]
+    fn platform_override_wins_per_field() {
+        let config = test_window_config(json!({
+            "min_width": 400.0,
+            "platform_overrides": { current_platform(): { "width": 900.0, "min_width": 600.0 } }
+        }));
+        let size = config.window_size();
+        assert_eq!(size.width, 900.0);
+        assert_eq!(size.min_width, 600.0);
+        // Fields the override leaves unset fall back to the top-level ones
+        assert_eq!(size.height, 780.0);
+        assert_eq!(size.max_width, 0.0);
+    }
+
+    #[test]
+    fn other_platforms_overrides_are_ignored() {
+        let others: serde_json::Map<String, serde_json::Value> = PLATFORMS
+            .into_iter()
+            .filter(|platform| *platform != current_platform())
+            .map(|platform| (platform.to_string(), json!({ "width": 500.0 })))
+            .collect();
+        let config = test_window_config(json!({ "platform_overrides": others }));
+        assert_eq!(config.window_size().width, 1200.0);
+    }
+
+    #[test]
+    fn ui_scale_applies_after_overrides() {
+        let config = test_window_config(json!({
+            "ui_scale": 2.0,
+            "platform_overrides": { current_platform(): { "width": 900.0 } }
+        }));
+        let size = config.window_size();
+        assert_eq!(size.width, 1800.0);
+        assert_eq!(size.height, 1560.0);
+    }
+
+    #[test]
+    fn permissions_default_to_prompt_with_notifications_allowed() {
+        let expected = PermissionsConfig {
+            camera: PermissionPolicy::Prompt,
+            microphone: PermissionPolicy::Prompt,
+            geolocation: PermissionPolicy::Prompt,
+            notifications: PermissionPolicy::Allow,
+        };
+        assert_eq!(PermissionsConfig::default(), expected);
+        assert_eq!(
+            serde_json::from_value::<PermissionsConfig>(json!({})).unwrap(),
+            expected
+        );
+        assert_eq!(test_window_config(json!({})).permissions, expected);
+    }
+
+    #[test]
+    fn permissions_parse_each_policy() {
+        let permissions: PermissionsConfig = serde_json::from_value(json!({
+            "camera": "allow",
+            "microphone": "deny",
+            "notifications": "prompt"
+        }))
+        .unwrap();
+        assert_eq!(permissions.camera, PermissionPolicy::Allow);
+        assert_eq!(permissions.microphone, PermissionPolicy::Deny);
+        assert_eq!(permissions.geolocation, PermissionPolicy::Prompt);
+        assert_eq!(permissions.notifications, PermissionPolicy::Prompt);
+    }
+
+    #[test]
+    fn permissions_reject_unknown_policies() {
+        assert!(serde_json::from_value::<PermissionsConfig>(json!({ "camera": "ask" })).is_err());
+        assert!(serde_json::from_value::<PermissionsConfig>(json!({ "camera": "Allow" })).is_err());
+    }
+
+    #[test]
+    fn zoom_accepts_a_single_percent() {
+        let zoom: ZoomConfig = serde_json::from_value(json!(125)).unwrap();
+        assert!(matches!(zoom, ZoomConfig::All(125)));
+        assert_eq!(zoom.percent(), 125);
+    }
+
+    #[test]
+    fn zoom_accepts_per_platform_percents() {
+        let zoom: ZoomConfig =
+            serde_json::from_value(json!({ "macos": 110, "linux": 105, "windows": 90 })).unwrap();
+        assert!(matches!(zoom, ZoomConfig::PerPlatform(_)));
+        let expected = match current_platform() {
+            "macos" => 110,
+            "windows" => 90,
+            _ => 105,
+        };
+        assert_eq!(zoom.percent(), expected);
+    }
+
+    #[test]
+    fn zoom_falls_back_to_100() {
+        assert_eq!(ZoomConfig::default().percent(), 100);
+        assert_eq!(test_window_config(json!({})).zoom.percent(), 100);
+        let others: serde_json::Map<String, serde_json::Value> = PLATFORMS
+            .into_iter()
+            .filter(|platform| *platform != current_platform())
+            .map(|platform| (platform.to_string(), json!(150)))
+            .collect();
+        let zoom: ZoomConfig = serde_json::from_value(others.into()).unwrap();
+        assert_eq!(zoom.percent(), 100);
+    }
+
+    #[test]
+    fn zoom_rejects_other_shapes() {
+        assert!(serde_json::from_value::<ZoomConfig>(json!("110%")).is_err());
+        assert!(serde_json::from_value::<ZoomConfig>(json!(-5)).is_err());
+    }
+
+    #[test]
+    fn accelerators_normalize_modifier_names_and_order() {
+        assert_eq!(normalize_accelerator("CmdOrCtrl+K"), "cmdorctrl+k");
+        assert_eq!(normalize_accelerator("commandorcontrol+k"), "cmdorctrl+k");
+        assert_eq!(
+            normalize_accelerator("Shift + Option + Cmd + V"),
+            normalize_accelerator("command+alt+shift+v")
+        );
+        assert_ne!(
+            normalize_accelerator("Ctrl+K"),
+            normalize_accelerator("CmdOrCtrl+K")
+        );
+    }
+
+    #[test]
+    fn shortcut_conflicts_match_across_spellings() {
+        let config = test_window_config(json!({
+            "shortcuts": { "reader_mode": "CmdOrCtrl+K", "copy_url": "commandorcontrol+k" }
+        }));
+        assert_eq!(
+            config.shortcut_conflicts(),
+            [(
+                "CmdOrCtrl+K".to_string(),
+                vec![ShortcutAction::ReaderMode, ShortcutAction::CopyUrl]
+            )]
+        );
+    }
+
+    #[test]
+    fn rebinding_onto_a_default_conflicts() {
+        let config = test_window_config(json!({ "shortcuts": { "home": "CmdOrCtrl+R" } }));
+        let conflicts = config.shortcut_conflicts();
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(
+            conflicts[0].1,
+            [ShortcutAction::Reload, ShortcutAction::Home]
+        );
+    }
+
+    #[test]
+    fn defaults_and_unbound_actions_do_not_conflict() {
+        assert!(test_window_config(json!({}))
+            .shortcut_conflicts()
+            .is_empty());
+
+        let config = test_window_config(json!({
+            "activation_shortcut": "CmdOrCtrl+Shift+P",
+            "shortcuts": { "reload": "", "home": "CmdOrCtrl+R" }
+        }));
+        assert!(config.shortcut_conflicts().is_empty());
+        assert_eq!(config.shortcut(ShortcutAction::Reload), None);
+        assert_eq!(
+            config.shortcut(ShortcutAction::Activate).as_deref(),
+            Some("CmdOrCtrl+Shift+P")
+        );
+    }
+
+    #[test]
+    fn inline_content_types_match_exactly_or_by_wildcard() {
+        let config = test_window_config(json!({
+            "inline_content_types": ["application/pdf", " Image/* "]
+        }));
+        assert!(config.opens_inline("application/pdf"));
+        assert!(config.opens_inline("APPLICATION/PDF; name=report.pdf"));
+        assert!(config.opens_inline("image/png"));
+        assert!(config.opens_inline("image/svg+xml"));
+        assert!(!config.opens_inline("application/zip"));
+        assert!(!config.opens_inline("imagex/png"));
+        assert!(!config.opens_inline(""));
+        assert!(!test_window_config(json!({})).opens_inline("application/pdf"));
+    }
 }
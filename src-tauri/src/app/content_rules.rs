@@ -0,0 +1,393 @@
+use crate::app::setup::refresh_tray_tooltip;
+use crate::util::{get_pake_config, load_app_state, save_app_state};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{LazyLock, Mutex};
+use tauri::{AppHandle, Manager, WebviewWindow};
+
+const ADBLOCK_STATE_KEY: &str = "adblock";
+
+// Compact EasyList-style list bundled for `adblock`, kept apart from user rules
+static ADBLOCK_RULES: LazyLock<Vec<ContentRule>> =
+    LazyLock::new(|| parse_easylist(include_str!("adblock.txt")));
+
+static BLOCKED_REQUESTS: AtomicU64 = AtomicU64::new(0);
+
+/// A single rule in WebKit content-rule-list form, which is also what gets
+/// compiled natively on macOS and handed to the injected fallback.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContentRule {
+    pub trigger: RuleTrigger,
+    pub action: RuleAction,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleTrigger {
+    #[serde(rename = "url-filter")]
+    pub url_filter: String,
+    #[serde(rename = "if-domain", default, skip_serializing_if = "Vec::is_empty")]
+    pub if_domain: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum RuleAction {
+    Block,
+    CssDisplayNone { selector: String },
+}
+
+struct ActiveRules {
+    /// Rules from `content_block_rules` or `set_content_rules`.
+    custom: Vec<ContentRule>,
+    adblock: bool,
+    /// `custom` plus the bundled list when `adblock` is on.
+    rules: Vec<ContentRule>,
+    block_patterns: Vec<Regex>,
+}
+
+static ACTIVE_RULES: LazyLock<Mutex<ActiveRules>> = LazyLock::new(|| {
+    Mutex::new(ActiveRules {
+        custom: Vec::new(),
+        adblock: false,
+        rules: Vec::new(),
+        block_patterns: Vec::new(),
+    })
+});
+
+/// Parse either a WebKit content-rule-list JSON array or a subset of EasyList:
+/// `||domain^` blocks, `##selector` and `domain.com##selector` hide elements,
+/// plain lines block URLs containing them. Exceptions (`@@`) are not supported.
+pub fn parse_rules(source: &str) -> Result<Vec<ContentRule>, String> {
+    let rules = if source.trim_start().starts_with('[') {
+        serde_json::from_str(source).map_err(|e| format!("Invalid content rule list: {}", e))?
+    } else {
+        parse_easylist(source)
+    };
+
+    for rule in &rules {
+        Regex::new(&rule.trigger.url_filter)
+            .map_err(|e| format!("Invalid url-filter {}: {}", rule.trigger.url_filter, e))?;
+    }
+
+    Ok(rules)
+}
+
+fn parse_easylist(source: &str) -> Vec<ContentRule> {
+    source
+        .lines()
+        .map(str::trim)
+        .filter(|line| {
+            !line.is_empty()
+                && !line.starts_with('!')
+                && !line.starts_with('[')
+                && !line.starts_with("@@")
+        })
+        .flat_map(|line| {
+            if let Some((domains, selector)) = line.split_once("##") {
+                let if_domain = domains
+                    .split(',')
+                    .filter(|d| !d.is_empty())
+                    .map(|d| format!("*{}", d))
+                    .collect();
+                return vec![ContentRule {
+                    trigger: RuleTrigger {
+                        url_filter: ".*".to_string(),
+                        if_domain,
+                    },
+                    action: RuleAction::CssDisplayNone {
+                        selector: selector.to_string(),
+                    },
+                }];
+            }
+
+            let pattern = line.split('$').next().unwrap_or(line);
+            let url_filters = match pattern.strip_prefix("||") {
+                Some(target) => domain_anchor_filters(target),
+                None => vec![regex::escape(pattern.trim_matches('|'))],
+            };
+            url_filters
+                .into_iter()
+                .filter(|url_filter| !url_filter.is_empty())
+                .map(|url_filter| ContentRule {
+                    trigger: RuleTrigger {
+                        url_filter,
+                        if_domain: Vec::new(),
+                    },
+                    action: RuleAction::Block,
+                })
+                .collect()
+        })
+        .collect()
+}
+
+// `||host^` matches the host and its subdomains up to a separator, so `||ads.com^`
+// leaves `ads.company.com` alone. WebKit filters have no alternation, so the
+// `([/:?]|$)` ending becomes two rules. A rule with a path and no trailing `^`
+// matches as a prefix.
+fn domain_anchor_filters(target: &str) -> Vec<String> {
+    let anchored = target.ends_with('^') || !target.contains('/');
+    let target = target.trim_end_matches('^');
+    if target.is_empty() {
+        return Vec::new();
+    }
+    let prefix = format!("^[a-z]+://([^/]+\\.)?{}", regex::escape(target));
+    if !anchored {
+        return vec![prefix];
+    }
+    vec![format!("{}[/:?]", prefix), format!("{}$", prefix)]
+}
+
+pub fn load_rules_file(path: &str) -> Result<Vec<ContentRule>, String> {
+    let source = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read content rules {}: {}", path, e))?;
+    parse_rules(&source)
+}
+
+pub fn active_rules() -> Vec<ContentRule> {
+    ACTIVE_RULES.lock().unwrap().rules.clone()
+}
+
+pub fn is_blocked(url: &str) -> bool {
+    ACTIVE_RULES
+        .lock()
+        .unwrap()
+        .block_patterns
+        .iter()
+        .any(|pattern| pattern.is_match(url))
+}
+
+/// Replace the custom rules, compile them natively where supported and refresh
+/// the injected fallback in the current page.
+pub fn apply_rules(window: &WebviewWindow, rules: Vec<ContentRule>) {
+    ACTIVE_RULES.lock().unwrap().custom = rules;
+    refresh_rules(window);
+}
+
+/// Turn the bundled ad-block list on or off alongside the custom rules.
+pub fn apply_adblock(window: &WebviewWindow, enabled: bool) {
+    ACTIVE_RULES.lock().unwrap().adblock = enabled;
+    refresh_rules(window);
+}
+
+fn refresh_rules(window: &WebviewWindow) {
+    let rules_json = {
+        let mut active = ACTIVE_RULES.lock().unwrap();
+        let mut rules = active.custom.clone();
+        if active.adblock {
+            rules.extend(ADBLOCK_RULES.iter().cloned());
+        }
+        active.block_patterns = rules
+            .iter()
+            .filter(|rule| matches!(rule.action, RuleAction::Block))
+            .filter_map(|rule| Regex::new(&rule.trigger.url_filter).ok())
+            .collect();
+        let rules_json = serde_json::to_string(&rules).unwrap();
+        active.rules = rules;
+        rules_json
+    };
+
+    #[cfg(target_os = "macos")]
+    compile_native_rules(window, rules_json.clone());
+
+    let _ = window.eval(&format!("window.pakeSetContentRules?.({})", rules_json));
+}
+
+/// Whether the bundled ad-block list is on: the last `toggle_adblock`, else
+/// `adblock` from pake.json.
+pub fn adblock_enabled(app: &AppHandle) -> bool {
+    load_app_state(app, ADBLOCK_STATE_KEY)
+        .and_then(|value| value.as_bool())
+        .unwrap_or_else(|| get_pake_config().0.windows[0].adblock)
+}
+
+/// Apply the bundled ad-block list to the main window and remember the choice.
+pub fn set_adblock(app: &AppHandle, enabled: bool) -> Result<(), String> {
+    let window = app.get_webview_window("pake").ok_or("Window not found")?;
+    save_app_state(app, ADBLOCK_STATE_KEY, enabled.into())?;
+    apply_adblock(&window, enabled);
+    Ok(())
+}
+
+pub fn blocked_requests() -> u64 {
+    BLOCKED_REQUESTS.load(Ordering::Relaxed)
+}
+
+/// Add to the blocked request count shown in the tray tooltip. macOS rule lists
+/// block without telling anyone, so there only page-script requests are counted.
+pub fn record_blocked(app: &AppHandle, count: u64) {
+    if count == 0 {
+        return;
+    }
+    BLOCKED_REQUESTS.fetch_add(count, Ordering::Relaxed);
+    refresh_tray_tooltip(app);
+}
+
+/// WebView2 has no rule lists, so requests are matched against the active rules
+/// one by one. Installed once; later rule changes only swap the shared patterns.
+#[cfg(target_os = "windows")]
+pub fn install_request_filter(window: &WebviewWindow) {
+    let app = window.app_handle().clone();
+    let result = window.with_webview(move |webview| unsafe {
+        use webview2_com::Microsoft::Web::WebView2::Win32::COREWEBVIEW2_WEB_RESOURCE_CONTEXT_ALL;
+        use webview2_com::{take_pwstr, WebResourceRequestedEventHandler};
+        use windows::core::{w, PWSTR};
+        use windows::Win32::System::Com::IStream;
+
+        let Ok(core) = webview.controller().CoreWebView2() else {
+            return;
+        };
+        let environment = webview.environment().clone();
+        let _ = core.AddWebResourceRequestedFilter(w!("*"), COREWEBVIEW2_WEB_RESOURCE_CONTEXT_ALL);
+
+        let handler = WebResourceRequestedEventHandler::create(Box::new(move |_, args| {
+            let Some(args) = args else {
+                return Ok(());
+            };
+            let mut uri = PWSTR::null();
+            args.Request()?.Uri(&mut uri)?;
+            if is_blocked(&take_pwstr(uri)) {
+                let response = environment.CreateWebResourceResponse(
+                    None::<&IStream>,
+                    403,
+                    w!("Blocked"),
+                    w!(""),
+                )?;
+                args.SetResponse(&response)?;
+                record_blocked(&app, 1);
+            }
+            Ok(())
+        }));
+        let mut token = 0;
+        let _ = core.add_WebResourceRequested(&handler, &mut token);
+    });
+
+    if let Err(e) = result {
+        log::error!("Failed to install content blocking filter: {}", e);
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn compile_native_rules(window: &WebviewWindow, rules_json: String) {
+    let result = window.with_webview(move |webview| unsafe {
+        use block2::RcBlock;
+        use objc2_foundation::{NSError, NSString};
+        use objc2_web_kit::{WKContentRuleList, WKContentRuleListStore, WKWebView};
+
+        let wk_webview: &WKWebView = &*webview.inner().cast();
+        let controller = wk_webview.configuration().userContentController();
+        controller.removeAllContentRuleLists();
+        if rules_json == "[]" {
+            return;
+        }
+
+        let Some(store) = WKContentRuleListStore::defaultStore() else {
+            return;
+        };
+        let handler = RcBlock::new(move |list: *mut WKContentRuleList, error: *mut NSError| {
+            if let Some(list) = list.as_ref() {
+                controller.addContentRuleList(list);
+            } else if let Some(error) = error.as_ref() {
+                log::error!(
+                    "Failed to compile content rules: {}",
+                    error.localizedDescription()
+                );
+            }
+        });
+        store.compileContentRuleListForIdentifier_encodedContentRuleList_completionHandler(
+            Some(&NSString::from_str("pake-content-rules")),
+            Some(&NSString::from_str(&rules_json)),
+            Some(&handler),
+        );
+    });
+
+    if let Err(e) = result {
+        log::error!("Failed to apply content rules: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn blocks(rules: &[ContentRule], url: &str) -> bool {
+        rules.iter().any(|rule| {
+            matches!(rule.action, RuleAction::Block)
+                && Regex::new(&rule.trigger.url_filter).unwrap().is_match(url)
+        })
+    }
+
+    #[test]
+    fn domain_rule_matches_host_and_subdomains() {
+        let rules = parse_rules("||ads.com^").unwrap();
+        for url in [
+            "https://ads.com",
+            "https://ads.com/banner.js",
+            "https://ads.com:8443/",
+            "https://ads.com?id=1",
+            "http://cdn.ads.com/x.gif",
+        ] {
+            assert!(blocks(&rules, url), "{url}");
+        }
+    }
+
+    #[test]
+    fn domain_rule_does_not_match_lookalike_hosts() {
+        let rules = parse_rules("||ads.com^").unwrap();
+        for url in [
+            "https://ads.company.com/",
+            "https://ads.com.example.net/",
+            "https://notads.com/",
+            "https://example.com/ads.com",
+        ] {
+            assert!(!blocks(&rules, url), "{url}");
+        }
+    }
+
+    #[test]
+    fn domain_rule_without_separator_is_anchored_too() {
+        let rules = parse_rules("||tracker.io").unwrap();
+        assert!(blocks(&rules, "https://tracker.io/pixel"));
+        assert!(!blocks(&rules, "https://tracker.ionic.dev/"));
+    }
+
+    #[test]
+    fn domain_rule_with_path_matches_as_prefix() {
+        let rules = parse_rules("||example.com/ads").unwrap();
+        assert!(blocks(&rules, "https://example.com/ads/banner.gif"));
+        assert!(blocks(&rules, "https://example.com/adserver"));
+        assert!(!blocks(&rules, "https://example.com/news"));
+
+        let rules = parse_rules("||example.com/ads^").unwrap();
+        assert!(blocks(&rules, "https://example.com/ads/banner.gif"));
+        assert!(!blocks(&rules, "https://example.com/adserver"));
+    }
+
+    #[test]
+    fn options_and_comments_are_ignored() {
+        let rules =
+            parse_rules("! comment\n[Adblock Plus 2.0]\n@@||ok.com^\n||ads.com^$third-party")
+                .unwrap();
+        assert!(blocks(&rules, "https://ads.com/x"));
+        assert!(!blocks(&rules, "https://ok.com/x"));
+    }
+
+    #[test]
+    fn hiding_rules_become_css_display_none() {
+        let rules = parse_rules("example.com,foo.org##.banner").unwrap();
+        assert_eq!(rules.len(), 1);
+        assert_eq!(rules[0].trigger.if_domain, ["*example.com", "*foo.org"]);
+        assert!(matches!(
+            &rules[0].action,
+            RuleAction::CssDisplayNone { selector } if selector == ".banner"
+        ));
+    }
+
+    #[test]
+    fn bundled_adblock_list_compiles() {
+        assert!(!ADBLOCK_RULES.is_empty());
+        for rule in ADBLOCK_RULES.iter() {
+            assert!(Regex::new(&rule.trigger.url_filter).is_ok(), "{rule:?}");
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use crate::util::{load_app_state, save_app_state};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::menu::{MenuItem, Submenu};
+use tauri::{AppHandle, Manager, Wry};
+use tauri_plugin_opener::OpenerExt;
+
+const DOWNLOAD_HISTORY_STATE_KEY: &str = "download_history";
+const DOWNLOAD_ID_PREFIX: &str = "download_";
+
+// Older entries fall off the end of the history
+const MAX_DOWNLOAD_HISTORY: usize = 200;
+const TRAY_RECENT_DOWNLOADS: usize = 10;
+
+/// One finished download, as listed by `get_download_history`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DownloadRecord {
+    /// Source URL, `None` for files the page handed over as bytes.
+    pub url: Option<String>,
+    pub filename: String,
+    pub path: String,
+    /// Bytes on disk once the download finished.
+    pub size: u64,
+    /// Seconds since the Unix epoch.
+    pub timestamp: u64,
+}
+
+/// Tray "Recent Downloads" submenu, kept in app state so new downloads show up in it.
+pub struct DownloadsSubmenu(pub Submenu<Wry>);
+
+/// Every recorded download, newest first.
+pub fn history(app: &AppHandle) -> Vec<DownloadRecord> {
+    load_app_state(app, DOWNLOAD_HISTORY_STATE_KEY)
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+}
+
+fn save(app: &AppHandle, history: &[DownloadRecord]) -> Result<(), String> {
+    save_app_state(
+        app,
+        DOWNLOAD_HISTORY_STATE_KEY,
+        serde_json::to_value(history).unwrap(),
+    )?;
+    if let Some(submenu) = app.try_state::<DownloadsSubmenu>() {
+        if let Err(e) = fill_submenu(app, &submenu.0, history) {
+            log::warn!("Failed to update recent downloads: {}", e);
+        }
+    }
+    Ok(())
+}
+
+/// Add the file just saved at `path` to the front of the history.
+pub fn record(app: &AppHandle, url: Option<String>, path: &str) -> Result<(), String> {
+    let record = DownloadRecord {
+        url,
+        filename: Path::new(path)
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| path.to_string()),
+        path: path.to_string(),
+        size: std::fs::metadata(path).map(|meta| meta.len()).unwrap_or(0),
+        timestamp: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or(0),
+    };
+    let mut history = history(app);
+    history.insert(0, record);
+    history.truncate(MAX_DOWNLOAD_HISTORY);
+    save(app, &history)
+}
+
+pub fn clear(app: &AppHandle) -> Result<(), String> {
+    save(app, &[])
+}
+
+fn fill_submenu(
+    app: &AppHandle,
+    submenu: &Submenu<Wry>,
+    history: &[DownloadRecord],
+) -> tauri::Result<()> {
+    for item in submenu.items()? {
+        submenu.remove(&item)?;
+    }
+    if history.is_empty() {
+        let empty = MenuItem::new(app, "No Downloads", false, None::<&str>)?;
+        return submenu.append(&empty);
+    }
+    for (index, record) in history.iter().take(TRAY_RECENT_DOWNLOADS).enumerate() {
+        let item = MenuItem::with_id(
+            app,
+            format!("{}{}", DOWNLOAD_ID_PREFIX, index),
+            &record.filename,
+            true,
+            None::<&str>,
+        )?;
+        submenu.append(&item)?;
+    }
+    Ok(())
+}
+
+/// "Recent Downloads" submenu listing the newest downloads; clicking one reveals the file.
+pub fn downloads_submenu(app: &AppHandle<Wry>) -> tauri::Result<Submenu<Wry>> {
+    let submenu = Submenu::new(app, "Recent Downloads", true)?;
+    fill_submenu(app, &submenu, &history(app))?;
+    app.manage(DownloadsSubmenu(submenu.clone()));
+    Ok(submenu)
+}
+
+/// Reveal the download behind a menu id in the file manager. Returns `false` for other ids.
+pub fn handle_download_menu_event(app: &AppHandle, id: &str) -> bool {
+    let Some(index) = id
+        .strip_prefix(DOWNLOAD_ID_PREFIX)
+        .and_then(|index| index.parse::<usize>().ok())
+    else {
+        return false;
+    };
+    if let Some(record) = history(app).get(index) {
+        if let Err(e) = app.opener().reveal_item_in_dir(&record.path) {
+            log::warn!("Failed to reveal download: {}", e);
+        }
+    }
+    true
+}
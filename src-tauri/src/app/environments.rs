@@ -0,0 +1,121 @@
+use std::str::FromStr;
+use std::sync::Mutex;
+use tauri::menu::{CheckMenuItem, Submenu};
+use tauri::{AppHandle, Manager, Url, Wry};
+
+use crate::app::config::{Environment, WindowConfig};
+use crate::util::{get_pake_config, host_matches, load_app_state, save_app_state};
+
+const ENVIRONMENT_ID_PREFIX: &str = "environment_";
+const ENVIRONMENT_STATE_KEY: &str = "environment";
+
+// Host of the environment switched to, replacing the configured one for validation
+static ACTIVE_HOST: Mutex<Option<String>> = Mutex::new(None);
+
+/// Tray environment items, kept in app state so switching moves the check mark.
+pub struct EnvironmentMenuItems(pub Vec<CheckMenuItem<Wry>>);
+
+fn host_of(url: &str) -> Option<String> {
+    Url::from_str(url)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_string))
+}
+
+/// The host pages are validated against: the active environment's, else that of `url`.
+pub fn allowed_host(window_config: &WindowConfig) -> Option<String> {
+    ACTIVE_HOST
+        .lock()
+        .unwrap()
+        .clone()
+        .or_else(|| host_of(&window_config.url))
+}
+
+/// Whether `host` belongs to any of `environments`, subdomains included.
+pub fn is_environment_host(environments: &[Environment], host: &str) -> bool {
+    environments
+        .iter()
+        .filter_map(|environment| host_of(&environment.url))
+        .any(|environment_host| host_matches(host, &environment_host))
+}
+
+fn find<'a>(window_config: &'a WindowConfig, name: &str) -> Result<&'a Environment, String> {
+    window_config
+        .environments
+        .iter()
+        .find(|environment| environment.name == name)
+        .ok_or_else(|| format!("Unknown environment: {}", name))
+}
+
+/// Start URL of the environment used last, which also becomes the allowed host.
+pub fn restore(app: &AppHandle, window_config: &WindowConfig) -> Option<Url> {
+    let name = load_app_state(app, ENVIRONMENT_STATE_KEY)?;
+    let environment = find(window_config, name.as_str()?).ok()?;
+    let url = Url::from_str(&environment.url).ok()?;
+    *ACTIVE_HOST.lock().unwrap() = url.host_str().map(str::to_string);
+    Some(url)
+}
+
+/// Navigate the main window to environment `name` and remember the choice.
+pub fn switch(app: &AppHandle, name: &str) -> Result<(), String> {
+    let (config, _) = get_pake_config();
+    let environment = find(&config.windows[0], name)?;
+    let url = Url::from_str(&environment.url)
+        .map_err(|e| format!("Invalid URL for environment {}: {}", name, e))?;
+    let window = app.get_webview_window("pake").ok_or("Window not found")?;
+
+    *ACTIVE_HOST.lock().unwrap() = url.host_str().map(str::to_string);
+    save_app_state(app, ENVIRONMENT_STATE_KEY, name.into())?;
+    if let Some(items) = app.try_state::<EnvironmentMenuItems>() {
+        for (item, environment) in items.0.iter().zip(&config.windows[0].environments) {
+            let _ = item.set_checked(environment.name == name);
+        }
+    }
+    window
+        .navigate(url)
+        .map_err(|e| format!("Failed to switch environment: {}", e))
+}
+
+/// "Environment" submenu with one checkable item per entry, `None` when there are none.
+pub fn environments_submenu(
+    app: &AppHandle<Wry>,
+    window_config: &WindowConfig,
+) -> tauri::Result<Option<Submenu<Wry>>> {
+    if window_config.environments.is_empty() {
+        return Ok(None);
+    }
+    let active = load_app_state(app, ENVIRONMENT_STATE_KEY)
+        .and_then(|name| name.as_str().map(str::to_string));
+    let submenu = Submenu::new(app, "Environment", true)?;
+    let mut items = Vec::new();
+    for (index, environment) in window_config.environments.iter().enumerate() {
+        let item = CheckMenuItem::with_id(
+            app,
+            format!("{}{}", ENVIRONMENT_ID_PREFIX, index),
+            &environment.name,
+            true,
+            active.as_deref() == Some(environment.name.as_str()),
+            None::<&str>,
+        )?;
+        submenu.append(&item)?;
+        items.push(item);
+    }
+    app.manage(EnvironmentMenuItems(items));
+    Ok(Some(submenu))
+}
+
+/// Switch to the environment behind a menu id. Returns `false` for other ids.
+pub fn handle_environment_menu_event(app: &AppHandle, id: &str) -> bool {
+    let Some(index) = id
+        .strip_prefix(ENVIRONMENT_ID_PREFIX)
+        .and_then(|index| index.parse::<usize>().ok())
+    else {
+        return false;
+    };
+    let environments = get_pake_config().0.windows[0].environments.clone();
+    if let Some(environment) = environments.get(index) {
+        if let Err(e) = switch(app, &environment.name) {
+            log::error!("{}", e);
+        }
+    }
+    true
+}
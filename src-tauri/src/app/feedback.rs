@@ -0,0 +1,94 @@
+use serde::Deserialize;
+use tauri::AppHandle;
+
+/// Named feedback events pages can trigger through `play_feedback`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FeedbackKind {
+    Tap,
+    Send,
+    Receive,
+    Success,
+    Error,
+}
+
+/// Play the platform sound for `kind`, plus a trackpad haptic on macOS.
+/// Linux relies on libcanberra's `canberra-gtk-play` and stays silent without it.
+pub fn play(app: &AppHandle, kind: FeedbackKind) -> Result<(), String> {
+    #[cfg(target_os = "macos")]
+    {
+        app.run_on_main_thread(move || play_macos(kind))
+            .map_err(|e| format!("Failed to play feedback: {}", e))?;
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        let _ = app;
+        play_windows(kind)?;
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        let _ = app;
+        play_linux(kind);
+    }
+
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+fn play_macos(kind: FeedbackKind) {
+    use objc2_app_kit::{
+        NSHapticFeedbackManager, NSHapticFeedbackPattern, NSHapticFeedbackPerformanceTime,
+        NSHapticFeedbackPerformer, NSSound,
+    };
+    use objc2_foundation::NSString;
+
+    let (sound, pattern) = match kind {
+        FeedbackKind::Tap => ("Tink", NSHapticFeedbackPattern::Generic),
+        FeedbackKind::Send => ("Pop", NSHapticFeedbackPattern::Generic),
+        FeedbackKind::Receive => ("Purr", NSHapticFeedbackPattern::Alignment),
+        FeedbackKind::Success => ("Glass", NSHapticFeedbackPattern::LevelChange),
+        FeedbackKind::Error => ("Basso", NSHapticFeedbackPattern::LevelChange),
+    };
+
+    unsafe {
+        if let Some(sound) = NSSound::soundNamed(&NSString::from_str(sound)) {
+            sound.play();
+        }
+        NSHapticFeedbackManager::defaultPerformer()
+            .performFeedbackPattern_performanceTime(pattern, NSHapticFeedbackPerformanceTime::Now);
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn play_windows(kind: FeedbackKind) -> Result<(), String> {
+    use windows::Win32::UI::WindowsAndMessaging::{
+        MessageBeep, MB_ICONASTERISK, MB_ICONEXCLAMATION, MB_ICONHAND, MB_OK,
+    };
+
+    let sound = match kind {
+        FeedbackKind::Tap | FeedbackKind::Send => MB_OK,
+        FeedbackKind::Receive => MB_ICONASTERISK,
+        FeedbackKind::Success => MB_ICONEXCLAMATION,
+        FeedbackKind::Error => MB_ICONHAND,
+    };
+    unsafe { MessageBeep(sound) }.map_err(|e| format!("Failed to play feedback: {}", e))
+}
+
+#[cfg(target_os = "linux")]
+fn play_linux(kind: FeedbackKind) {
+    // freedesktop sound theme event ids
+    let event_id = match kind {
+        FeedbackKind::Tap => "button-pressed",
+        FeedbackKind::Send => "message-sent-instant",
+        FeedbackKind::Receive => "message-new-instant",
+        FeedbackKind::Success => "complete",
+        FeedbackKind::Error => "dialog-error",
+    };
+    let _ = std::process::Command::new("canberra-gtk-play")
+        .args(["--id", event_id])
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn();
+}
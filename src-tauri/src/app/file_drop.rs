@@ -0,0 +1,111 @@
+use crate::app::config::WindowConfig;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use tauri::{PhysicalPosition, WebviewWindow};
+
+/// Which natively dropped files reach the page and how. Empty `extensions` accepts any type.
+#[derive(Debug, Clone)]
+pub struct FileDropRules {
+    max_bytes: Option<u64>,
+    extensions: Vec<String>,
+    callback: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct DroppedFile {
+    name: String,
+    data: String,
+}
+
+impl FileDropRules {
+    /// `None` unless `enable_file_drop` is set.
+    pub fn from_config(config: &WindowConfig) -> Option<Self> {
+        config.enable_file_drop.then(|| Self {
+            max_bytes: config.file_drop_max_bytes,
+            extensions: config
+                .file_drop_extensions
+                .iter()
+                .map(|ext| ext.trim().trim_start_matches('.').to_lowercase())
+                .filter(|ext| !ext.is_empty())
+                .collect(),
+            callback: config
+                .file_drop_callback
+                .clone()
+                .filter(|name| !name.trim().is_empty()),
+        })
+    }
+
+    fn accepts(&self, path: &Path, size: u64) -> bool {
+        if self.max_bytes.is_some_and(|max| size > max) {
+            return false;
+        }
+        self.extensions.is_empty()
+            || path
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| self.extensions.contains(&ext.to_lowercase()))
+    }
+}
+
+/// Hand files dropped on the window to the page: either their paths to
+/// `file_drop_callback`, or their contents replayed as a drop on the element
+/// under the cursor. Files outside the allowlist are skipped.
+pub async fn deliver(
+    window: WebviewWindow,
+    rules: FileDropRules,
+    paths: Vec<PathBuf>,
+    position: PhysicalPosition<f64>,
+) {
+    let mut accepted = Vec::new();
+    for path in paths {
+        match tokio::fs::metadata(&path).await {
+            Ok(metadata) if metadata.is_file() && rules.accepts(&path, metadata.len()) => {
+                accepted.push(path)
+            }
+            _ => log::info!("Skipped dropped file: {}", path.display()),
+        }
+    }
+    if accepted.is_empty() {
+        return;
+    }
+
+    let script = match &rules.callback {
+        Some(callback) => {
+            let paths: Vec<String> = accepted
+                .iter()
+                .map(|path| path.to_string_lossy().into_owned())
+                .collect();
+            format!(
+                "window[{}]?.({})",
+                serde_json::to_string(callback).unwrap(),
+                serde_json::to_string(&paths).unwrap()
+            )
+        }
+        None => {
+            let mut files = Vec::new();
+            for path in &accepted {
+                match tokio::fs::read(path).await {
+                    Ok(bytes) => files.push(DroppedFile {
+                        name: path
+                            .file_name()
+                            .map(|name| name.to_string_lossy().into_owned())
+                            .unwrap_or_default(),
+                        data: STANDARD.encode(bytes),
+                    }),
+                    Err(e) => log::warn!("Failed to read dropped file {}: {}", path.display(), e),
+                }
+            }
+            let position = position.to_logical::<f64>(window.scale_factor().unwrap_or(1.0));
+            format!(
+                "pakeDropFiles({}, {}, {})",
+                serde_json::to_string(&files).unwrap(),
+                position.x,
+                position.y
+            )
+        }
+    };
+    if let Err(e) = window.eval(&script) {
+        log::error!("Failed to deliver dropped files: {}", e);
+    }
+}
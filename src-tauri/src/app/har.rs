@@ -0,0 +1,147 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use tauri::Url;
+
+const MAX_ENTRIES: usize = 2000;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static ENTRIES: Mutex<VecDeque<HarEntry>> = Mutex::new(VecDeque::new());
+
+/// A finished request as reported by `inject/har.js`. Only the fields the
+/// page can observe are captured; request headers and bodies are not.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HarEntry {
+    pub started_date_time: String,
+    pub time: f64,
+    pub method: String,
+    pub url: String,
+    pub status: u16,
+    #[serde(default)]
+    pub status_text: String,
+    #[serde(default)]
+    pub mime_type: String,
+    #[serde(default)]
+    pub resource_type: String,
+    #[serde(default)]
+    pub response_headers: Vec<(String, String)>,
+    #[serde(default = "unknown_size")]
+    pub request_body_size: i64,
+    #[serde(default = "unknown_size")]
+    pub response_body_size: i64,
+    #[serde(default)]
+    pub timings: Option<HarTimings>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct HarTimings {
+    pub send: f64,
+    pub wait: f64,
+    pub receive: f64,
+}
+
+fn unknown_size() -> i64 {
+    -1
+}
+
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::SeqCst);
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::SeqCst)
+}
+
+/// Buffer an entry, dropping the oldest once the buffer is full.
+pub fn record(entry: HarEntry) {
+    if !is_enabled() {
+        return;
+    }
+    let mut entries = ENTRIES.lock().unwrap();
+    if entries.len() >= MAX_ENTRIES {
+        entries.pop_front();
+    }
+    entries.push_back(entry);
+}
+
+pub fn clear() {
+    ENTRIES.lock().unwrap().clear();
+}
+
+fn name_values<'a>(pairs: impl Iterator<Item = (&'a str, &'a str)>) -> Vec<Value> {
+    pairs
+        .map(|(name, value)| json!({ "name": name, "value": value }))
+        .collect()
+}
+
+fn entry_to_har(entry: &HarEntry) -> Value {
+    let query_string: Vec<Value> = Url::parse(&entry.url)
+        .map(|url| {
+            url.query_pairs()
+                .map(|(name, value)| json!({ "name": name, "value": value }))
+                .collect()
+        })
+        .unwrap_or_default();
+    let timings = entry.timings.unwrap_or(HarTimings {
+        send: 0.0,
+        wait: entry.time,
+        receive: 0.0,
+    });
+    let headers = name_values(
+        entry
+            .response_headers
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str())),
+    );
+
+    json!({
+        "startedDateTime": entry.started_date_time,
+        "time": entry.time,
+        "_resourceType": entry.resource_type,
+        "request": {
+            "method": entry.method,
+            "url": entry.url,
+            "httpVersion": "HTTP/1.1",
+            "headers": [],
+            "queryString": query_string,
+            "cookies": [],
+            "headersSize": -1,
+            "bodySize": entry.request_body_size,
+        },
+        "response": {
+            "status": entry.status,
+            "statusText": entry.status_text,
+            "httpVersion": "HTTP/1.1",
+            "headers": headers,
+            "cookies": [],
+            "content": {
+                "size": entry.response_body_size,
+                "mimeType": entry.mime_type,
+            },
+            "redirectURL": "",
+            "headersSize": -1,
+            "bodySize": entry.response_body_size,
+        },
+        "cache": {},
+        "timings": timings,
+    })
+}
+
+/// Serialize the buffered entries as a HAR 1.2 document.
+pub fn to_har(creator_name: &str, creator_version: &str) -> Value {
+    let entries = ENTRIES.lock().unwrap();
+    json!({
+        "log": {
+            "version": "1.2",
+            "creator": { "name": creator_name, "version": creator_version },
+            "entries": entries.iter().map(entry_to_har).collect::<Vec<_>>(),
+        }
+    })
+}
+
+pub fn len() -> usize {
+    ENTRIES.lock().unwrap().len()
+}
@@ -0,0 +1,100 @@
+use crate::app::config::WindowConfig;
+use crate::util::{get_pake_config, host_matches};
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{LazyLock, Mutex};
+use std::time::Duration;
+use tauri::{Url, WebviewWindow};
+
+// Validated once so a bad entry is reported a single time
+static HOME_URLS: LazyLock<Vec<Url>> =
+    LazyLock::new(|| valid_home_urls(&get_pake_config().0.windows[0]));
+
+static NEXT_HOME_URL: AtomicUsize = AtomicUsize::new(0);
+
+// Bumped per window label so a new `cycle` call ends the previous loop
+static CYCLES: LazyLock<Mutex<HashMap<String, u64>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Entries of `home_urls` that parse and stay on the host of `url`; the rest
+/// are dropped with a warning.
+fn valid_home_urls(window_config: &WindowConfig) -> Vec<Url> {
+    let allowed_host = Url::from_str(&window_config.url)
+        .ok()
+        .and_then(|u| u.host_str().map(str::to_string));
+
+    window_config
+        .home_urls
+        .iter()
+        .filter_map(|raw| {
+            let Ok(url) = Url::from_str(raw) else {
+                log::warn!("Invalid home_urls entry, ignoring: {}", raw);
+                return None;
+            };
+            let is_allowed = match (url.host_str(), allowed_host.as_deref()) {
+                (Some(host), Some(allowed_host)) => host_matches(host, allowed_host),
+                _ => false,
+            };
+            if !is_allowed {
+                log::warn!(
+                    "home_urls must stay on the configured host, ignoring: {}",
+                    url
+                );
+                return None;
+            }
+            Some(url)
+        })
+        .collect()
+}
+
+/// The next `home_urls` entry, wrapping around, so every window opened takes
+/// the following page in the list. `None` when no entries are configured.
+pub fn next_home_url() -> Option<Url> {
+    if HOME_URLS.is_empty() {
+        return None;
+    }
+    let index = NEXT_HOME_URL.fetch_add(1, Ordering::SeqCst) % HOME_URLS.len();
+    Some(HOME_URLS[index].clone())
+}
+
+/// Load the `home_urls` entries in turn, one every `interval`, starting after
+/// the page the window is on. `None` stops a running cycle.
+pub fn cycle(window: &WebviewWindow, interval: Option<Duration>) -> Result<(), String> {
+    if interval.is_some() && HOME_URLS.is_empty() {
+        return Err("No valid home_urls are configured".to_string());
+    }
+
+    let label = window.label().to_string();
+    let generation = {
+        let mut cycles = CYCLES.lock().unwrap();
+        let generation = cycles.entry(label.clone()).or_default();
+        *generation += 1;
+        *generation
+    };
+    let Some(interval) = interval else {
+        return Ok(());
+    };
+
+    let urls = HOME_URLS.clone();
+    let mut index = window
+        .url()
+        .ok()
+        .and_then(|current| urls.iter().position(|url| *url == current))
+        .map_or(0, |position| position + 1);
+    let window = window.clone();
+
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(interval).await;
+            if CYCLES.lock().unwrap().get(&label) != Some(&generation) {
+                break;
+            }
+            // Fails once the window has been closed
+            if window.navigate(urls[index % urls.len()].clone()).is_err() {
+                break;
+            }
+            index += 1;
+        }
+    });
+    Ok(())
+}
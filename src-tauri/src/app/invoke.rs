@@ -1,10 +1,52 @@
-use crate::util::{check_file_or_append, get_download_message_with_lang, show_toast, MessageType};
+use crate::app::accessibility::{self, AccessibilityModes};
+use crate::app::config::{PipCorner, ResizeEdge, SnapPosition};
+use crate::app::content_rules::{self, ContentRule};
+use crate::app::downloads::{self, DownloadRecord};
+use crate::app::environments;
+use crate::app::feedback::{self, FeedbackKind};
+use crate::app::har::{self, HarEntry};
+use crate::app::home_urls;
+use crate::app::load_timing::{self, LoadTiming};
+use crate::app::media::{self, MediaAction};
+use crate::app::permissions::authorize_clipboard;
+use crate::app::preferences::{self, PreferenceChanges, Preferences};
+use crate::app::save_page::{self, SavePageFormat};
+use crate::app::screenshot;
+use crate::app::setup::{self, checkpoint_window_state, DndMenuItem};
+use crate::app::site_data::{self, OriginData};
+use crate::app::throttle::{self, NetworkConditions, ThrottleProfile};
+use crate::app::translate;
+use crate::app::unread;
+use crate::app::updater;
+use crate::app::view_state;
+use crate::app::window::{
+    self, apply_titlebar_color, build_external_window, build_viewer_window, enter_pip, exit_pip,
+    inline_content_type, pin_current_monitor, reading_progress_enabled, snap_window_to,
+    start_resize_from,
+};
+use crate::app::workspace;
+use crate::util::{
+    broadcast, check_file_or_append, element_call_script, encode_png, eval_with_result,
+    expand_url_template, filename_from_content_disposition, get_download_message_with_lang,
+    get_pake_config, is_invalid_cert_allowed, load_app_state, load_setting, resolve_eval_result,
+    resolve_href, save_app_state, save_setting, scroll_into_view_script, scroll_script, show_toast,
+    user_script, version_info, MessageType, ScrollDirection, VersionInfo, DND_CHANGED_EVENT,
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use std::collections::HashMap;
 use std::fs::{self, File};
-use std::io::Write;
+use std::io::{self, Write};
+use std::path::PathBuf;
 use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
 use tauri::http::Method;
-use tauri::{command, AppHandle, Manager, Url, WebviewWindow};
+use tauri::{command, AppHandle, Emitter, Manager, Url, UserAttentionType, WebviewWindow};
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
 use tauri_plugin_http::reqwest::{ClientBuilder, Request};
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
 
 #[cfg(target_os = "macos")]
 use tauri::Theme;
@@ -23,15 +65,141 @@ pub struct BinaryDownloadParams {
     language: Option<String>,
 }
 
-#[derive(serde::Deserialize)]
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct PageMetadata {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub image: Option<String>,
+    /// Only a declared `link[rel~="icon"]`; `/favicon.ico` isn't guessed.
+    pub favicon: Option<String>,
+}
+
+const PAGE_METADATA_SCRIPT: &str = r#"(() => {
+  const meta = (selector) => document.querySelector(selector)?.content || null;
+  const icon = document.querySelector('link[rel~="icon"]');
+  return {
+    title: document.title || null,
+    description:
+      meta('meta[name="description"]') || meta('meta[property="og:description"]'),
+    image: meta('meta[property="og:image"]'),
+    favicon: icon?.href || null,
+  };
+})()"#;
+
+/// Scroll and size of whatever actually scrolls the page, in CSS pixels.
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct ViewportMetrics {
+    pub scroll_x: f64,
+    pub scroll_y: f64,
+    pub content_width: f64,
+    pub content_height: f64,
+    pub viewport_width: f64,
+    pub viewport_height: f64,
+    pub device_pixel_ratio: f64,
+}
+
+// SPA shells often keep the document fixed and scroll an inner container, so
+// walk up from the middle of the viewport to the first element that scrolls.
+// Measured on every call, content that grows after load is always current.
+const VIEWPORT_HELPERS: &str = r#"
+  const root = document.scrollingElement || document.documentElement;
+  const scroller = () => {
+    if (root.scrollHeight > root.clientHeight) return root;
+    let element = document.elementFromPoint(innerWidth / 2, innerHeight / 2);
+    while (element && element !== root) {
+      const overflow = getComputedStyle(element).overflowY;
+      const scrollable = overflow === "auto" || overflow === "scroll";
+      if (scrollable && element.scrollHeight > element.clientHeight) return element;
+      element = element.parentElement;
+    }
+    return root;
+  };
+  const metrics = (element) => ({
+    scroll_x: element.scrollLeft,
+    scroll_y: element.scrollTop,
+    content_width: element.scrollWidth,
+    content_height: element.scrollHeight,
+    viewport_width: element === root ? innerWidth : element.clientWidth,
+    viewport_height: element === root ? innerHeight : element.clientHeight,
+    device_pixel_ratio: devicePixelRatio,
+  });"#;
+
+fn viewport_script(body: &str) -> String {
+    format!("(() => {{{}\n  {}\n}})()", VIEWPORT_HELPERS, body)
+}
+
+#[derive(Clone, serde::Deserialize)]
 pub struct NotificationParams {
     title: String,
     body: String,
     icon: String,
 }
 
+static BUSY_TASKS: AtomicUsize = AtomicUsize::new(0);
+
+/// Held while a download or OAuth flow runs so the hang watchdog leaves the page alone.
+struct BusyGuard;
+
+impl BusyGuard {
+    fn new() -> Self {
+        BUSY_TASKS.fetch_add(1, Ordering::SeqCst);
+        BusyGuard
+    }
+}
+
+impl Drop for BusyGuard {
+    fn drop(&mut self) {
+        BUSY_TASKS.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+pub fn is_busy() -> bool {
+    BUSY_TASKS.load(Ordering::SeqCst) > 0
+}
+
+/// Where to save `filename`. With `download_prompt` a save dialog picks the path
+/// (`None` when cancelled), otherwise it goes to the download directory without
+/// overwriting existing files.
+async fn download_destination(app: &AppHandle, filename: &str) -> Result<Option<String>, String> {
+    let (pake_config, _) = get_pake_config();
+    let window_config = &pake_config.windows[0];
+    let download_dir = match &window_config.download_dir {
+        Some(dir) => PathBuf::from(dir),
+        None => app
+            .path()
+            .download_dir()
+            .map_err(|e| format!("Failed to get download dir: {}", e))?,
+    };
+
+    if window_config.download_prompt {
+        let (sender, receiver) = tokio::sync::oneshot::channel();
+        app.dialog()
+            .file()
+            .set_directory(&download_dir)
+            .set_file_name(filename)
+            .save_file(move |path| {
+                let _ = sender.send(path);
+            });
+        return receiver
+            .await
+            .ok()
+            .flatten()
+            .map(|path| {
+                path.into_path()
+                    .map(|path| path.to_string_lossy().into_owned())
+                    .map_err(|e| format!("Invalid save path: {}", e))
+            })
+            .transpose();
+    }
+
+    let output_path = download_dir.join(filename);
+    let path_str = output_path.to_str().ok_or("Invalid output path")?;
+    Ok(Some(check_file_or_append(path_str)))
+}
+
 #[command]
 pub async fn download_file(app: AppHandle, params: DownloadFileParams) -> Result<(), String> {
+    let _busy = BusyGuard::new();
     let window: WebviewWindow = app.get_webview_window("pake").ok_or("Window not found")?;
 
     show_toast(
@@ -39,31 +207,38 @@ pub async fn download_file(app: AppHandle, params: DownloadFileParams) -> Result
         &get_download_message_with_lang(MessageType::Start, params.language.clone()),
     );
 
-    let download_dir = app
-        .path()
-        .download_dir()
-        .map_err(|e| format!("Failed to get download dir: {}", e))?;
-
-    let output_path = download_dir.join(&params.filename);
-
-    let path_str = output_path.to_str().ok_or("Invalid output path")?;
+    let url = Url::from_str(&params.url).map_err(|e| format!("Invalid URL: {}", e))?;
 
-    let file_path = check_file_or_append(path_str);
+    let (pake_config, _) = get_pake_config();
+    let accept_invalid_certs = url.host_str().is_some_and(|host| {
+        is_invalid_cert_allowed(host, &pake_config.windows[0].invalid_cert_hosts())
+    });
 
     let client = ClientBuilder::new()
+        .danger_accept_invalid_certs(accept_invalid_certs)
         .build()
         .map_err(|e| format!("Failed to build client: {}", e))?;
 
-    let url = Url::from_str(&params.url).map_err(|e| format!("Invalid URL: {}", e))?;
-
-    let request = Request::new(Method::GET, url);
+    let request = Request::new(Method::GET, url.clone());
 
     let response = client.execute(request).await;
 
     match response {
         Ok(mut res) => {
+            let filename = res
+                .headers()
+                .get("content-disposition")
+                .and_then(|value| value.to_str().ok())
+                .and_then(filename_from_content_disposition)
+                .unwrap_or_else(|| params.filename.clone());
+            let Some(file_path) = download_destination(&app, &filename).await? else {
+                log::info!("Download cancelled: {}", filename);
+                return Ok(());
+            };
+            log::info!("Downloading {} to {}", url, file_path);
+
             let mut file =
-                File::create(file_path).map_err(|e| format!("Failed to create file: {}", e))?;
+                File::create(&file_path).map_err(|e| format!("Failed to create file: {}", e))?;
 
             while let Some(chunk) = res
                 .chunk()
@@ -74,6 +249,10 @@ pub async fn download_file(app: AppHandle, params: DownloadFileParams) -> Result
                     .map_err(|e| format!("Failed to write chunk: {}", e))?;
             }
 
+            log::info!("Download finished: {}", filename);
+            if let Err(e) = downloads::record(&app, Some(url.to_string()), &file_path) {
+                log::warn!("Failed to record download: {}", e);
+            }
             show_toast(
                 &window,
                 &get_download_message_with_lang(MessageType::Success, params.language.clone()),
@@ -81,6 +260,7 @@ pub async fn download_file(app: AppHandle, params: DownloadFileParams) -> Result
             Ok(())
         }
         Err(e) => {
+            log::error!("Download failed: {}: {}", params.filename, e);
             show_toast(
                 &window,
                 &get_download_message_with_lang(MessageType::Failure, params.language),
@@ -90,11 +270,23 @@ pub async fn download_file(app: AppHandle, params: DownloadFileParams) -> Result
     }
 }
 
+/// Files saved through `download_file` and `download_file_by_binary`, newest first.
+#[command]
+pub fn get_download_history(app: AppHandle) -> Vec<DownloadRecord> {
+    downloads::history(&app)
+}
+
+#[command]
+pub fn clear_download_history(app: AppHandle) -> Result<(), String> {
+    downloads::clear(&app)
+}
+
 #[command]
 pub async fn download_file_by_binary(
     app: AppHandle,
     params: BinaryDownloadParams,
 ) -> Result<(), String> {
+    let _busy = BusyGuard::new();
     let window: WebviewWindow = app.get_webview_window("pake").ok_or("Window not found")?;
 
     show_toast(
@@ -102,19 +294,17 @@ pub async fn download_file_by_binary(
         &get_download_message_with_lang(MessageType::Start, params.language.clone()),
     );
 
-    let download_dir = app
-        .path()
-        .download_dir()
-        .map_err(|e| format!("Failed to get download dir: {}", e))?;
-
-    let output_path = download_dir.join(&params.filename);
-
-    let path_str = output_path.to_str().ok_or("Invalid output path")?;
-
-    let file_path = check_file_or_append(path_str);
+    let Some(file_path) = download_destination(&app, &params.filename).await? else {
+        log::info!("Download cancelled: {}", params.filename);
+        return Ok(());
+    };
 
-    match fs::write(file_path, &params.binary) {
+    match fs::write(&file_path, &params.binary) {
         Ok(_) => {
+            log::info!("Saved binary download to {}", file_path);
+            if let Err(e) = downloads::record(&app, None, &file_path) {
+                log::warn!("Failed to record download: {}", e);
+            }
             show_toast(
                 &window,
                 &get_download_message_with_lang(MessageType::Success, params.language.clone()),
@@ -122,6 +312,7 @@ pub async fn download_file_by_binary(
             Ok(())
         }
         Err(e) => {
+            log::error!("Failed to save binary download {}: {}", file_path, e);
             show_toast(
                 &window,
                 &get_download_message_with_lang(MessageType::Failure, params.language),
@@ -131,8 +322,42 @@ pub async fn download_file_by_binary(
     }
 }
 
-#[command]
-pub fn send_notification(app: AppHandle, params: NotificationParams) -> Result<(), String> {
+static DO_NOT_DISTURB: AtomicBool = AtomicBool::new(false);
+static QUEUED_NOTIFICATIONS: Mutex<Vec<NotificationParams>> = Mutex::new(Vec::new());
+
+pub fn is_dnd_enabled() -> bool {
+    DO_NOT_DISTURB.load(Ordering::SeqCst)
+}
+
+pub fn restore_dnd_state(app: &AppHandle) {
+    let enabled = load_app_state(app, "do_not_disturb")
+        .and_then(|value| value.as_bool())
+        .unwrap_or(false);
+    DO_NOT_DISTURB.store(enabled, Ordering::SeqCst);
+}
+
+/// Switch do-not-disturb, persist it, sync the tray item and flush anything queued.
+pub fn set_dnd_state(app: &AppHandle, enabled: bool) -> Result<(), String> {
+    DO_NOT_DISTURB.store(enabled, Ordering::SeqCst);
+    if let Some(item) = app.try_state::<DndMenuItem>() {
+        let _ = item.0.set_checked(enabled);
+    }
+    save_app_state(app, "do_not_disturb", enabled.into())?;
+    let _ = broadcast(app, DND_CHANGED_EVENT, enabled);
+
+    if !enabled {
+        // One notification failing to show must not drop the rest of the queue
+        let queued = std::mem::take(&mut *QUEUED_NOTIFICATIONS.lock().unwrap());
+        for params in queued {
+            if let Err(e) = show_notification(app, &params) {
+                log::warn!("{} ({})", e, params.title);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn show_notification(app: &AppHandle, params: &NotificationParams) -> Result<(), String> {
     use tauri_plugin_notification::NotificationExt;
     app.notification()
         .builder()
@@ -145,41 +370,915 @@ pub fn send_notification(app: AppHandle, params: NotificationParams) -> Result<(
 }
 
 #[command]
-pub async fn update_theme_mode(app: AppHandle, mode: String) {
-    #[cfg(target_os = "macos")]
-    {
-        if let Some(window) = app.get_webview_window("pake") {
-            let theme = if mode == "dark" {
-                Theme::Dark
-            } else {
-                Theme::Light
-            };
-            let _ = window.set_theme(Some(theme));
+pub fn send_notification(app: AppHandle, params: NotificationParams) -> Result<(), String> {
+    if is_dnd_enabled() {
+        let (pake_config, _) = get_pake_config();
+        if pake_config.windows[0].dnd_queue_notifications {
+            QUEUED_NOTIFICATIONS.lock().unwrap().push(params);
         }
+        return Ok(());
     }
-    #[cfg(not(target_os = "macos"))]
+    show_notification(&app, &params)
+}
+
+#[command]
+pub fn set_dnd(app: AppHandle, enabled: bool) -> Result<(), String> {
+    set_dnd_state(&app, enabled)
+}
+
+#[command]
+pub fn get_dnd() -> bool {
+    is_dnd_enabled()
+}
+
+#[command]
+pub fn media_play_pause(app: AppHandle) -> Result<(), String> {
+    media::dispatch(&app, MediaAction::PlayPause)
+}
+
+#[command]
+pub fn media_next(app: AppHandle) -> Result<(), String> {
+    media::dispatch(&app, MediaAction::NextTrack)
+}
+
+#[command]
+pub fn media_prev(app: AppHandle) -> Result<(), String> {
+    media::dispatch(&app, MediaAction::PreviousTrack)
+}
+
+/// Called by `inject/media.js` when playback starts or stops.
+#[command]
+pub fn report_media_state(app: AppHandle, playing: bool) {
+    media::set_playing(&app, playing);
+}
+
+/// Rotate the calling window through `home_urls` every `interval_secs`; 0 or none stops.
+#[command]
+pub fn cycle_urls(window: WebviewWindow, interval_secs: Option<u64>) -> Result<(), String> {
+    let interval = interval_secs
+        .filter(|secs| *secs > 0)
+        .map(Duration::from_secs);
+    home_urls::cycle(&window, interval)
+}
+
+/// Emit `name` with `payload` to every Pake window; returns the window labels reached.
+#[command]
+pub fn broadcast_event(
+    app: AppHandle,
+    name: String,
+    payload: Option<serde_json::Value>,
+) -> Result<Vec<String>, String> {
+    broadcast(&app, &name, payload.unwrap_or_default())
+}
+
+/// Follow the page's explicit theme in whichever window reported it.
+#[command]
+pub async fn update_theme_mode(window: WebviewWindow, mode: String) {
+    let dark = mode == "dark";
+    preferences::set_color_scheme_meta(&window, Some(if dark { "dark" } else { "light" }));
+    #[cfg(target_os = "macos")]
     {
-        let _ = app;
-        let _ = mode;
+        let theme = if dark { Theme::Dark } else { Theme::Light };
+        let _ = window.set_theme(Some(theme));
     }
 }
 
+#[command]
+pub fn set_zoom(window: WebviewWindow, zoom: u32) -> Result<u32, String> {
+    view_state::set_zoom(&window, zoom)
+}
+
+#[command]
+pub fn get_zoom(window: WebviewWindow) -> u32 {
+    view_state::zoom(window.app_handle(), window.label())
+}
+
+#[command]
+pub fn set_muted(window: WebviewWindow, muted: bool) -> Result<(), String> {
+    view_state::set_muted(&window, muted)
+}
+
+#[command]
+pub fn get_muted(window: WebviewWindow) -> bool {
+    view_state::is_muted(window.label())
+}
+
+#[command]
+pub fn set_title_prefix(window: WebviewWindow, prefix: String) -> Result<(), String> {
+    view_state::set_title_prefix(&window, &prefix)
+}
+
+#[command]
+pub async fn move_to_workspace(window: WebviewWindow, index: u32) -> Result<(), String> {
+    workspace::move_to_workspace(&window, index).await
+}
+
+#[command]
+pub fn report_first_paint() {
+    window::mark_first_paint();
+}
+
+#[command]
+pub fn is_loading(window: WebviewWindow) -> bool {
+    window::is_loading(window.label())
+}
+
+#[command]
+pub fn set_reading_progress(app: AppHandle, enabled: bool) -> Result<(), String> {
+    window::set_reading_progress(&app, enabled)
+}
+
+#[command]
+pub fn get_reading_progress(app: AppHandle) -> bool {
+    reading_progress_enabled(&app)
+}
+
+#[command]
+pub fn set_grayscale(app: AppHandle, enabled: bool) -> Result<(), String> {
+    accessibility::set_grayscale(&app, enabled)
+}
+
+#[command]
+pub fn set_reduced_motion(app: AppHandle, enabled: bool) -> Result<(), String> {
+    accessibility::set_reduced_motion(&app, enabled)
+}
+
+#[command]
+pub fn get_accessibility_modes(app: AppHandle) -> AccessibilityModes {
+    accessibility::modes(&app)
+}
+
+#[command]
+pub async fn list_origins_with_data(window: WebviewWindow) -> Result<Vec<OriginData>, String> {
+    site_data::list_origins(&window).await
+}
+
+/// Finer-grained than `clear_cache_and_restart`: only `origin` is touched and no restart is needed.
+#[command]
+pub async fn clear_origin_data(window: WebviewWindow, origin: String) -> Result<(), String> {
+    site_data::clear_origin(&window, &origin).await?;
+    log::info!("Cleared site data for {}", origin);
+    Ok(())
+}
+
+/// Cookies listed in `preserve_cookies` survive the clear and are set again after the restart.
 #[command]
 #[allow(unreachable_code)]
-pub fn clear_cache_and_restart(app: AppHandle) -> Result<(), String> {
+pub async fn clear_cache_and_restart(app: AppHandle) -> Result<(), String> {
     if let Some(window) = app.get_webview_window("pake") {
-        match window.clear_all_browsing_data() {
+        match site_data::clear_all_keeping_cookies(&window).await {
             Ok(_) => {
                 // Clear all browsing data successfully
                 app.restart();
                 Ok(())
             }
             Err(e) => {
-                eprintln!("Failed to clear browsing data: {}", e);
-                Err(format!("Failed to clear browsing data: {}", e))
+                log::error!("{}", e);
+                Err(e)
             }
         }
     } else {
         Err("Main window not found".to_string())
     }
 }
+
+/// Reloads, or rebuilds the main window where the webview can't switch live.
+#[command]
+pub async fn set_javascript_enabled(app: AppHandle, enabled: bool) -> Result<(), String> {
+    let window = app.get_webview_window("pake").ok_or("Window not found")?;
+    window::set_javascript_enabled(&window, enabled).await
+}
+
+/// Open a `data:` URL, or a file on the calling page's own origin, in a viewer
+/// window when its content type is listed in `inline_content_types`.
+#[command]
+pub fn open_inline_content(window: WebviewWindow, url: String) -> Result<(), String> {
+    let url = Url::from_str(&url).map_err(|e| format!("Invalid URL: {}", e))?;
+    if url.scheme() != "data" {
+        let page = window
+            .url()
+            .map_err(|e| format!("Failed to read the current URL: {}", e))?;
+        if url.origin() != page.origin() {
+            return Err("Inline content must be a data: URL or on the page's origin".to_string());
+        }
+    }
+    let content_type = inline_content_type(&url).ok_or("Unknown inline content type")?;
+    if !get_pake_config().0.windows[0].opens_inline(&content_type) {
+        return Err(format!("{} is not in inline_content_types", content_type));
+    }
+    build_viewer_window(window.app_handle(), url)
+        .map(|_| ())
+        .map_err(|e| format!("Failed to open viewer: {}", e))
+}
+
+#[derive(Debug, Clone, Copy, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JsDialogKind {
+    Alert,
+    Confirm,
+}
+
+/// Native dialog behind the `js_dialogs: custom` `alert` and the notification
+/// permission prompt, titled with the page's host like a browser would. True when confirmed.
+#[command]
+pub async fn show_js_dialog(
+    window: WebviewWindow,
+    kind: JsDialogKind,
+    message: String,
+) -> Result<bool, String> {
+    let title = window
+        .url()
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_string))
+        .unwrap_or_default();
+    let buttons = match kind {
+        JsDialogKind::Alert => MessageDialogButtons::Ok,
+        JsDialogKind::Confirm => MessageDialogButtons::OkCancel,
+    };
+    let (sender, receiver) = tokio::sync::oneshot::channel();
+    window
+        .dialog()
+        .message(message)
+        .title(title)
+        .kind(MessageDialogKind::Info)
+        .buttons(buttons)
+        .show(move |confirmed| {
+            let _ = sender.send(confirmed);
+        });
+    receiver.await.map_err(|_| "Dialog was closed".to_string())
+}
+
+/// Navigate the main window to a configured environment, remembered across launches.
+#[command]
+pub fn switch_environment(app: AppHandle, name: String) -> Result<(), String> {
+    environments::switch(&app, &name)
+}
+
+/// Open `href` in a new window, resolved against the calling page's current URL.
+/// Only the configured host (or, for local apps, the page's own host) is allowed.
+#[command]
+pub async fn open_relative_in_new_window(
+    window: WebviewWindow,
+    href: String,
+) -> Result<(), String> {
+    let current = eval_with_result(&window, "location.href").await?;
+    let base = current
+        .as_str()
+        .and_then(|href| Url::from_str(href).ok())
+        .ok_or("Failed to read the current URL")?;
+    let allowed_host = environments::allowed_host(&get_pake_config().0.windows[0])
+        .or_else(|| base.host_str().map(str::to_string))
+        .ok_or("No host to validate against")?;
+    let url = resolve_href(&base, &href, &allowed_host)?;
+    build_external_window(window.app_handle(), url)
+        .map(|_| ())
+        .map_err(|e| format!("Failed to open window: {}", e))
+}
+
+/// Text selected in the page, `None` when nothing is selected.
+#[command]
+pub async fn get_selection(window: WebviewWindow) -> Result<Option<String>, String> {
+    let selection = eval_with_result(&window, "window.getSelection()?.toString() ?? \"\"").await?;
+    Ok(selection
+        .as_str()
+        .map(str::trim)
+        .filter(|text| !text.is_empty())
+        .map(str::to_string))
+}
+
+/// Account name or email read through `identity_selector`, `None` when signed out.
+/// Also refreshes the window's title prefix.
+#[command]
+pub async fn get_identity(window: WebviewWindow) -> Result<Option<String>, String> {
+    if get_pake_config().0.windows[0].identity_selector.is_none() {
+        return Err("identity_selector is not configured".to_string());
+    }
+    view_state::refresh_identity(&window).await
+}
+
+/// Open `lookup_url_template` for `text`, or for the current selection when omitted.
+#[command]
+pub async fn lookup_selection(window: WebviewWindow, text: Option<String>) -> Result<(), String> {
+    let (config, _) = get_pake_config();
+    let template = config.windows[0]
+        .lookup_url_template
+        .clone()
+        .ok_or("lookup_url_template is not configured")?;
+    let text = match text.filter(|text| !text.trim().is_empty()) {
+        Some(text) => text,
+        None => get_selection(window.clone())
+            .await?
+            .ok_or("Nothing is selected")?,
+    };
+    let url = expand_url_template(&template, &text)?;
+    build_external_window(window.app_handle(), url)
+        .map(|_| ())
+        .map_err(|e| format!("Failed to open window: {}", e))
+}
+
+#[command]
+pub fn set_titlebar_color(app: AppHandle, color: String) -> Result<(), String> {
+    let window = app.get_webview_window("pake").ok_or("Window not found")?;
+    apply_titlebar_color(&window, &color)
+}
+
+// Critical bounces the dock until focus on macOS and flashes the taskbar on Windows;
+// Informational bounces once / flashes briefly. Linux sets the urgency hint for both.
+fn attention_type(critical: bool) -> UserAttentionType {
+    if critical {
+        UserAttentionType::Critical
+    } else {
+        UserAttentionType::Informational
+    }
+}
+
+#[command]
+pub fn request_attention(app: AppHandle, critical: bool) -> Result<(), String> {
+    let window = app.get_webview_window("pake").ok_or("Window not found")?;
+    if window.is_focused().unwrap_or(false) || is_dnd_enabled() {
+        return Ok(());
+    }
+    window
+        .request_user_attention(Some(attention_type(critical)))
+        .map_err(|e| format!("Failed to request attention: {}", e))
+}
+
+/// Briefly keep the calling window above all others, for events that need the user now.
+#[command]
+pub fn flash_to_front(window: WebviewWindow, duration_ms: u64) -> Result<(), String> {
+    if is_dnd_enabled() {
+        return Ok(());
+    }
+    window::flash_to_front(&window, Duration::from_millis(duration_ms))
+}
+
+#[command]
+pub fn play_feedback(app: AppHandle, kind: FeedbackKind) -> Result<(), String> {
+    if is_dnd_enabled() {
+        return Ok(());
+    }
+    feedback::play(&app, kind)
+}
+
+/// Zip the app logs with a diagnostics summary into the download directory,
+/// returning the path or `None` when the save dialog was cancelled.
+#[command]
+pub async fn export_logs(app: AppHandle) -> Result<Option<String>, String> {
+    let log_dir = app
+        .path()
+        .app_log_dir()
+        .map_err(|e| format!("Failed to get log dir: {}", e))?;
+    let filename = format!("{}-logs.zip", app.package_info().name);
+    let Some(path) = download_destination(&app, &filename).await? else {
+        return Ok(None);
+    };
+
+    let file = File::create(&path).map_err(|e| format!("Failed to create archive: {}", e))?;
+    let mut zip = ZipWriter::new(file);
+    let options = SimpleFileOptions::default();
+
+    zip.start_file("diagnostics.txt", options)
+        .map_err(|e| format!("Failed to write diagnostics: {}", e))?;
+    zip.write_all(version_info(&app).summary().as_bytes())
+        .map_err(|e| format!("Failed to write diagnostics: {}", e))?;
+
+    for entry in fs::read_dir(&log_dir).into_iter().flatten().flatten() {
+        let log_path = entry.path();
+        if !log_path.is_file() {
+            continue;
+        }
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let mut log_file =
+            File::open(&log_path).map_err(|e| format!("Failed to read {}: {}", name, e))?;
+        zip.start_file(name.as_str(), options)
+            .map_err(|e| format!("Failed to add {}: {}", name, e))?;
+        io::copy(&mut log_file, &mut zip).map_err(|e| format!("Failed to add {}: {}", name, e))?;
+    }
+
+    zip.finish()
+        .map_err(|e| format!("Failed to finish archive: {}", e))?;
+    log::info!("Exported logs to {}", path);
+    Ok(Some(path))
+}
+
+#[command]
+pub fn focus_element(app: AppHandle, selector: String) -> Result<(), String> {
+    let window = app.get_webview_window("pake").ok_or("Window not found")?;
+    window
+        .eval(&element_call_script(&selector, "focus"))
+        .map_err(|e| format!("Failed to focus element: {}", e))
+}
+
+#[command]
+pub fn blur_element(app: AppHandle, selector: String) -> Result<(), String> {
+    let window = app.get_webview_window("pake").ok_or("Window not found")?;
+    window
+        .eval(&element_call_script(&selector, "blur"))
+        .map_err(|e| format!("Failed to blur element: {}", e))
+}
+
+/// Click the first element matching `selector`; nothing happens when none does.
+#[command]
+pub fn click_element(app: AppHandle, selector: String) -> Result<(), String> {
+    let window = app.get_webview_window("pake").ok_or("Window not found")?;
+    window
+        .eval(&element_call_script(&selector, "click"))
+        .map_err(|e| format!("Failed to click element: {}", e))
+}
+
+/// Paste the clipboard image into the focused element as a PNG file.
+/// Resolves to `false` when the clipboard holds no image.
+#[command]
+pub fn paste_image_from_clipboard(app: AppHandle) -> Result<bool, String> {
+    use tauri_plugin_clipboard_manager::ClipboardExt;
+
+    let window = app.get_webview_window("pake").ok_or("Window not found")?;
+    let Ok(image) = app.clipboard().read_image() else {
+        return Ok(false);
+    };
+
+    let png = encode_png(image.rgba(), image.width(), image.height())?;
+    let script = format!(
+        "pakePasteImage({})",
+        serde_json::to_string(&STANDARD.encode(png)).unwrap()
+    );
+    window
+        .eval(&script)
+        .map_err(|e| format!("Failed to paste image: {}", e))?;
+    Ok(true)
+}
+
+/// Native fallback for `navigator.clipboard.readText`, see `inject/clipboard.js`.
+#[command]
+pub async fn clipboard_read_text(window: WebviewWindow) -> Result<String, String> {
+    use tauri_plugin_clipboard_manager::ClipboardExt;
+
+    authorize_clipboard(&window, false).await?;
+    window
+        .clipboard()
+        .read_text()
+        .map_err(|e| format!("Failed to read clipboard: {}", e))
+}
+
+/// Native fallback for `navigator.clipboard.writeText`, see `inject/clipboard.js`.
+#[command]
+pub async fn clipboard_write_text(window: WebviewWindow, text: String) -> Result<(), String> {
+    use tauri_plugin_clipboard_manager::ClipboardExt;
+
+    authorize_clipboard(&window, true).await?;
+    window
+        .clipboard()
+        .write_text(text)
+        .map_err(|e| format!("Failed to write clipboard: {}", e))
+}
+
+/// Exit for real, bypassing hide_on_close.
+#[command]
+pub fn force_quit(app: AppHandle) {
+    setup::force_quit(&app);
+}
+
+/// Checkpoint window geometry now, e.g. before automation does something risky.
+#[command]
+pub fn save_window_state(app: AppHandle) -> Result<(), String> {
+    checkpoint_window_state(&app)
+}
+
+#[command]
+pub fn open_preferences(app: AppHandle) -> Result<(), String> {
+    preferences::open(&app).map_err(|e| format!("Failed to open preferences: {}", e))
+}
+
+#[command]
+pub fn get_preferences(app: AppHandle) -> Result<Preferences, String> {
+    preferences::current(&app)
+}
+
+/// Apply whichever preferences are set in `changes` and return the result.
+#[command]
+pub fn set_preferences(app: AppHandle, changes: PreferenceChanges) -> Result<Preferences, String> {
+    preferences::apply(&app, changes)
+}
+
+/// Read a value stored with `set_setting`, `null` when unset.
+#[command]
+pub fn get_setting(app: AppHandle, key: String) -> Option<serde_json::Value> {
+    load_setting(&app, &key)
+}
+
+/// Store any JSON value under `key`; `null` deletes it.
+#[command]
+pub fn set_setting(app: AppHandle, key: String, value: serde_json::Value) -> Result<(), String> {
+    save_setting(&app, &key, value)
+}
+
+/// Development builds only; emits `pake://network-throttle` with the new profile.
+#[command]
+pub fn set_network_throttle(app: AppHandle, profile: ThrottleProfile) -> Result<(), String> {
+    throttle::set_profile(&app, profile)
+}
+
+#[command]
+pub fn get_network_throttle() -> Option<NetworkConditions> {
+    throttle::active_profile().conditions()
+}
+
+#[command]
+pub fn report_eval_result(id: u64, value: serde_json::Value) {
+    resolve_eval_result(id, value);
+}
+
+/// Scroll `selector` into view, or scroll by `direction` when no selector is given.
+#[command]
+pub async fn scroll_page(
+    window: WebviewWindow,
+    direction: Option<ScrollDirection>,
+    amount: Option<f64>,
+    selector: Option<String>,
+) -> Result<(), String> {
+    if let Some(selector) = selector {
+        let found = eval_with_result(&window, &scroll_into_view_script(&selector)).await?;
+        if found.as_bool() != Some(true) {
+            return Err(format!("No element matches selector: {}", selector));
+        }
+        return Ok(());
+    }
+
+    let direction = direction.ok_or("Either direction or selector is required")?;
+    window
+        .eval(&scroll_script(direction, amount))
+        .map_err(|e| format!("Failed to scroll: {}", e))
+}
+
+#[command]
+pub async fn get_page_metadata(app: AppHandle) -> Result<PageMetadata, String> {
+    let window = app.get_webview_window("pake").ok_or("Window not found")?;
+    let value = eval_with_result(&window, PAGE_METADATA_SCRIPT).await?;
+    serde_json::from_value(value).map_err(|e| format!("Invalid page metadata: {}", e))
+}
+
+/// PNG of the main window's page, the whole scrollable document when `full_page`.
+/// With `filename` it is saved to the download directory and the path returned;
+/// otherwise it is returned as base64.
+#[command]
+pub async fn capture_screenshot(
+    app: AppHandle,
+    full_page: Option<bool>,
+    filename: Option<String>,
+) -> Result<String, String> {
+    let window = app.get_webview_window("pake").ok_or("Window not found")?;
+    let png = if full_page.unwrap_or(false) {
+        screenshot::capture_full_page(&window).await?
+    } else {
+        screenshot::capture_viewport(&window).await?
+    };
+    let Some(filename) = filename else {
+        return Ok(STANDARD.encode(png));
+    };
+
+    let filename = screenshot::png_filename(&filename).ok_or("Invalid screenshot filename")?;
+    let path = download_destination(&app, &filename)
+        .await?
+        .ok_or("Screenshot save was cancelled")?;
+    fs::write(&path, png).map_err(|e| format!("Failed to save screenshot: {}", e))?;
+    log::info!("Saved screenshot to {}", path);
+    Ok(path)
+}
+
+/// Save the main window's page to the download directory, returning the path
+/// or `None` when the save dialog was cancelled.
+#[command]
+pub async fn save_page(app: AppHandle, format: SavePageFormat) -> Result<Option<String>, String> {
+    let _busy = BusyGuard::new();
+    let window = app.get_webview_window("pake").ok_or("Window not found")?;
+    let filename = save_page::filename(&window, format);
+    let Some(path) = download_destination(&app, &filename).await? else {
+        log::info!("Save page cancelled: {}", filename);
+        return Ok(None);
+    };
+
+    save_page::save(&window, format, PathBuf::from(&path)).await?;
+    log::info!("Saved page to {}", path);
+    show_toast(
+        &window,
+        &get_download_message_with_lang(MessageType::Success, None),
+    );
+    Ok(Some(path))
+}
+
+#[command]
+pub async fn get_viewport(window: WebviewWindow) -> Result<ViewportMetrics, String> {
+    let value = eval_with_result(&window, &viewport_script("return metrics(scroller());")).await?;
+    serde_json::from_value(value).map_err(|e| format!("Invalid viewport metrics: {}", e))
+}
+
+/// Jump to `(x, y)` and return the resulting metrics, the page clamping out-of-range offsets.
+#[command]
+pub async fn set_scroll(window: WebviewWindow, x: f64, y: f64) -> Result<ViewportMetrics, String> {
+    if !x.is_finite() || !y.is_finite() {
+        return Err("Scroll offsets must be finite numbers".to_string());
+    }
+    let body = format!(
+        r#"const element = scroller();
+  element.scrollTo({{ left: {x}, top: {y}, behavior: "instant" }});
+  return metrics(element);"#
+    );
+    let value = eval_with_result(&window, &viewport_script(&body)).await?;
+    serde_json::from_value(value).map_err(|e| format!("Invalid viewport metrics: {}", e))
+}
+
+const OAUTH_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Run an OAuth round trip through a temporary localhost listener.
+/// `redirect_uri` is appended to the provider URL unless it already sets one,
+/// and the redirect's query parameters are returned and emitted as `pake://oauth-redirect`.
+#[command]
+pub async fn start_oauth(
+    app: AppHandle,
+    provider_url: String,
+) -> Result<HashMap<String, String>, String> {
+    let _busy = BusyGuard::new();
+    let mut provider_url =
+        Url::from_str(&provider_url).map_err(|e| format!("Invalid provider URL: {}", e))?;
+
+    let (pake_config, _) = get_pake_config();
+    let window_config = &pake_config.windows[0];
+    let is_allowed = provider_url.host_str().is_some_and(|host| {
+        window_config
+            .oauth_domains
+            .iter()
+            .any(|domain| host_matches(host, domain))
+    });
+    if !is_allowed {
+        return Err(format!("OAuth provider is not allowed: {}", provider_url));
+    }
+
+    let (sender, receiver) = tokio::sync::oneshot::channel::<String>();
+    let sender = std::sync::Mutex::new(Some(sender));
+    let port = tauri_plugin_oauth::start(move |redirect_url| {
+        if let Some(sender) = sender.lock().unwrap().take() {
+            let _ = sender.send(redirect_url);
+        }
+    })
+    .map_err(|e| format!("Failed to start OAuth listener: {}", e))?;
+
+    if !provider_url
+        .query_pairs()
+        .any(|(key, _)| key == "redirect_uri")
+    {
+        provider_url
+            .query_pairs_mut()
+            .append_pair("redirect_uri", &format!("http://localhost:{}", port));
+    }
+
+    let opened = if window_config.oauth_in_browser {
+        use tauri_plugin_opener::OpenerExt;
+        app.opener()
+            .open_url(provider_url.as_str(), None::<&str>)
+            .map_err(|e| e.to_string())
+    } else {
+        app.get_webview_window("pake")
+            .ok_or("Window not found".to_string())
+            .and_then(|window| window.navigate(provider_url).map_err(|e| e.to_string()))
+    };
+    if let Err(e) = opened {
+        let _ = tauri_plugin_oauth::cancel(port);
+        return Err(format!("Failed to open OAuth provider: {}", e));
+    }
+
+    let result = tokio::time::timeout(OAUTH_TIMEOUT, receiver).await;
+    let _ = tauri_plugin_oauth::cancel(port);
+    let redirect_url = match result {
+        Ok(Ok(url)) => url,
+        _ => return Err("OAuth flow timed out".to_string()),
+    };
+
+    let params: HashMap<String, String> = Url::from_str(&redirect_url)
+        .map_err(|e| format!("Invalid redirect URL: {}", e))?
+        .query_pairs()
+        .into_owned()
+        .collect();
+    let _ = app.emit("pake://oauth-redirect", &params);
+    Ok(params)
+}
+
+#[command]
+pub fn toggle_reader_mode(app: AppHandle) -> Result<(), String> {
+    let window = app.get_webview_window("pake").ok_or("Window not found")?;
+    window
+        .eval("toggleReaderMode()")
+        .map_err(|e| format!("Failed to toggle reader mode: {}", e))
+}
+
+/// Translate the main window's page through `translate` from pake.json, or undo
+/// it on a second call. Returns whether the page is now translated.
+#[command]
+pub async fn toggle_translate(app: AppHandle) -> Result<bool, String> {
+    let window = app.get_webview_window("pake").ok_or("Window not found")?;
+    translate::toggle(&window).await
+}
+
+/// Accepts WebKit content-rule-list JSON or EasyList-style filter text.
+#[command]
+pub fn set_content_rules(app: AppHandle, rules: String) -> Result<(), String> {
+    let window = app.get_webview_window("pake").ok_or("Window not found")?;
+    let rules = content_rules::parse_rules(&rules)?;
+    content_rules::apply_rules(&window, rules);
+    Ok(())
+}
+
+#[command]
+pub fn clear_content_rules(app: AppHandle) -> Result<(), String> {
+    let window = app.get_webview_window("pake").ok_or("Window not found")?;
+    content_rules::apply_rules(&window, Vec::new());
+    Ok(())
+}
+
+#[command]
+pub fn get_content_rules() -> Vec<ContentRule> {
+    content_rules::active_rules()
+}
+
+/// Last count read from the title with `unread_title_regex`, zero when unset.
+#[command]
+pub fn get_unread_count() -> u32 {
+    unread::unread_count()
+}
+
+/// Flip the bundled ad-block list and return whether it is now on.
+#[command]
+pub fn toggle_adblock(app: AppHandle) -> Result<bool, String> {
+    let enabled = !content_rules::adblock_enabled(&app);
+    content_rules::set_adblock(&app, enabled)?;
+    Ok(enabled)
+}
+
+/// Requests the injected fallback stopped, batched by the page.
+#[command]
+pub fn report_blocked_requests(app: AppHandle, count: u64) {
+    content_rules::record_blocked(&app, count);
+}
+
+#[command]
+pub fn snap_window(app: AppHandle, position: SnapPosition) -> Result<(), String> {
+    let window = app.get_webview_window("pake").ok_or("Window not found")?;
+    snap_window_to(&window, position).map_err(|e| format!("Failed to snap window: {}", e))
+}
+
+/// Shrink into a corner, `corner` defaulting to `pip_corner` from pake.json.
+#[command]
+pub fn enter_pip_mode(app: AppHandle, corner: Option<PipCorner>) -> Result<(), String> {
+    let window = app.get_webview_window("pake").ok_or("Window not found")?;
+    let (config, _) = get_pake_config();
+    let window_config = &config.windows[0];
+    enter_pip(
+        &window,
+        window_config,
+        corner.unwrap_or(window_config.pip_corner),
+    )
+    .map_err(|e| format!("Failed to enter picture-in-picture: {}", e))
+}
+
+#[command]
+pub fn exit_pip_mode(app: AppHandle) -> Result<(), String> {
+    let window = app.get_webview_window("pake").ok_or("Window not found")?;
+    match exit_pip(&window) {
+        Ok(true) => Ok(()),
+        Ok(false) => Err("Not in picture-in-picture mode".to_string()),
+        Err(e) => Err(format!("Failed to exit picture-in-picture: {}", e)),
+    }
+}
+
+/// Resize an undecorated window from one of the handles injected by `inject/resize.js`.
+#[command]
+pub fn start_resize(window: WebviewWindow, direction: ResizeEdge) -> Result<(), String> {
+    start_resize_from(&window, direction).map_err(|e| format!("Failed to start resize: {}", e))
+}
+
+/// Run one of the configured `named_actions` in the calling window.
+#[command]
+pub fn run_named_action(window: WebviewWindow, name: String) -> Result<(), String> {
+    let (config, _) = get_pake_config();
+    let action = config.windows[0]
+        .named_actions
+        .get(&name)
+        .ok_or(format!("Unknown action: {}", name))?;
+    window
+        .eval(&user_script(&action.js))
+        .map_err(|e| format!("Failed to run action {}: {}", name, e))
+}
+
+/// Pin the window to the monitor it is on now. Returns the recorded monitor name or index.
+#[command]
+pub fn pin_to_current_monitor(app: AppHandle) -> Result<String, String> {
+    let window = app.get_webview_window("pake").ok_or("Window not found")?;
+    pin_current_monitor(&window)
+}
+
+/// Called by `inject/load_timing.js` once a document has finished loading.
+#[command]
+pub fn report_load_timing(window: WebviewWindow, timing: LoadTiming) {
+    let log = get_pake_config().0.windows[0].log_load_timing;
+    load_timing::record(window.label(), timing, log);
+}
+
+/// Navigation Timing of the calling window's last finished load.
+#[command]
+pub fn get_last_load_timing(window: WebviewWindow) -> Option<LoadTiming> {
+    load_timing::last(window.label())
+}
+
+#[command]
+pub fn record_har_entry(entry: HarEntry) {
+    har::record(entry);
+}
+
+/// Write the captured traffic as a HAR file in the download directory, returning
+/// the path or `None` when the save dialog was cancelled.
+#[command]
+pub async fn export_har(app: AppHandle) -> Result<Option<String>, String> {
+    if !har::is_enabled() {
+        return Err("HAR capture is disabled; set har_capture in pake.json".to_string());
+    }
+    let package_info = app.package_info();
+    let filename = format!("{}.har", package_info.name);
+    let Some(path) = download_destination(&app, &filename).await? else {
+        return Ok(None);
+    };
+    let document = har::to_har(&package_info.name, &package_info.version.to_string());
+    let content = serde_json::to_string_pretty(&document)
+        .map_err(|e| format!("Failed to serialize HAR: {}", e))?;
+    fs::write(&path, content).map_err(|e| format!("Failed to write HAR: {}", e))?;
+
+    log::info!("Exported {} HAR entries to {}", har::len(), path);
+    Ok(Some(path))
+}
+
+#[command]
+pub fn clear_har() {
+    har::clear();
+}
+
+#[command]
+pub fn get_version_info(app: AppHandle) -> VersionInfo {
+    version_info(&app)
+}
+
+#[command]
+pub async fn check_for_updates(app: AppHandle) -> Result<Option<String>, String> {
+    let update = updater::check_for_update(&app).await?;
+    Ok(update.map(|update| update.version))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn page_metadata_deserializes_every_field() {
+        let metadata: PageMetadata = serde_json::from_value(json!({
+            "title": "Inbox",
+            "description": "Mail",
+            "image": "https://example.com/og.png",
+            "favicon": "https://example.com/favicon.ico"
+        }))
+        .unwrap();
+        assert_eq!(metadata.title.as_deref(), Some("Inbox"));
+        assert_eq!(metadata.description.as_deref(), Some("Mail"));
+        assert_eq!(
+            metadata.image.as_deref(),
+            Some("https://example.com/og.png")
+        );
+        assert_eq!(
+            metadata.favicon.as_deref(),
+            Some("https://example.com/favicon.ico")
+        );
+    }
+
+    #[test]
+    fn page_metadata_missing_tags_are_none() {
+        let metadata: PageMetadata = serde_json::from_value(json!({
+            "title": null,
+            "favicon": "https://example.com/favicon.ico"
+        }))
+        .unwrap();
+        assert_eq!(metadata.title, None);
+        assert_eq!(metadata.description, None);
+        assert_eq!(metadata.image, None);
+        assert!(metadata.favicon.is_some());
+    }
+
+    #[test]
+    fn page_metadata_without_an_icon_link_has_no_favicon() {
+        let metadata: PageMetadata = serde_json::from_value(json!({
+            "title": "Inbox",
+            "description": null,
+            "image": null,
+            "favicon": null
+        }))
+        .unwrap();
+        assert_eq!(metadata.title.as_deref(), Some("Inbox"));
+        assert_eq!(metadata.favicon, None);
+    }
+}
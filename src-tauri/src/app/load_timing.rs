@@ -0,0 +1,38 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+
+/// Navigation Timing for a document load, reported by `inject/load_timing.js`.
+/// Phases are durations in milliseconds; `dom_content_loaded_ms` and `load_ms`
+/// count from the start of the navigation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoadTiming {
+    pub url: String,
+    pub dns_ms: f64,
+    pub connect_ms: f64,
+    pub ttfb_ms: f64,
+    pub dom_content_loaded_ms: f64,
+    pub load_ms: f64,
+}
+
+// Latest load per window label
+static TIMINGS: LazyLock<Mutex<HashMap<String, LoadTiming>>> = LazyLock::new(Default::default);
+
+pub fn record(label: &str, timing: LoadTiming, log: bool) {
+    if log {
+        log::info!(
+            "Loaded {} in {:.0}ms (dns {:.0}ms, connect {:.0}ms, ttfb {:.0}ms, DOMContentLoaded {:.0}ms)",
+            timing.url,
+            timing.load_ms,
+            timing.dns_ms,
+            timing.connect_ms,
+            timing.ttfb_ms,
+            timing.dom_content_loaded_ms
+        );
+    }
+    TIMINGS.lock().unwrap().insert(label.to_string(), timing);
+}
+
+pub fn last(label: &str) -> Option<LoadTiming> {
+    TIMINGS.lock().unwrap().get(label).cloned()
+}
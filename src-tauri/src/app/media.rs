@@ -0,0 +1,66 @@
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use tauri::{menu::MenuItem, AppHandle, Manager, Wry};
+
+/// Playback controls, run in the page by `inject/media.js`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MediaAction {
+    PlayPause,
+    NextTrack,
+    PreviousTrack,
+}
+
+impl MediaAction {
+    pub const ALL: [MediaAction; 3] = [
+        MediaAction::PlayPause,
+        MediaAction::NextTrack,
+        MediaAction::PreviousTrack,
+    ];
+
+    /// OS media key registered for this action when `media_controls` is on.
+    pub fn media_key(self) -> &'static str {
+        match self {
+            MediaAction::PlayPause => "MediaPlayPause",
+            MediaAction::NextTrack => "MediaTrackNext",
+            MediaAction::PreviousTrack => "MediaTrackPrevious",
+        }
+    }
+}
+
+/// Tray play/pause item, kept in app state so its label follows the page's playback.
+pub struct MediaMenuItem(pub MenuItem<Wry>);
+
+static PLAYING: AtomicBool = AtomicBool::new(false);
+
+pub fn is_playing() -> bool {
+    PLAYING.load(Ordering::SeqCst)
+}
+
+pub fn play_pause_label(playing: bool) -> &'static str {
+    if playing {
+        "Pause"
+    } else {
+        "Play"
+    }
+}
+
+/// Run `action` in the main window, through the site's Media Session handlers
+/// when it registered any.
+pub fn dispatch(app: &AppHandle, action: MediaAction) -> Result<(), String> {
+    let window = app.get_webview_window("pake").ok_or("Window not found")?;
+    window
+        .eval(format!(
+            "window.pakeMediaAction?.({})",
+            serde_json::to_string(&action).unwrap()
+        ))
+        .map_err(|e| format!("Failed to control playback: {}", e))
+}
+
+/// Record playback reported by the page and relabel the tray item.
+pub fn set_playing(app: &AppHandle, playing: bool) {
+    PLAYING.store(playing, Ordering::SeqCst);
+    if let Some(item) = app.try_state::<MediaMenuItem>() {
+        let _ = item.0.set_text(play_pause_label(playing));
+    }
+}
@@ -2,40 +2,98 @@
 #![cfg(target_os = "macos")]
 
 use std::sync::atomic::{AtomicU32, Ordering};
-use tauri::menu::{AboutMetadata, Menu, MenuItem, PredefinedMenuItem, Submenu};
+use tauri::menu::{AboutMetadata, IsMenuItem, Menu, MenuItem, PredefinedMenuItem, Submenu};
 use tauri::{AppHandle, Manager, WebviewUrl, WebviewWindowBuilder, Wry};
 use tauri_plugin_opener::OpenerExt;
 
+use crate::app::config::ShortcutAction;
+use crate::app::home_urls::next_home_url;
+use crate::app::preferences;
+use crate::app::scripts::{handle_script_menu_event, scripts_submenu};
+use crate::app::setup::force_quit;
+use crate::app::site_data;
+use crate::app::updater::prompt_for_update;
+use crate::app::view_state;
+use crate::app::window::track_loading;
+use crate::util::{get_pake_config, version_info};
+
 static WINDOW_COUNTER: AtomicU32 = AtomicU32::new(1);
 
+const ZOOM_STEP: u32 = 10;
+
 pub fn get_menu(app: &AppHandle<Wry>) -> tauri::Result<Menu<Wry>> {
     let pake_version = env!("CARGO_PKG_VERSION");
     let pake_menu_item_title = format!("Built with Pake V{}", pake_version);
 
-    let menu = Menu::with_items(
-        app,
-        &[
-            &app_menu(app)?,
-            &file_menu(app)?,
-            &edit_menu(app)?,
-            &view_menu(app)?,
-            &navigation_menu(app)?,
-            &window_menu(app)?,
-            &help_menu(app, &pake_menu_item_title)?,
-        ],
-    )?;
+    let (pake_config, _) = get_pake_config();
+    let menu_config = &pake_config.menu;
+
+    let mut submenus = vec![app_menu(app)?, file_menu(app)?];
+    if menu_config.edit {
+        submenus.push(edit_menu(app)?);
+    }
+    if menu_config.view {
+        submenus.push(view_menu(app)?);
+    }
+    submenus.push(navigation_menu(app)?);
+    if let Some(scripts) = scripts_submenu(app, &pake_config.windows[0].scripts_menu)? {
+        submenus.push(scripts);
+    }
+    if menu_config.window {
+        submenus.push(window_menu(app)?);
+    }
+    if menu_config.help {
+        submenus.push(help_menu(
+            app,
+            &pake_menu_item_title,
+            menu_config.report_issue_url.is_some(),
+        )?);
+    }
+
+    let items: Vec<&dyn IsMenuItem<Wry>> = submenus
+        .iter()
+        .map(|submenu| submenu as &dyn IsMenuItem<Wry>)
+        .collect();
+    let menu = Menu::with_items(app, &items)?;
 
     Ok(menu)
 }
 
+// Menu accelerators follow `shortcuts` from pake.json
+fn accelerator(action: ShortcutAction) -> Option<String> {
+    get_pake_config().0.windows[0].shortcut(action)
+}
+
 fn app_menu(app: &AppHandle<Wry>) -> tauri::Result<Submenu<Wry>> {
     let app_menu = Submenu::new(app, "Pake", true)?;
-    let about_metadata = AboutMetadata::default();
+    let version_info = version_info(app);
+    let about_metadata = AboutMetadata {
+        version: Some(version_info.app_version.clone()),
+        credits: Some(version_info.summary()),
+        ..Default::default()
+    };
     app_menu.append(&PredefinedMenuItem::about(
         app,
         Some("Pake"),
         Some(about_metadata),
     )?)?;
+    if get_pake_config().0.updates_enabled() {
+        app_menu.append(&MenuItem::with_id(
+            app,
+            "check_updates",
+            "Check for Updates...",
+            true,
+            None::<&str>,
+        )?)?;
+    }
+    app_menu.append(&PredefinedMenuItem::separator(app)?)?;
+    app_menu.append(&MenuItem::with_id(
+        app,
+        "preferences",
+        "Preferences...",
+        true,
+        accelerator(ShortcutAction::Preferences).as_deref(),
+    )?)?;
     app_menu.append(&PredefinedMenuItem::separator(app)?)?;
     app_menu.append(&PredefinedMenuItem::services(app, None)?)?;
     app_menu.append(&PredefinedMenuItem::separator(app)?)?;
@@ -44,6 +102,13 @@ fn app_menu(app: &AppHandle<Wry>) -> tauri::Result<Submenu<Wry>> {
     app_menu.append(&PredefinedMenuItem::show_all(app, None)?)?;
     app_menu.append(&PredefinedMenuItem::separator(app)?)?;
     app_menu.append(&PredefinedMenuItem::quit(app, None)?)?;
+    app_menu.append(&MenuItem::with_id(
+        app,
+        "force_quit",
+        "Quit (Force)",
+        true,
+        None::<&str>,
+    )?)?;
     Ok(app_menu)
 }
 
@@ -54,17 +119,24 @@ fn file_menu(app: &AppHandle<Wry>) -> tauri::Result<Submenu<Wry>> {
         "new_window",
         "New Window",
         true,
-        Some("CmdOrCtrl+N"),
+        accelerator(ShortcutAction::NewWindow).as_deref(),
     )?)?;
     file_menu.append(&PredefinedMenuItem::separator(app)?)?;
     file_menu.append(&PredefinedMenuItem::close_window(app, None)?)?;
     file_menu.append(&PredefinedMenuItem::separator(app)?)?;
+    file_menu.append(&MenuItem::with_id(
+        app,
+        "site_data",
+        "Site Data...",
+        true,
+        None::<&str>,
+    )?)?;
     file_menu.append(&MenuItem::with_id(
         app,
         "clear_cache_restart",
         "Clear Cache & Restart",
         true,
-        Some("CmdOrCtrl+Shift+Backspace"),
+        accelerator(ShortcutAction::ClearCacheRestart).as_deref(),
     )?)?;
     Ok(file_menu)
 }
@@ -82,7 +154,7 @@ fn edit_menu(app: &AppHandle<Wry>) -> tauri::Result<Submenu<Wry>> {
         "paste_and_match_style",
         "Paste and Match Style",
         true,
-        Some("CmdOrCtrl+Shift+Option+V"),
+        accelerator(ShortcutAction::PasteAndMatchStyle).as_deref(),
     )?)?;
     edit_menu.append(&PredefinedMenuItem::select_all(app, None)?)?;
     edit_menu.append(&PredefinedMenuItem::separator(app)?)?;
@@ -91,7 +163,7 @@ fn edit_menu(app: &AppHandle<Wry>) -> tauri::Result<Submenu<Wry>> {
         "copy_url",
         "Copy URL",
         true,
-        Some("CmdOrCtrl+L"),
+        accelerator(ShortcutAction::CopyUrl).as_deref(),
     )?)?;
     Ok(edit_menu)
 }
@@ -103,7 +175,7 @@ fn view_menu(app: &AppHandle<Wry>) -> tauri::Result<Submenu<Wry>> {
         "reload",
         "Reload",
         true,
-        Some("CmdOrCtrl+R"),
+        accelerator(ShortcutAction::Reload).as_deref(),
     )?)?;
     view_menu.append(&PredefinedMenuItem::separator(app)?)?;
     view_menu.append(&MenuItem::with_id(
@@ -111,23 +183,30 @@ fn view_menu(app: &AppHandle<Wry>) -> tauri::Result<Submenu<Wry>> {
         "zoom_in",
         "Zoom In",
         true,
-        Some("CmdOrCtrl+="),
+        accelerator(ShortcutAction::ZoomIn).as_deref(),
     )?)?;
     view_menu.append(&MenuItem::with_id(
         app,
         "zoom_out",
         "Zoom Out",
         true,
-        Some("CmdOrCtrl+-"),
+        accelerator(ShortcutAction::ZoomOut).as_deref(),
     )?)?;
     view_menu.append(&MenuItem::with_id(
         app,
         "zoom_reset",
         "Actual Size",
         true,
-        Some("CmdOrCtrl+0"),
+        accelerator(ShortcutAction::ZoomReset).as_deref(),
     )?)?;
     view_menu.append(&PredefinedMenuItem::separator(app)?)?;
+    view_menu.append(&MenuItem::with_id(
+        app,
+        "toggle_reader_mode",
+        "Toggle Reader Mode",
+        true,
+        accelerator(ShortcutAction::ReaderMode).as_deref(),
+    )?)?;
     view_menu.append(&PredefinedMenuItem::fullscreen(app, None)?)?;
     view_menu.append(&PredefinedMenuItem::separator(app)?)?;
     view_menu.append(&MenuItem::with_id(
@@ -135,7 +214,7 @@ fn view_menu(app: &AppHandle<Wry>) -> tauri::Result<Submenu<Wry>> {
         "toggle_devtools",
         "Toggle Developer Tools",
         cfg!(debug_assertions),
-        Some("CmdOrCtrl+Option+I"),
+        accelerator(ShortcutAction::Devtools).as_deref(),
     )?)?;
     Ok(view_menu)
 }
@@ -147,21 +226,21 @@ fn navigation_menu(app: &AppHandle<Wry>) -> tauri::Result<Submenu<Wry>> {
         "go_back",
         "Back",
         true,
-        Some("CmdOrCtrl+["),
+        accelerator(ShortcutAction::Back).as_deref(),
     )?)?;
     navigation_menu.append(&MenuItem::with_id(
         app,
         "go_forward",
         "Forward",
         true,
-        Some("CmdOrCtrl+]"),
+        accelerator(ShortcutAction::Forward).as_deref(),
     )?)?;
     navigation_menu.append(&MenuItem::with_id(
         app,
         "go_home",
         "Go Home",
         true,
-        Some("CmdOrCtrl+Shift+H"),
+        accelerator(ShortcutAction::Home).as_deref(),
     )?)?;
     Ok(navigation_menu)
 }
@@ -183,8 +262,18 @@ fn window_menu(app: &AppHandle<Wry>) -> tauri::Result<Submenu<Wry>> {
     Ok(window_menu)
 }
 
-fn help_menu(app: &AppHandle<Wry>, title: &str) -> tauri::Result<Submenu<Wry>> {
+fn help_menu(app: &AppHandle<Wry>, title: &str, report_issue: bool) -> tauri::Result<Submenu<Wry>> {
     let help_menu = Submenu::new(app, "Help", true)?;
+    if report_issue {
+        help_menu.append(&MenuItem::with_id(
+            app,
+            "report_issue",
+            "Report Issue",
+            true,
+            None::<&str>,
+        )?)?;
+        help_menu.append(&PredefinedMenuItem::separator(app)?)?;
+    }
     let github_item = MenuItem::with_id(app, "pake_github_link", title, true, None::<&str>)?;
     help_menu.append(&github_item)?;
     Ok(help_menu)
@@ -200,22 +289,30 @@ pub fn handle_menu_click(app_handle: &AppHandle, id: &str) {
 
                 let main_window_clone = main_window.clone();
                 tauri::async_runtime::spawn(async move {
-                    let home_url = main_window_clone
-                        .url()
-                        .map(|u| {
-                            let origin = format!("{}://{}", u.scheme(), u.host_str().unwrap_or("localhost"));
-                            origin
-                        })
-                        .unwrap_or_else(|_| "about:blank".to_string());
+                    let url = match next_home_url() {
+                        Some(home_url) => WebviewUrl::External(home_url),
+                        None => {
+                            let home_url = main_window_clone
+                                .url()
+                                .map(|u| {
+                                    let origin = format!("{}://{}", u.scheme(), u.host_str().unwrap_or("localhost"));
+                                    origin
+                                })
+                                .unwrap_or_else(|_| "about:blank".to_string());
 
-                    let url = WebviewUrl::External(home_url.parse().unwrap_or_else(|_| "about:blank".parse().unwrap()));
+                            WebviewUrl::External(home_url.parse().unwrap_or_else(|_| "about:blank".parse().unwrap()))
+                        }
+                    };
 
                     if let Err(e) = WebviewWindowBuilder::new(&app_handle_clone, &window_label, url)
                         .title("")
+                        .on_page_load(|window, payload| track_loading(&window, &payload))
+                        .on_document_title_changed(|window, title| view_state::on_page_title_changed(&window, &title))
                         .inner_size(1200.0, 780.0)
                         .build()
+                        .inspect(view_state::restore_view_state)
                     {
-                        eprintln!("Failed to create new window: {}", e);
+                        log::error!("Failed to create new window: {}", e);
                     }
                 });
             }
@@ -225,6 +322,11 @@ pub fn handle_menu_click(app_handle: &AppHandle, id: &str) {
                 .opener()
                 .open_url("https://github.com/tw93/Pake", None::<&str>);
         }
+        "report_issue" => {
+            if let Some(url) = get_pake_config().0.menu.report_issue_url {
+                let _ = app_handle.opener().open_url(url, None::<&str>);
+            }
+        }
         "reload" => {
             if let Some(window) = app_handle.get_webview_window("pake") {
                 let _ = window.eval("window.location.reload()");
@@ -240,19 +342,29 @@ pub fn handle_menu_click(app_handle: &AppHandle, id: &str) {
                 }
             }
         }
-        "zoom_in" => {
-            if let Some(window) = app_handle.get_webview_window("pake") {
-                let _ = window.eval("zoomIn()");
+        "zoom_in" | "zoom_out" | "zoom_reset" => {
+            // Zoom is per window, so follow whichever one is focused
+            let window = app_handle
+                .webview_windows()
+                .into_values()
+                .find(|window| window.is_focused().unwrap_or(false))
+                .or_else(|| app_handle.get_webview_window("pake"));
+            if let Some(window) = window {
+                let current = view_state::zoom(app_handle, window.label());
+                let zoom = match id {
+                    "zoom_in" => current + ZOOM_STEP,
+                    "zoom_out" => current.saturating_sub(ZOOM_STEP),
+                    _ => 100,
+                };
+                let _ = view_state::set_zoom(&window, zoom);
             }
         }
-        "zoom_out" => {
-            if let Some(window) = app_handle.get_webview_window("pake") {
-                let _ = window.eval("zoomOut()");
-            }
+        "check_updates" => {
+            tauri::async_runtime::spawn(prompt_for_update(app_handle.clone(), true));
         }
-        "zoom_reset" => {
+        "toggle_reader_mode" => {
             if let Some(window) = app_handle.get_webview_window("pake") {
-                let _ = window.eval("setZoom('100%')");
+                let _ = window.eval("toggleReaderMode()");
             }
         }
         "go_back" => {
@@ -280,11 +392,26 @@ pub fn handle_menu_click(app_handle: &AppHandle, id: &str) {
                 let _ = window.eval("triggerPasteAsPlainText()");
             }
         }
+        "force_quit" => force_quit(app_handle),
+        "preferences" => {
+            if let Err(e) = preferences::open(app_handle) {
+                log::error!("Failed to open preferences: {}", e);
+            }
+        }
+        "site_data" => {
+            if let Some(window) = app_handle.get_webview_window("pake") {
+                let _ = window.eval("window.pakeShowSiteData?.()");
+            }
+        }
         "clear_cache_restart" => {
             if let Some(window) = app_handle.get_webview_window("pake") {
-                if let Ok(_) = window.clear_all_browsing_data() {
-                    app_handle.restart();
-                }
+                let app_handle = app_handle.clone();
+                tauri::async_runtime::spawn(async move {
+                    match site_data::clear_all_keeping_cookies(&window).await {
+                        Ok(_) => app_handle.restart(),
+                        Err(e) => log::error!("{}", e),
+                    }
+                });
             }
         }
         "always_on_top" => {
@@ -293,6 +420,8 @@ pub fn handle_menu_click(app_handle: &AppHandle, id: &str) {
                 let _ = window.set_always_on_top(!is_on_top);
             }
         }
-        _ => {}
+        id => {
+            handle_script_menu_event(app_handle, id);
+        }
     }
 }
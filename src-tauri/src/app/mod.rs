@@ -1,6 +1,30 @@
+pub mod accessibility;
 pub mod config;
+pub mod content_rules;
+pub mod downloads;
+pub mod environments;
+pub mod feedback;
+pub mod file_drop;
+pub mod har;
+pub mod home_urls;
 pub mod invoke;
+pub mod load_timing;
+pub mod media;
 #[cfg(target_os = "macos")]
 pub mod menu;
+pub mod monitor_state;
+pub mod navigation;
+pub mod permissions;
+pub mod preferences;
+pub mod save_page;
+pub mod screenshot;
+pub mod scripts;
 pub mod setup;
+pub mod site_data;
+pub mod throttle;
+pub mod translate;
+pub mod unread;
+pub mod updater;
+pub mod view_state;
 pub mod window;
+pub mod workspace;
@@ -0,0 +1,165 @@
+use crate::util::{load_app_state, save_app_state};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, PhysicalPosition, PhysicalSize, WebviewWindow};
+
+const MONITOR_GEOMETRY_STATE_KEY: &str = "monitor_geometry";
+
+// Monitors come and go without a window event, so the layout is polled
+const MONITOR_POLL_INTERVAL: Duration = Duration::from_secs(3);
+
+// Moves and resizes arrive in bursts; only the last one is written
+const SAVE_DEBOUNCE: Duration = Duration::from_millis(500);
+
+static SAVE_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+// Monitor setup the window's geometry currently belongs to. Until the poll has
+// caught up with a change, moves made by the OS aren't saved under the new setup.
+static CURRENT_SIGNATURE: Mutex<Option<String>> = Mutex::new(None);
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct MonitorGeometry {
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+    maximized: bool,
+}
+
+/// Identifies the connected monitors: each one's name, position, resolution and
+/// scale, sorted so the order the OS lists them in doesn't matter.
+pub fn monitor_signature(app: &AppHandle) -> Option<String> {
+    let monitors = app.available_monitors().ok()?;
+    let mut parts: Vec<String> = monitors
+        .iter()
+        .map(|monitor| {
+            format!(
+                "{}@{},{}:{}x{}*{}",
+                monitor.name().map_or("", String::as_str),
+                monitor.position().x,
+                monitor.position().y,
+                monitor.size().width,
+                monitor.size().height,
+                monitor.scale_factor()
+            )
+        })
+        .collect();
+    if parts.is_empty() {
+        return None;
+    }
+    parts.sort();
+    Some(parts.join("|"))
+}
+
+fn saved_geometries(app: &AppHandle) -> HashMap<String, MonitorGeometry> {
+    load_app_state(app, MONITOR_GEOMETRY_STATE_KEY)
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+}
+
+/// Put the window where it was last left with this monitor setup. Returns `false`
+/// when the setup hasn't been seen before.
+fn restore(window: &WebviewWindow, signature: &str) -> tauri::Result<bool> {
+    let Some(geometry) = saved_geometries(window.app_handle())
+        .get(signature)
+        .copied()
+    else {
+        return Ok(false);
+    };
+    if window.is_fullscreen()? {
+        return Ok(true);
+    }
+    if window.is_maximized()? {
+        window.unmaximize()?;
+    }
+    window.set_size(PhysicalSize::new(geometry.width, geometry.height))?;
+    window.set_position(PhysicalPosition::new(geometry.x, geometry.y))?;
+    if geometry.maximized {
+        window.maximize()?;
+    }
+    Ok(true)
+}
+
+fn save(window: &WebviewWindow) -> Result<(), String> {
+    let app = window.app_handle();
+    let Some(signature) = monitor_signature(app) else {
+        return Ok(());
+    };
+    if CURRENT_SIGNATURE.lock().unwrap().as_deref() != Some(signature.as_str()) {
+        return Ok(());
+    }
+    if window.is_fullscreen().unwrap_or(false) || window.is_minimized().unwrap_or(false) {
+        return Ok(());
+    }
+
+    let mut geometries = saved_geometries(app);
+    let maximized = window.is_maximized().unwrap_or(false);
+    // A maximized window keeps the size it will un-maximize to
+    let geometry = match geometries.get(&signature) {
+        Some(saved) if maximized => MonitorGeometry {
+            maximized,
+            ..*saved
+        },
+        _ => {
+            let position = window.outer_position().map_err(|e| e.to_string())?;
+            let size = window.inner_size().map_err(|e| e.to_string())?;
+            MonitorGeometry {
+                x: position.x,
+                y: position.y,
+                width: size.width,
+                height: size.height,
+                maximized,
+            }
+        }
+    };
+    geometries.insert(signature, geometry);
+    save_app_state(
+        app,
+        MONITOR_GEOMETRY_STATE_KEY,
+        serde_json::to_value(geometries).unwrap(),
+    )
+}
+
+/// Record the geometry for the current monitor setup once the window settles.
+pub fn schedule_save(window: &WebviewWindow) {
+    let generation = SAVE_GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+    let window = window.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(SAVE_DEBOUNCE).await;
+        if SAVE_GENERATION.load(Ordering::SeqCst) != generation {
+            return;
+        }
+        if let Err(e) = save(&window) {
+            log::warn!("Failed to save monitor geometry: {}", e);
+        }
+    });
+}
+
+/// Restore the geometry for the monitors connected now, then again whenever
+/// the setup changes.
+pub fn watch(window: &WebviewWindow) {
+    let window = window.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let signature = monitor_signature(window.app_handle());
+            let changed = {
+                let mut current = CURRENT_SIGNATURE.lock().unwrap();
+                let changed = *current != signature;
+                current.clone_from(&signature);
+                changed
+            };
+            if let (true, Some(signature)) = (changed, &signature) {
+                match restore(&window, signature) {
+                    Ok(true) => {}
+                    // First time on this setup: start it from where the window is now
+                    Ok(false) => schedule_save(&window),
+                    Err(e) => log::warn!("Failed to restore monitor geometry: {}", e),
+                }
+            }
+            tokio::time::sleep(MONITOR_POLL_INTERVAL).await;
+        }
+    });
+}
@@ -0,0 +1,169 @@
+use regex::Regex;
+use tauri::AppHandle;
+
+/// What `navigation_deny` / `navigation_allow` say about a URL.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NavigationDecision {
+    Deny,
+    Allow,
+    /// Neither list matched; the usual host checks apply.
+    Default,
+}
+
+/// Compiled navigation allow/deny lists. Deny wins over allow when both match.
+#[derive(Debug, Default)]
+pub struct NavigationRules {
+    allow: Vec<Regex>,
+    deny: Vec<Regex>,
+}
+
+impl NavigationRules {
+    /// Invalid patterns are reported and skipped so one typo doesn't drop the whole list.
+    pub fn new(allow: &[String], deny: &[String]) -> Self {
+        Self {
+            allow: compile_patterns(allow),
+            deny: compile_patterns(deny),
+        }
+    }
+
+    pub fn decide(&self, url: &str) -> NavigationDecision {
+        if self.deny.iter().any(|pattern| pattern.is_match(url)) {
+            NavigationDecision::Deny
+        } else if self.allow.iter().any(|pattern| pattern.is_match(url)) {
+            NavigationDecision::Allow
+        } else {
+            NavigationDecision::Default
+        }
+    }
+}
+
+fn compile_patterns(patterns: &[String]) -> Vec<Regex> {
+    patterns
+        .iter()
+        .filter_map(|pattern| match compile_pattern(pattern) {
+            Ok(regex) => Some(regex),
+            Err(e) => {
+                log::warn!("Invalid navigation pattern {}: {}", pattern, e);
+                None
+            }
+        })
+        .collect()
+}
+
+/// `/.../` is an unanchored regex; anything else is a glob over the whole URL
+/// where `*` matches any run of characters and `?` a single one.
+pub fn compile_pattern(pattern: &str) -> Result<Regex, regex::Error> {
+    if let Some(regex) = pattern
+        .strip_prefix('/')
+        .and_then(|rest| rest.strip_suffix('/'))
+        .filter(|regex| !regex.is_empty())
+    {
+        return Regex::new(regex);
+    }
+
+    let glob: String = pattern
+        .chars()
+        .map(|c| match c {
+            '*' => ".*".to_string(),
+            '?' => ".".to_string(),
+            c => regex::escape(&c.to_string()),
+        })
+        .collect();
+    Regex::new(&format!("^{}$", glob))
+}
+
+pub fn notify_denied(app: &AppHandle, url: &str) {
+    use tauri_plugin_notification::NotificationExt;
+
+    log::info!("Blocked navigation to: {}", url);
+    let _ = app
+        .notification()
+        .builder()
+        .title("Navigation blocked")
+        .body(url)
+        .show();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(allow: &[&str], deny: &[&str]) -> NavigationRules {
+        let to_vec = |patterns: &[&str]| patterns.iter().map(|p| p.to_string()).collect::<Vec<_>>();
+        NavigationRules::new(&to_vec(allow), &to_vec(deny))
+    }
+
+    #[test]
+    fn deny_beats_allow() {
+        let rules = rules(&["https://example.com/*"], &["https://example.com/admin*"]);
+        assert_eq!(
+            rules.decide("https://example.com/admin/users"),
+            NavigationDecision::Deny
+        );
+        assert_eq!(
+            rules.decide("https://example.com/inbox"),
+            NavigationDecision::Allow
+        );
+    }
+
+    #[test]
+    fn allow_beats_default() {
+        let rules = rules(&["https://docs.example.com/*"], &[]);
+        assert_eq!(
+            rules.decide("https://docs.example.com/guide"),
+            NavigationDecision::Allow
+        );
+        assert_eq!(
+            rules.decide("https://other.com/"),
+            NavigationDecision::Default
+        );
+        assert_eq!(
+            NavigationRules::default().decide("https://other.com/"),
+            NavigationDecision::Default
+        );
+    }
+
+    #[test]
+    fn globs_match_the_whole_url() {
+        let rules = rules(&["https://*.example.com/?"], &[]);
+        assert_eq!(
+            rules.decide("https://a.example.com/x"),
+            NavigationDecision::Allow
+        );
+        assert_eq!(
+            rules.decide("https://a.example.com/xy"),
+            NavigationDecision::Default
+        );
+        // Dots and other regex characters in globs are literal
+        assert_eq!(
+            rules.decide("https://a.exampleXcom/x"),
+            NavigationDecision::Default
+        );
+    }
+
+    #[test]
+    fn slashed_patterns_are_unanchored_regexes() {
+        let rules = rules(&[], &[r"//logout(\?|$)/"]);
+        assert_eq!(
+            rules.decide("https://example.com/logout"),
+            NavigationDecision::Deny
+        );
+        assert_eq!(
+            rules.decide("https://example.com/logout?next=/"),
+            NavigationDecision::Deny
+        );
+        assert_eq!(
+            rules.decide("https://example.com/logouts"),
+            NavigationDecision::Default
+        );
+    }
+
+    #[test]
+    fn invalid_patterns_are_skipped() {
+        let rules = rules(&["/(unclosed/", "https://example.com/*"], &[]);
+        assert_eq!(
+            rules.decide("https://example.com/a"),
+            NavigationDecision::Allow
+        );
+    }
+}
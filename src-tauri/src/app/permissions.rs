@@ -0,0 +1,201 @@
+use crate::app::config::{PermissionPolicy, PermissionsConfig};
+use crate::app::environments;
+use crate::util::{get_pake_config, host_matches};
+use tauri::WebviewWindow;
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+use tokio::sync::oneshot;
+
+/// Answer camera, microphone, geolocation, notification and clipboard requests per
+/// `permissions` and `clipboard`. WebView2 and WebKitGTK get native handlers; `Prompt`
+/// keeps WebView2's own prompt and shows a dialog on Linux. WKWebView's request hook
+/// lives on the UI delegate wry already owns, so macOS relies on
+/// `inject/permissions.js` to refuse denied capabilities inside the page.
+pub fn apply_permissions(
+    window: &WebviewWindow,
+    permissions: PermissionsConfig,
+    clipboard: PermissionPolicy,
+) {
+    #[cfg(target_os = "linux")]
+    let app = {
+        use tauri::Manager;
+        window.app_handle().clone()
+    };
+
+    let result = window.with_webview(move |_webview| {
+        #[cfg(target_os = "linux")]
+        {
+            use webkit2gtk::glib::prelude::*;
+            use webkit2gtk::{
+                GeolocationPermissionRequest, NotificationPermissionRequest, PermissionRequestExt,
+                SettingsExt, UserMediaPermissionRequest, WebViewExt,
+            };
+
+            // WebKitGTK has no clipboard permission request, only an on/off setting
+            if let Some(settings) = WebViewExt::settings(&_webview.inner()) {
+                settings.set_javascript_can_access_clipboard(clipboard == PermissionPolicy::Allow);
+            }
+
+            _webview
+                .inner()
+                .connect_permission_request(move |_, request| {
+                    let (policy, subject) = if let Some(media) =
+                        request.downcast_ref::<UserMediaPermissionRequest>()
+                    {
+                        let video = webkit2gtk::user_media_permission_is_for_video_device(media);
+                        let audio = webkit2gtk::user_media_permission_is_for_audio_device(media);
+                        let wanted: Vec<_> =
+                            [(video, permissions.camera), (audio, permissions.microphone)]
+                                .into_iter()
+                                .filter_map(|(requested, policy)| requested.then_some(policy))
+                                .collect();
+                        // A combined request is only as permissive as its strictest part
+                        let policy = if wanted.contains(&PermissionPolicy::Deny) {
+                            PermissionPolicy::Deny
+                        } else if wanted.contains(&PermissionPolicy::Prompt) {
+                            PermissionPolicy::Prompt
+                        } else {
+                            PermissionPolicy::Allow
+                        };
+                        let subject = match (video, audio) {
+                            (true, true) => "camera and microphone",
+                            (true, false) => "camera",
+                            _ => "microphone",
+                        };
+                        (policy, subject)
+                    } else if request.is::<GeolocationPermissionRequest>() {
+                        (permissions.geolocation, "location")
+                    } else if request.is::<NotificationPermissionRequest>() {
+                        (permissions.notifications, "notifications")
+                    } else {
+                        return false;
+                    };
+
+                    match policy {
+                        PermissionPolicy::Allow => request.allow(),
+                        PermissionPolicy::Deny => request.deny(),
+                        PermissionPolicy::Prompt => {
+                            let (sender, receiver) = oneshot::channel();
+                            app.dialog()
+                                .message(format!("This page wants to use your {}.", subject))
+                                .title("Allow access?")
+                                .kind(MessageDialogKind::Info)
+                                .buttons(MessageDialogButtons::OkCancelCustom(
+                                    "Allow".to_string(),
+                                    "Deny".to_string(),
+                                ))
+                                .show(move |allowed| {
+                                    let _ = sender.send(allowed);
+                                });
+                            // The request is not Send, so answer it back on the GTK thread
+                            let request = request.clone();
+                            webkit2gtk::glib::MainContext::default().spawn_local(async move {
+                                if receiver.await.unwrap_or(false) {
+                                    request.allow();
+                                } else {
+                                    request.deny();
+                                }
+                            });
+                        }
+                    }
+                    true
+                });
+        }
+
+        #[cfg(target_os = "windows")]
+        unsafe {
+            use webview2_com::Microsoft::Web::WebView2::Win32::{
+                COREWEBVIEW2_PERMISSION_KIND, COREWEBVIEW2_PERMISSION_KIND_CAMERA,
+                COREWEBVIEW2_PERMISSION_KIND_CLIPBOARD_READ,
+                COREWEBVIEW2_PERMISSION_KIND_GEOLOCATION, COREWEBVIEW2_PERMISSION_KIND_MICROPHONE,
+                COREWEBVIEW2_PERMISSION_KIND_NOTIFICATIONS, COREWEBVIEW2_PERMISSION_STATE_ALLOW,
+                COREWEBVIEW2_PERMISSION_STATE_DEFAULT, COREWEBVIEW2_PERMISSION_STATE_DENY,
+            };
+            use webview2_com::PermissionRequestedEventHandler;
+
+            let Ok(core) = _webview.controller().CoreWebView2() else {
+                return;
+            };
+            let handler = PermissionRequestedEventHandler::create(Box::new(move |_, args| {
+                let Some(args) = args else {
+                    return Ok(());
+                };
+                let mut kind = COREWEBVIEW2_PERMISSION_KIND::default();
+                args.PermissionKind(&mut kind)?;
+                let policy = match kind {
+                    COREWEBVIEW2_PERMISSION_KIND_CAMERA => permissions.camera,
+                    COREWEBVIEW2_PERMISSION_KIND_MICROPHONE => permissions.microphone,
+                    COREWEBVIEW2_PERMISSION_KIND_GEOLOCATION => permissions.geolocation,
+                    COREWEBVIEW2_PERMISSION_KIND_NOTIFICATIONS => permissions.notifications,
+                    COREWEBVIEW2_PERMISSION_KIND_CLIPBOARD_READ => clipboard,
+                    _ => return Ok(()),
+                };
+                args.SetState(match policy {
+                    PermissionPolicy::Allow => COREWEBVIEW2_PERMISSION_STATE_ALLOW,
+                    PermissionPolicy::Deny => COREWEBVIEW2_PERMISSION_STATE_DENY,
+                    PermissionPolicy::Prompt => COREWEBVIEW2_PERMISSION_STATE_DEFAULT,
+                })?;
+                Ok(())
+            }));
+            let mut token = 0;
+            let _ = core.add_PermissionRequested(&handler, &mut token);
+        }
+
+        #[cfg(target_os = "macos")]
+        let _ = (permissions, clipboard);
+    });
+
+    if let Err(e) = result {
+        log::error!("Failed to configure permission handling: {}", e);
+    }
+}
+
+/// Gate the native clipboard bridge: only pages on the app's own host may use
+/// it, writes pass unless `clipboard_access` is `Deny`, and reads ask first
+/// under `Prompt`.
+pub async fn authorize_clipboard(window: &WebviewWindow, write: bool) -> Result<(), String> {
+    let (config, _) = get_pake_config();
+    let window_config = &config.windows[0];
+    let policy = window_config.clipboard_access;
+    if policy == PermissionPolicy::Deny {
+        return Err("Clipboard access is denied".to_string());
+    }
+
+    let page_host = window
+        .url()
+        .map_err(|e| format!("Failed to read page URL: {}", e))?
+        .host_str()
+        .map(str::to_string);
+    let app_host = environments::allowed_host(window_config);
+    let on_app_host = match (page_host.as_deref(), app_host.as_deref()) {
+        (Some(page_host), Some(app_host)) => host_matches(page_host, app_host),
+        _ => false,
+    };
+    if !on_app_host {
+        return Err(format!(
+            "Clipboard access is limited to {}",
+            app_host.as_deref().unwrap_or("the app's own pages")
+        ));
+    }
+
+    if write || policy == PermissionPolicy::Allow {
+        return Ok(());
+    }
+    let (sender, receiver) = oneshot::channel();
+    window
+        .dialog()
+        .message("This page wants to read your clipboard.")
+        .title("Allow clipboard access?")
+        .kind(MessageDialogKind::Info)
+        .buttons(MessageDialogButtons::OkCancelCustom(
+            "Allow".to_string(),
+            "Deny".to_string(),
+        ))
+        .show(move |allowed| {
+            let _ = sender.send(allowed);
+        });
+    if receiver.await.unwrap_or(false) {
+        Ok(())
+    } else {
+        Err("Clipboard access was refused".to_string())
+    }
+}
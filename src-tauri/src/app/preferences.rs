@@ -0,0 +1,174 @@
+use crate::app::invoke::{is_dnd_enabled, set_dnd_state};
+use crate::app::view_state;
+use crate::app::window::track_loading;
+use crate::util::{broadcast, get_pake_config, load_setting, save_setting, THEME_CHANGED_EVENT};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, Theme, WebviewUrl, WebviewWindow, WebviewWindowBuilder};
+
+pub const PREFERENCES_LABEL: &str = "pake-preferences";
+const THEME_SETTING_KEY: &str = "theme";
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AppTheme {
+    #[default]
+    System,
+    Light,
+    Dark,
+}
+
+impl AppTheme {
+    fn native(self) -> Option<Theme> {
+        match self {
+            AppTheme::System => None,
+            AppTheme::Light => Some(Theme::Light),
+            AppTheme::Dark => Some(Theme::Dark),
+        }
+    }
+
+    /// Forced page color scheme, `None` to follow the OS.
+    fn color_scheme(self) -> Option<&'static str> {
+        match self {
+            AppTheme::System => None,
+            AppTheme::Light => Some("light"),
+            AppTheme::Dark => Some("dark"),
+        }
+    }
+}
+
+/// Payload of `pake://theme-changed`: the chosen theme and whether it resolved to dark.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct ThemeChange {
+    pub theme: AppTheme,
+    pub dark: bool,
+}
+
+/// Runtime options shown by the preferences window, read from the main window.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct Preferences {
+    pub zoom: u32,
+    pub theme: AppTheme,
+    pub always_on_top: bool,
+    pub do_not_disturb: bool,
+}
+
+/// Fields left out are kept as they are.
+#[derive(Debug, Default, Deserialize)]
+pub struct PreferenceChanges {
+    pub zoom: Option<u32>,
+    pub theme: Option<AppTheme>,
+    pub always_on_top: Option<bool>,
+    pub do_not_disturb: Option<bool>,
+}
+
+/// Focus the preferences window, opening it on first use.
+pub fn open(app: &AppHandle) -> tauri::Result<()> {
+    if let Some(window) = app.get_webview_window(PREFERENCES_LABEL) {
+        window.show()?;
+        return window.set_focus();
+    }
+    WebviewWindowBuilder::new(
+        app,
+        PREFERENCES_LABEL,
+        WebviewUrl::App("pake_preferences.html".into()),
+    )
+    .title("Preferences")
+    .on_page_load(|window, payload| track_loading(&window, &payload))
+    .inner_size(420.0, 320.0)
+    .resizable(false)
+    .minimizable(false)
+    .maximizable(false)
+    .build()?;
+    Ok(())
+}
+
+fn saved_theme(app: &AppHandle) -> AppTheme {
+    load_setting(app, THEME_SETTING_KEY)
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+}
+
+fn apply_theme(app: &AppHandle, theme: AppTheme) {
+    for window in app.webview_windows().values() {
+        let _ = window.set_theme(theme.native());
+        set_color_scheme_meta(window, theme.color_scheme());
+    }
+}
+
+/// Point the page's `<meta name="color-scheme">` at `mode` ("light" or "dark"),
+/// or back at the OS appearance for `None`. No-op unless `color_scheme_meta` is on.
+pub fn set_color_scheme_meta(window: &WebviewWindow, mode: Option<&str>) {
+    if !get_pake_config().0.windows[0].color_scheme_meta {
+        return;
+    }
+    let mode = mode.map_or("null".to_string(), |mode| format!("\"{}\"", mode));
+    let _ = window.eval(format!("window.pakeSetColorSchemeMeta?.({})", mode));
+}
+
+/// Re-apply the saved theme's color scheme after a load.
+pub fn sync_color_scheme_meta(window: &WebviewWindow) {
+    set_color_scheme_meta(window, saved_theme(window.app_handle()).color_scheme());
+}
+
+fn emit_theme_change(app: &AppHandle, theme: AppTheme, dark: bool) {
+    let _ = broadcast(app, THEME_CHANGED_EVENT, ThemeChange { theme, dark });
+}
+
+/// Follow a live OS appearance change while the theme is "system": webviews
+/// that don't pick it up natively get it through `inject/theme_sync.js`.
+pub fn sync_system_theme(app: &AppHandle, theme: Theme) {
+    if saved_theme(app) != AppTheme::System {
+        return;
+    }
+    let dark = theme == Theme::Dark;
+    let mode = if dark { "dark" } else { "light" };
+    let script = format!("window.pakeApplySystemTheme?.(\"{}\")", mode);
+    for window in app.webview_windows().values() {
+        let _ = window.eval(&script);
+    }
+    emit_theme_change(app, AppTheme::System, dark);
+}
+
+/// Re-apply a theme chosen in an earlier session.
+pub fn restore_theme(app: &AppHandle) {
+    let theme = saved_theme(app);
+    if theme != AppTheme::System {
+        apply_theme(app, theme);
+    }
+}
+
+pub fn current(app: &AppHandle) -> Result<Preferences, String> {
+    let window = app.get_webview_window("pake").ok_or("Window not found")?;
+    Ok(Preferences {
+        zoom: view_state::zoom(app, window.label()),
+        theme: saved_theme(app),
+        always_on_top: window.is_always_on_top().unwrap_or(false),
+        do_not_disturb: is_dnd_enabled(),
+    })
+}
+
+pub fn apply(app: &AppHandle, changes: PreferenceChanges) -> Result<Preferences, String> {
+    let window = app.get_webview_window("pake").ok_or("Window not found")?;
+    if let Some(zoom) = changes.zoom {
+        view_state::set_zoom(&window, zoom)?;
+    }
+    if let Some(theme) = changes.theme {
+        save_setting(app, THEME_SETTING_KEY, serde_json::to_value(theme).unwrap())?;
+        apply_theme(app, theme);
+        let dark = match theme {
+            AppTheme::System => window.theme().is_ok_and(|theme| theme == Theme::Dark),
+            AppTheme::Light => false,
+            AppTheme::Dark => true,
+        };
+        emit_theme_change(app, theme, dark);
+    }
+    if let Some(always_on_top) = changes.always_on_top {
+        window
+            .set_always_on_top(always_on_top)
+            .map_err(|e| format!("Failed to set always on top: {}", e))?;
+    }
+    if let Some(enabled) = changes.do_not_disturb {
+        set_dnd_state(app, enabled)?;
+    }
+    current(app)
+}
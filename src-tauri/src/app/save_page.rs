@@ -0,0 +1,287 @@
+use crate::util::eval_with_result_timeout;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::Duration;
+use tauri::WebviewWindow;
+#[cfg(not(target_os = "macos"))]
+use tokio::sync::oneshot;
+
+#[cfg(not(target_os = "macos"))]
+type Reply<T> = oneshot::Sender<Result<T, String>>;
+
+// Saved pages stop growing here; resources past it stay as links
+const MAX_PAGE_BYTES: usize = 100 * 1024 * 1024;
+
+// Inlining fetches every image and stylesheet again, which takes a while on big pages
+const SERIALIZE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// How `save_page` writes the document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SavePageFormat {
+    /// One `.html` file with stylesheets and images inlined as data URIs. Scripts
+    /// are dropped, so the copy shows the page as it looked when saved.
+    SingleFileHtml,
+    /// The webview's own MHTML archive. Windows and Linux only.
+    Mhtml,
+}
+
+impl SavePageFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            SavePageFormat::SingleFileHtml => "html",
+            SavePageFormat::Mhtml => "mhtml",
+        }
+    }
+}
+
+// Rebuilds the DOM with every stylesheet, image and poster as a data URI until
+// the byte budget runs out; anything after that keeps its absolute URL
+fn serialize_script(max_bytes: usize) -> String {
+    format!(
+        r#"(async (maxBytes) => {{
+  let budget = maxBytes;
+  const cache = new Map();
+
+  function readAsDataUrl(blob) {{
+    return new Promise((resolve, reject) => {{
+      const reader = new FileReader();
+      reader.onload = () => resolve(reader.result);
+      reader.onerror = () => reject(reader.error);
+      reader.readAsDataURL(blob);
+    }});
+  }}
+
+  function dataUrl(url, base = document.baseURI) {{
+    let absolute;
+    try {{
+      absolute = new URL(url, base).href;
+    }} catch {{
+      return Promise.resolve(url);
+    }}
+    if (absolute.startsWith("data:")) return Promise.resolve(absolute);
+    if (!cache.has(absolute)) {{
+      cache.set(absolute, (async () => {{
+        try {{
+          const response = await fetch(absolute);
+          if (!response.ok) return absolute;
+          const blob = await response.blob();
+          if (blob.size > budget) return absolute;
+          budget -= blob.size;
+          return await readAsDataUrl(blob);
+        }} catch {{
+          return absolute;
+        }}
+      }})());
+    }}
+    return cache.get(absolute);
+  }}
+
+  async function inlineCssUrls(css, base) {{
+    const pattern = /url\(\s*(['"]?)([^'")]+)\1\s*\)/g;
+    const urls = [...new Set(Array.from(css.matchAll(pattern), (match) => match[2]))];
+    const inlined = new Map(
+      await Promise.all(urls.map(async (url) => [url, await dataUrl(url, base)])),
+    );
+    return css.replace(pattern, (match, quote, url) => `url("${{inlined.get(url)}}")`);
+  }}
+
+  // Cross-origin sheets hide their rules from CSSOM, so those are fetched instead
+  async function sheetText(sheet) {{
+    let css;
+    try {{
+      css = Array.from(sheet.cssRules, (rule) => rule.cssText).join("\n");
+    }} catch {{
+      try {{
+        css = await (await fetch(sheet.href)).text();
+      }} catch {{
+        return "";
+      }}
+    }}
+    css = await inlineCssUrls(css, sheet.href || document.baseURI);
+    const media = sheet.media?.mediaText;
+    return media ? `@media ${{media}} {{\n${{css}}\n}}` : css;
+  }}
+
+  const root = document.documentElement;
+  const clone = root.cloneNode(true);
+  const pairs = (selector) => {{
+    const copies = clone.querySelectorAll(selector);
+    return Array.from(root.querySelectorAll(selector), (original, i) => [original, copies[i]]);
+  }};
+
+  const images = pairs("img, input[type=image]").map(async ([original, copy]) => {{
+    const source = original.currentSrc || original.src;
+    if (!source) return;
+    copy.src = await dataUrl(source);
+    copy.removeAttribute("srcset");
+    copy.removeAttribute("loading");
+  }});
+  const posters = pairs("video[poster]").map(async ([original, copy]) => {{
+    copy.poster = await dataUrl(original.poster);
+  }});
+  const inlineStyles = pairs('[style*="url("]').map(async ([original, copy]) => {{
+    copy.setAttribute("style", await inlineCssUrls(original.getAttribute("style"), document.baseURI));
+  }});
+  for (const [original, copy] of pairs("a[href], area[href]")) copy.href = original.href;
+  for (const [original, copy] of pairs("iframe[src]")) copy.src = original.src;
+  const sheets = Promise.all(Array.from(document.styleSheets, sheetText));
+  await Promise.all([...images, ...posters, ...inlineStyles]);
+
+  clone
+    .querySelectorAll('script, noscript, style, link[rel~="stylesheet"], link[rel~="preload"], link[rel~="modulepreload"], source[srcset]')
+    .forEach((element) => element.remove());
+  const head = clone.querySelector("head") ?? clone.insertBefore(document.createElement("head"), clone.firstChild);
+  const charset = document.createElement("meta");
+  charset.setAttribute("charset", "utf-8");
+  head.prepend(charset);
+  for (const css of await sheets) {{
+    const style = document.createElement("style");
+    style.textContent = css;
+    head.appendChild(style);
+  }}
+
+  return "<!DOCTYPE html>\n" + clone.outerHTML;
+}})({max_bytes})"#
+    )
+}
+
+/// Filename for the saved page, built from its title.
+pub fn filename(window: &WebviewWindow, format: SavePageFormat) -> String {
+    let title = window.title().unwrap_or_default();
+    let name: String = title
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '-',
+            c if c.is_control() => ' ',
+            c => c,
+        })
+        .take(100)
+        .collect();
+    let name = name.trim().trim_matches('.');
+    let name = if name.is_empty() { "page" } else { name };
+    format!("{}.{}", name, format.extension())
+}
+
+/// Write the window's current page to `path` in `format`.
+pub async fn save(
+    window: &WebviewWindow,
+    format: SavePageFormat,
+    path: PathBuf,
+) -> Result<(), String> {
+    match format {
+        SavePageFormat::SingleFileHtml => {
+            let value = eval_with_result_timeout(
+                window,
+                &serialize_script(MAX_PAGE_BYTES),
+                SERIALIZE_TIMEOUT,
+            )
+            .await?;
+            let html = value.as_str().ok_or("Failed to serialize page")?;
+            if html.len() > MAX_PAGE_BYTES {
+                return Err(format!(
+                    "Page is too large to save ({} MB)",
+                    html.len() / 1024 / 1024
+                ));
+            }
+            std::fs::write(&path, html).map_err(|e| format!("Failed to save page: {}", e))
+        }
+        SavePageFormat::Mhtml => save_mhtml(window, path).await,
+    }
+}
+
+#[cfg(target_os = "macos")]
+async fn save_mhtml(_window: &WebviewWindow, _path: PathBuf) -> Result<(), String> {
+    Err("MHTML is not supported on macOS, save as single_file_html instead".to_string())
+}
+
+#[cfg(not(target_os = "macos"))]
+async fn save_mhtml(window: &WebviewWindow, path: PathBuf) -> Result<(), String> {
+    let (sender, receiver) = oneshot::channel();
+    let target = path.clone();
+    window
+        .with_webview(move |webview| platform::save_mhtml(webview, target, sender))
+        .map_err(|e| format!("Failed to save page: {}", e))?;
+    receiver
+        .await
+        .map_err(|_| "Save request was dropped".to_string())??;
+
+    let size = std::fs::metadata(&path).map(|meta| meta.len()).unwrap_or(0);
+    if size > MAX_PAGE_BYTES as u64 {
+        let _ = std::fs::remove_file(&path);
+        return Err(format!(
+            "Page is too large to save ({} MB)",
+            size / 1024 / 1024
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use super::Reply;
+    use std::path::PathBuf;
+    use tauri::webview::PlatformWebview;
+    use webkit2gtk::gio::{Cancellable, File};
+    use webkit2gtk::{SaveMode, WebViewExt};
+
+    pub fn save_mhtml(webview: PlatformWebview, path: PathBuf, reply: Reply<()>) {
+        webview.inner().save_to_file(
+            &File::for_path(path),
+            SaveMode::Mhtml,
+            None::<&Cancellable>,
+            move |result| {
+                let _ = reply.send(result.map_err(|e| format!("Failed to save page: {}", e)));
+            },
+        );
+    }
+}
+
+/// WebView2 has no MHTML API of its own; the DevTools protocol snapshot is the same format.
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::{Reply, MAX_PAGE_BYTES};
+    use serde_json::Value;
+    use std::path::PathBuf;
+    use tauri::webview::PlatformWebview;
+    use webview2_com::CallDevToolsProtocolMethodCompletedHandler;
+    use windows::core::HSTRING;
+
+    pub fn save_mhtml(webview: PlatformWebview, path: PathBuf, reply: Reply<()>) {
+        let core = match unsafe { webview.controller().CoreWebView2() } {
+            Ok(core) => core,
+            Err(e) => {
+                let _ = reply.send(Err(e.to_string()));
+                return;
+            }
+        };
+        let handler =
+            CallDevToolsProtocolMethodCompletedHandler::create(Box::new(move |result, json| {
+                let saved = result
+                    .map_err(|e| format!("Failed to save page: {}", e))
+                    .and_then(|_| serde_json::from_str::<Value>(&json).map_err(|e| e.to_string()))
+                    .and_then(|value| {
+                        let data = value["data"].as_str().ok_or("Snapshot returned no data")?;
+                        if data.len() > MAX_PAGE_BYTES {
+                            return Err(format!(
+                                "Page is too large to save ({} MB)",
+                                data.len() / 1024 / 1024
+                            ));
+                        }
+                        std::fs::write(&path, data)
+                            .map_err(|e| format!("Failed to save page: {}", e))
+                    });
+                let _ = reply.send(saved);
+                Ok(())
+            }));
+        if let Err(e) = unsafe {
+            core.CallDevToolsProtocolMethod(
+                &HSTRING::from("Page.captureSnapshot"),
+                &HSTRING::from(r#"{"format":"mhtml"}"#),
+                &handler,
+            )
+        } {
+            log::error!("Failed to call Page.captureSnapshot: {}", e);
+        }
+    }
+}
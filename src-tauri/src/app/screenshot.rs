@@ -0,0 +1,335 @@
+use crate::util::{encode_png, eval_with_result};
+use serde::Deserialize;
+use tauri::WebviewWindow;
+use tokio::sync::oneshot;
+
+type Reply<T> = oneshot::Sender<Result<T, String>>;
+
+// Tall pages stop here, in device pixels, to keep the stitched image a sane size
+const MAX_CAPTURE_HEIGHT: u32 = 32_000;
+
+/// The last component of a page-supplied name, with a `.png` extension, so the
+/// screenshot can't be written outside the download directory.
+pub fn png_filename(name: &str) -> Option<String> {
+    let name = name.rsplit(['/', '\\']).next()?.trim();
+    let stem = name
+        .strip_suffix(".png")
+        .or_else(|| name.strip_suffix(".PNG"))
+        .unwrap_or(name);
+    if stem.is_empty() || stem.chars().all(|c| c == '.') {
+        return None;
+    }
+    Some(format!("{}.png", stem))
+}
+
+#[derive(Debug, Deserialize)]
+struct PageSize {
+    content_height: f64,
+    viewport_height: f64,
+}
+
+// Remember where the reader was and turn off smooth scrolling so each step lands at once
+const PREPARE_SCRIPT: &str = r#"(() => {
+  const root = document.scrollingElement || document.documentElement;
+  window.__pakeCapture = {
+    x: scrollX,
+    y: scrollY,
+    behavior: root.style.scrollBehavior,
+    hidden: [],
+  };
+  root.style.scrollBehavior = "auto";
+  return { content_height: root.scrollHeight, viewport_height: innerHeight };
+})()"#;
+
+// Fixed and sticky elements would repeat in every slice, so only the first keeps them
+const HIDE_FLOATING_SCRIPT: &str = r#"(() => {
+  const state = window.__pakeCapture;
+  for (const element of document.querySelectorAll("body *")) {
+    const position = getComputedStyle(element).position;
+    if (position === "fixed" || position === "sticky") {
+      state.hidden.push([element, element.style.visibility]);
+      element.style.visibility = "hidden";
+    }
+  }
+  return state.hidden.length;
+})()"#;
+
+const RESTORE_SCRIPT: &str = r#"(() => {
+  const state = window.__pakeCapture;
+  if (!state) return;
+  for (const [element, visibility] of state.hidden) {
+    element.style.visibility = visibility;
+  }
+  (document.scrollingElement || document.documentElement).style.scrollBehavior =
+    state.behavior;
+  scrollTo(state.x, state.y);
+  delete window.__pakeCapture;
+})()"#;
+
+// Resolves with the offset actually reached once the new position has painted
+fn scroll_script(top: f64) -> String {
+    format!(
+        r#"new Promise((resolve) => {{
+  scrollTo(0, {top});
+  requestAnimationFrame(() => requestAnimationFrame(() => resolve(scrollY)));
+}})"#
+    )
+}
+
+/// PNG of what the webview currently shows, at device resolution.
+pub async fn capture_viewport(window: &WebviewWindow) -> Result<Vec<u8>, String> {
+    let (sender, receiver) = oneshot::channel();
+    window
+        .with_webview(move |webview| platform::capture(webview, sender))
+        .map_err(|e| format!("Failed to capture page: {}", e))?;
+    receiver
+        .await
+        .map_err(|_| "Screenshot request was dropped".to_string())?
+}
+
+/// PNG of the whole document: scroll a viewport at a time, capture each step and
+/// stitch them together. Pages that scroll an inner container instead of the
+/// document come out as a single viewport.
+pub async fn capture_full_page(window: &WebviewWindow) -> Result<Vec<u8>, String> {
+    let size: PageSize = serde_json::from_value(eval_with_result(window, PREPARE_SCRIPT).await?)
+        .map_err(|e| format!("Failed to measure page: {}", e))?;
+    let result = capture_slices(window, &size).await;
+    let _ = window.eval(RESTORE_SCRIPT);
+    stitch(&result?, &size)
+}
+
+async fn capture_slices(
+    window: &WebviewWindow,
+    size: &PageSize,
+) -> Result<Vec<(f64, Vec<u8>)>, String> {
+    if size.viewport_height <= 0.0 {
+        return Err("Page has no visible viewport".to_string());
+    }
+    let mut slices: Vec<(f64, Vec<u8>)> = Vec::new();
+    let mut scale = None;
+    let mut top = 0.0;
+    loop {
+        let reached = eval_with_result(window, &scroll_script(top))
+            .await?
+            .as_f64()
+            .unwrap_or(top);
+        // A clamped last step overlaps the previous slice rather than leaving a gap
+        if slices.last().is_some_and(|(offset, _)| *offset >= reached) {
+            break;
+        }
+        let png = capture_viewport(window).await?;
+        if scale.is_none() {
+            scale = Some(device_scale(&png, size)?);
+            eval_with_result(window, HIDE_FLOATING_SCRIPT).await?;
+        }
+        slices.push((reached, png));
+
+        top = reached + size.viewport_height;
+        let too_tall = top * scale.unwrap_or(1.0) > MAX_CAPTURE_HEIGHT as f64;
+        if top >= size.content_height || too_tall {
+            break;
+        }
+    }
+    Ok(slices)
+}
+
+// Device pixels per CSS pixel, read off a capture so devicePixelRatio and any
+// platform snapshot scaling are both accounted for
+fn device_scale(png: &[u8], size: &PageSize) -> Result<f64, String> {
+    let (_, height, _) = decode_rgba(png)?;
+    Ok(height as f64 / size.viewport_height)
+}
+
+fn decode_rgba(png: &[u8]) -> Result<(u32, u32, Vec<u8>), String> {
+    let mut decoder = png::Decoder::new(png);
+    decoder.set_transformations(
+        png::Transformations::normalize_to_color8() | png::Transformations::ALPHA,
+    );
+    let mut reader = decoder
+        .read_info()
+        .map_err(|e| format!("Failed to decode capture: {}", e))?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader
+        .next_frame(&mut buffer)
+        .map_err(|e| format!("Failed to decode capture: {}", e))?;
+    if info.color_type != png::ColorType::Rgba {
+        return Err(format!("Unexpected capture format: {:?}", info.color_type));
+    }
+    buffer.truncate(info.buffer_size());
+    Ok((info.width, info.height, buffer))
+}
+
+fn stitch(slices: &[(f64, Vec<u8>)], size: &PageSize) -> Result<Vec<u8>, String> {
+    let (_, first) = slices.first().ok_or("Nothing was captured")?;
+    let scale = device_scale(first, size)?;
+    let (width, _, _) = decode_rgba(first)?;
+    let height = ((size.content_height * scale).round() as u32).min(MAX_CAPTURE_HEIGHT);
+    let row_bytes = width as usize * 4;
+    let mut canvas = vec![0u8; row_bytes * height as usize];
+
+    for (offset, png) in slices {
+        let (slice_width, slice_height, pixels) = decode_rgba(png)?;
+        let top = (offset * scale).round() as u32;
+        let rows = slice_height.min(height.saturating_sub(top));
+        let copy_bytes = row_bytes.min(slice_width as usize * 4);
+        for row in 0..rows as usize {
+            let source = row * slice_width as usize * 4;
+            let target = (top as usize + row) * row_bytes;
+            canvas[target..target + copy_bytes]
+                .copy_from_slice(&pixels[source..source + copy_bytes]);
+        }
+    }
+    encode_png(&canvas, width, height)
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use super::Reply;
+    use tauri::webview::PlatformWebview;
+    use webkit2gtk::gio::Cancellable;
+    use webkit2gtk::{SnapshotOptions, SnapshotRegion, WebViewExt};
+
+    pub fn capture(webview: PlatformWebview, reply: Reply<Vec<u8>>) {
+        webview.inner().snapshot(
+            SnapshotRegion::Visible,
+            SnapshotOptions::NONE,
+            None::<&Cancellable>,
+            move |result| {
+                let png = result
+                    .map_err(|e| format!("Failed to capture page: {}", e))
+                    .and_then(|surface| {
+                        let surface = cairo::ImageSurface::try_from(surface)
+                            .map_err(|_| "Snapshot is not an image surface".to_string())?;
+                        let mut png = Vec::new();
+                        surface
+                            .write_to_png(&mut png)
+                            .map_err(|e| format!("Failed to encode capture: {}", e))?;
+                        Ok(png)
+                    });
+                let _ = reply.send(png);
+            },
+        );
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::Reply;
+    use block2::RcBlock;
+    use objc2_app_kit::{NSBitmapImageFileType, NSBitmapImageRep, NSImage};
+    use objc2_foundation::{NSDictionary, NSError};
+    use objc2_web_kit::WKWebView;
+    use std::sync::Mutex;
+    use tauri::webview::PlatformWebview;
+
+    fn png_data(image: &NSImage) -> Option<Vec<u8>> {
+        let tiff = image.TIFFRepresentation()?;
+        let bitmap = NSBitmapImageRep::imageRepWithData(&tiff)?;
+        let png = unsafe {
+            bitmap.representationUsingType_properties(
+                NSBitmapImageFileType::PNG,
+                &NSDictionary::new(),
+            )
+        }?;
+        Some(png.to_vec())
+    }
+
+    pub fn capture(webview: PlatformWebview, reply: Reply<Vec<u8>>) {
+        // WebKit completion blocks are `Fn`, so the one-shot reply is taken on first call
+        let reply = Mutex::new(Some(reply));
+        unsafe {
+            let wk_webview: &WKWebView = &*webview.inner().cast();
+            let handler = RcBlock::new(move |image: *mut NSImage, error: *mut NSError| {
+                let Some(reply) = reply.lock().unwrap().take() else {
+                    return;
+                };
+                let result = match (image.as_ref(), error.as_ref()) {
+                    (Some(image), _) => {
+                        png_data(image).ok_or("Failed to encode capture".to_string())
+                    }
+                    (None, Some(error)) => Err(format!(
+                        "Failed to capture page: {}",
+                        error.localizedDescription()
+                    )),
+                    (None, None) => Err("Failed to capture page".to_string()),
+                };
+                let _ = reply.send(result);
+            });
+            wk_webview.takeSnapshotWithConfiguration_completionHandler(None, &handler);
+        }
+    }
+}
+
+/// WebView2's own CapturePreview needs a COM stream, the DevTools protocol hands back base64.
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::Reply;
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use serde_json::Value;
+    use tauri::webview::PlatformWebview;
+    use webview2_com::CallDevToolsProtocolMethodCompletedHandler;
+    use windows::core::HSTRING;
+
+    pub fn capture(webview: PlatformWebview, reply: Reply<Vec<u8>>) {
+        let core = match unsafe { webview.controller().CoreWebView2() } {
+            Ok(core) => core,
+            Err(e) => {
+                let _ = reply.send(Err(e.to_string()));
+                return;
+            }
+        };
+        let handler =
+            CallDevToolsProtocolMethodCompletedHandler::create(Box::new(move |result, json| {
+                let png = result
+                    .map_err(|e| format!("Failed to capture page: {}", e))
+                    .and_then(|_| serde_json::from_str::<Value>(&json).map_err(|e| e.to_string()))
+                    .and_then(|value| {
+                        let data = value["data"].as_str().ok_or("Capture returned no image")?;
+                        STANDARD
+                            .decode(data)
+                            .map_err(|e| format!("Invalid capture data: {}", e))
+                    });
+                let _ = reply.send(png);
+                Ok(())
+            }));
+        if let Err(e) = unsafe {
+            core.CallDevToolsProtocolMethod(
+                &HSTRING::from("Page.captureScreenshot"),
+                &HSTRING::from(r#"{"format":"png"}"#),
+                &handler,
+            )
+        } {
+            log::error!("Failed to call Page.captureScreenshot: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn png_filename_adds_the_extension() {
+        assert_eq!(png_filename("inbox").as_deref(), Some("inbox.png"));
+        assert_eq!(png_filename("inbox.png").as_deref(), Some("inbox.png"));
+        assert_eq!(png_filename("Inbox.PNG").as_deref(), Some("Inbox.png"));
+        assert_eq!(png_filename("report.v2").as_deref(), Some("report.v2.png"));
+    }
+
+    #[test]
+    fn png_filename_drops_directories() {
+        assert_eq!(png_filename("../../.ssh/key").as_deref(), Some("key.png"));
+        assert_eq!(png_filename("/etc/passwd").as_deref(), Some("passwd.png"));
+        assert_eq!(
+            png_filename(r"C:\Windows\shot").as_deref(),
+            Some("shot.png")
+        );
+    }
+
+    #[test]
+    fn png_filename_rejects_empty_names() {
+        for name in ["", "  ", "dir/", "..", ".png", "a/.."] {
+            assert_eq!(png_filename(name), None, "{name}");
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use tauri::menu::{MenuItem, Submenu};
+use tauri::{AppHandle, Manager, WebviewWindow, Wry};
+
+use crate::app::config::ScriptMenuItem;
+use crate::util::{get_pake_config, user_script};
+
+const SCRIPT_ID_PREFIX: &str = "run_script_";
+
+/// "Scripts" submenu with one item per `scripts_menu` entry, `None` when there are none.
+pub fn scripts_submenu(
+    app: &AppHandle<Wry>,
+    scripts: &[ScriptMenuItem],
+) -> tauri::Result<Option<Submenu<Wry>>> {
+    if scripts.is_empty() {
+        return Ok(None);
+    }
+    let submenu = Submenu::new(app, "Scripts", true)?;
+    for (index, script) in scripts.iter().enumerate() {
+        submenu.append(&MenuItem::with_id(
+            app,
+            format!("{}{}", SCRIPT_ID_PREFIX, index),
+            &script.label,
+            true,
+            None::<&str>,
+        )?)?;
+    }
+    Ok(Some(submenu))
+}
+
+// Scripts act on whatever the user is looking at, so prefer the focused window
+fn target_window(app: &AppHandle) -> Option<WebviewWindow> {
+    app.webview_windows()
+        .into_values()
+        .find(|window| window.is_focused().unwrap_or(false))
+        .or_else(|| app.get_webview_window("pake"))
+}
+
+/// Run the script behind a menu id. Returns `false` for ids that aren't scripts.
+pub fn handle_script_menu_event(app: &AppHandle, id: &str) -> bool {
+    let Some(index) = id
+        .strip_prefix(SCRIPT_ID_PREFIX)
+        .and_then(|index| index.parse::<usize>().ok())
+    else {
+        return false;
+    };
+    let scripts = get_pake_config().0.windows[0].scripts_menu.clone();
+    if let (Some(script), Some(window)) = (scripts.get(index), target_window(app)) {
+        if let Err(e) = window.eval(&user_script(&script.js)) {
+            log::warn!("Failed to run script {}: {}", script.label, e);
+        }
+    }
+    true
+}
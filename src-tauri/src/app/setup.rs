@@ -1,19 +1,47 @@
+use std::collections::{HashMap, HashSet};
 use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 use tauri::{
-    menu::{MenuBuilder, MenuItemBuilder},
+    menu::{CheckMenuItem, CheckMenuItemBuilder, MenuBuilder, MenuItemBuilder},
     tray::{TrayIconBuilder, TrayIconEvent},
-    AppHandle, Manager,
+    AppHandle, Emitter, Listener, Manager, Url, Wry,
 };
+use tauri_plugin_dialog::{DialogExt, MessageDialogKind};
 use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut};
 use tauri_plugin_window_state::{AppHandleExt, StateFlags};
 
+use crate::app::config::{SnapPosition, TrayFullscreenBehavior, WindowConfig};
+use crate::app::content_rules;
+use crate::app::downloads::{downloads_submenu, handle_download_menu_event};
+use crate::app::environments::{environments_submenu, handle_environment_menu_event};
+use crate::app::invoke::{is_busy, is_dnd_enabled, set_dnd_state};
+use crate::app::media::{self, MediaAction, MediaMenuItem};
+use crate::app::preferences;
+use crate::app::scripts::{handle_script_menu_event, scripts_submenu};
+use crate::app::unread;
+use crate::app::updater::prompt_for_update;
+use crate::app::window::{confirm_quit, move_to_pinned_monitor, pinned_monitor, snap_window_to};
+use crate::util::{
+    element_call_script, emit_shown, eval_with_result, get_pake_config, host_matches, version_info,
+    FULLSCREEN_EXIT_DELAY_MS, HANG_RECOVERED_EVENT, SHOWN_EVENT,
+};
+
+const HANG_PING_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Tray "Do Not Disturb" item, kept in app state so `set_dnd` can sync its check mark.
+pub struct DndMenuItem(pub CheckMenuItem<Wry>);
+
 pub fn set_system_tray(
     app: &AppHandle,
     show_system_tray: bool,
     tray_icon_path: &str,
+    _icon_template: Option<bool>,
     _init_fullscreen: bool,
+    fullscreen_behavior: TrayFullscreenBehavior,
+    remember_window_state: bool,
+    updates_enabled: bool,
 ) -> tauri::Result<()> {
     if !show_system_tray {
         app.remove_tray_by_id("pake-tray");
@@ -22,15 +50,68 @@ pub fn set_system_tray(
 
     let hide_app = MenuItemBuilder::with_id("hide_app", "Hide").build(app)?;
     let show_app = MenuItemBuilder::with_id("show_app", "Show").build(app)?;
+    let dnd = CheckMenuItemBuilder::with_id("toggle_dnd", "Do Not Disturb")
+        .checked(is_dnd_enabled())
+        .build(app)?;
+    let site_data = MenuItemBuilder::with_id("site_data", "Site Data...").build(app)?;
+    let preferences = MenuItemBuilder::with_id("preferences", "Preferences...").build(app)?;
+    let about = MenuItemBuilder::with_id("about", "About").build(app)?;
     let quit = MenuItemBuilder::with_id("quit", "Quit").build(app)?;
+    let force_quit_item = MenuItemBuilder::with_id("force_quit", "Quit (Force)").build(app)?;
 
-    let menu = MenuBuilder::new(app)
-        .items(&[&hide_app, &show_app, &quit])
-        .build()?;
+    let mut menu =
+        MenuBuilder::new(app).items(&[&hide_app, &show_app, &dnd, &site_data, &preferences]);
+    if get_pake_config().0.windows[0].media_controls {
+        let play_pause = MenuItemBuilder::with_id(
+            "media_play_pause",
+            media::play_pause_label(media::is_playing()),
+        )
+        .build(app)?;
+        let next = MenuItemBuilder::with_id("media_next", "Next Track").build(app)?;
+        let previous = MenuItemBuilder::with_id("media_prev", "Previous Track").build(app)?;
+        menu = menu
+            .separator()
+            .items(&[&play_pause, &next, &previous])
+            .separator();
+        app.manage(MediaMenuItem(play_pause));
+    }
+    let scripts = get_pake_config().0.windows[0].scripts_menu.clone();
+    if let Some(scripts) = scripts_submenu(app, &scripts)? {
+        menu = menu.item(&scripts);
+    }
+    if let Some(environments) = environments_submenu(app, &get_pake_config().0.windows[0])? {
+        menu = menu.item(&environments);
+    }
+    menu = menu.item(&downloads_submenu(app)?);
+    menu = menu.item(&about);
+    if updates_enabled {
+        let check_updates =
+            MenuItemBuilder::with_id("check_updates", "Check for Updates").build(app)?;
+        menu = menu.item(&check_updates);
+    }
+    let menu = menu.item(&quit).item(&force_quit_item).build()?;
+    app.manage(DndMenuItem(dnd));
 
     app.app_handle().remove_tray_by_id("pake-tray");
 
-    let tray = TrayIconBuilder::new()
+    let icon = if tray_icon_path.is_empty() {
+        app.default_window_icon()
+            .unwrap_or_else(|| panic!("Failed to get default window icon"))
+            .clone()
+    } else {
+        tauri::image::Image::from_path(tray_icon_path).unwrap_or_else(|_| {
+            // If custom tray icon fails to load, fallback to default
+            app.default_window_icon()
+                .unwrap_or_else(|| panic!("Failed to get default window icon"))
+                .clone()
+        })
+    };
+    #[cfg(target_os = "macos")]
+    let icon_template = _icon_template.unwrap_or_else(|| is_monochrome(&icon));
+    #[cfg(not(target_os = "macos"))]
+    let icon_template = false;
+
+    let tray = TrayIconBuilder::with_id("pake-tray")
         .menu(&menu)
         .on_menu_event(move |app, event| match event.id().as_ref() {
             "hide_app" => {
@@ -41,6 +122,7 @@ pub fn set_system_tray(
             "show_app" => {
                 if let Some(window) = app.get_webview_window("pake") {
                     window.show().unwrap();
+                    emit_shown(&window);
                     #[cfg(target_os = "linux")]
                     if _init_fullscreen && !window.is_fullscreen().unwrap_or(false) {
                         let _ = window.set_fullscreen(true);
@@ -48,22 +130,94 @@ pub fn set_system_tray(
                     }
                 }
             }
+            "toggle_dnd" => {
+                if let Err(e) = set_dnd_state(app, !is_dnd_enabled()) {
+                    log::error!("Failed to toggle do not disturb: {}", e);
+                }
+            }
+            "site_data" => {
+                if let Some(window) = app.get_webview_window("pake") {
+                    let _ = window.show();
+                    let _ = window.set_focus();
+                    emit_shown(&window);
+                    let _ = window.eval("window.pakeShowSiteData?.()");
+                }
+            }
+            "preferences" => {
+                if let Err(e) = preferences::open(app) {
+                    log::error!("Failed to open preferences: {}", e);
+                }
+            }
+            "about" => {
+                let info = version_info(app);
+                app.dialog()
+                    .message(info.summary())
+                    .title(format!("About {}", info.app_name))
+                    .kind(MessageDialogKind::Info)
+                    .show(|_| {});
+            }
+            "check_updates" => {
+                tauri::async_runtime::spawn(prompt_for_update(app.clone(), true));
+            }
             "quit" => {
-                app.save_window_state(StateFlags::all()).unwrap();
-                std::process::exit(0);
+                let app = app.clone();
+                tauri::async_runtime::spawn(async move {
+                    if !confirm_quit(&app).await {
+                        return;
+                    }
+                    if remember_window_state {
+                        app.save_window_state(StateFlags::all()).unwrap();
+                    }
+                    std::process::exit(0);
+                });
+            }
+            "force_quit" => force_quit(app),
+            "media_play_pause" | "media_next" | "media_prev" => {
+                let action = match event.id().as_ref() {
+                    "media_next" => MediaAction::NextTrack,
+                    "media_prev" => MediaAction::PreviousTrack,
+                    _ => MediaAction::PlayPause,
+                };
+                if let Err(e) = media::dispatch(app, action) {
+                    log::error!("{}", e);
+                }
+            }
+            id => {
+                if !handle_environment_menu_event(app, id) && !handle_download_menu_event(app, id) {
+                    handle_script_menu_event(app, id);
+                }
             }
-            _ => (),
         })
         .on_tray_icon_event(move |tray, event| match event {
             TrayIconEvent::Click { button, .. } => {
                 if button == tauri::tray::MouseButton::Left {
                     if let Some(window) = tray.app_handle().get_webview_window("pake") {
                         let is_visible = window.is_visible().unwrap_or(false);
-                        if is_visible {
+                        let is_fullscreen = window.is_fullscreen().unwrap_or(false);
+                        if is_visible && is_fullscreen {
+                            match fullscreen_behavior {
+                                TrayFullscreenBehavior::Hide => window.hide().unwrap(),
+                                TrayFullscreenBehavior::ExitFullscreen => {
+                                    let window = window.clone();
+                                    tauri::async_runtime::spawn(async move {
+                                        let _ = window.set_fullscreen(false);
+                                        tokio::time::sleep(Duration::from_millis(
+                                            FULLSCREEN_EXIT_DELAY_MS,
+                                        ))
+                                        .await;
+                                        let _ = window.hide();
+                                    });
+                                }
+                                TrayFullscreenBehavior::ActivateSpace => {
+                                    let _ = window.set_focus();
+                                }
+                            }
+                        } else if is_visible {
                             window.hide().unwrap();
                         } else {
                             window.show().unwrap();
                             window.set_focus().unwrap();
+                            emit_shown(&window);
                             #[cfg(target_os = "linux")]
                             if _init_fullscreen && !window.is_fullscreen().unwrap_or(false) {
                                 let _ = window.set_fullscreen(true);
@@ -74,35 +228,82 @@ pub fn set_system_tray(
             }
             _ => {}
         })
-        .icon(if tray_icon_path.is_empty() {
-            app.default_window_icon()
-                .unwrap_or_else(|| panic!("Failed to get default window icon"))
-                .clone()
-        } else {
-            tauri::image::Image::from_path(tray_icon_path).unwrap_or_else(|_| {
-                // If custom tray icon fails to load, fallback to default
-                app.default_window_icon()
-                    .unwrap_or_else(|| panic!("Failed to get default window icon"))
-                    .clone()
-            })
-        })
+        .icon(icon)
         .build(app)?;
 
-    tray.set_icon_as_template(false)?;
+    tray.set_icon_as_template(icon_template)?;
     Ok(())
 }
 
+/// Rebuild the tray tooltip from the app name, unread count and blocked requests.
+pub fn refresh_tray_tooltip(app: &AppHandle) {
+    let Some(tray) = app.tray_by_id("pake-tray") else {
+        return;
+    };
+    let mut parts = vec![app.package_info().name.clone()];
+    let unread = unread::unread_count();
+    if unread > 0 {
+        parts.push(format!("{} unread", unread));
+    }
+    let blocked = content_rules::blocked_requests();
+    if blocked > 0 {
+        parts.push(format!("{} requests blocked", blocked));
+    }
+    if let Err(e) = tray.set_tooltip(Some(parts.join(" - "))) {
+        log::warn!("Failed to update tray tooltip: {}", e);
+    }
+}
+
+// Template images keep only the alpha channel, so they suit icons whose visible
+// pixels are all gray
+#[cfg(target_os = "macos")]
+fn is_monochrome(icon: &tauri::image::Image) -> bool {
+    const TOLERANCE: u8 = 16;
+    icon.rgba()
+        .chunks_exact(4)
+        .filter(|pixel| pixel[3] > 0)
+        .all(|pixel| {
+            let (min, max) = (
+                pixel[..3].iter().min().unwrap(),
+                pixel[..3].iter().max().unwrap(),
+            );
+            max - min <= TOLERANCE
+        })
+}
+
 pub fn set_global_shortcut(
     app: &AppHandle,
     shortcut: String,
     _init_fullscreen: bool,
+    snap_shortcuts: &HashMap<String, SnapPosition>,
+    media_keys: bool,
 ) -> tauri::Result<()> {
-    if shortcut.is_empty() {
+    if shortcut.is_empty() && snap_shortcuts.is_empty() && !media_keys {
         return Ok(());
     }
 
     let app_handle = app.clone();
-    let shortcut_hotkey = Shortcut::from_str(&shortcut).unwrap();
+    let shortcut_hotkey = (!shortcut.is_empty()).then(|| Shortcut::from_str(&shortcut).unwrap());
+    let snap_hotkeys: Vec<(Shortcut, SnapPosition)> = snap_shortcuts
+        .iter()
+        .filter_map(
+            |(accelerator, position)| match Shortcut::from_str(accelerator) {
+                Ok(hotkey) => Some((hotkey, *position)),
+                Err(e) => {
+                    log::warn!("Invalid snap shortcut {}: {}", accelerator, e);
+                    None
+                }
+            },
+        )
+        .collect();
+    let media_hotkeys: Vec<(Shortcut, MediaAction)> = if media_keys {
+        MediaAction::ALL
+            .iter()
+            .map(|action| (Shortcut::from_str(action.media_key()).unwrap(), *action))
+            .collect()
+    } else {
+        Vec::new()
+    };
     let last_triggered = Arc::new(Mutex::new(Instant::now()));
 
     app_handle
@@ -110,6 +311,8 @@ pub fn set_global_shortcut(
             tauri_plugin_global_shortcut::Builder::new()
                 .with_handler({
                     let last_triggered = Arc::clone(&last_triggered);
+                    let snap_hotkeys = snap_hotkeys.clone();
+                    let media_hotkeys = media_hotkeys.clone();
                     move |app, event, _shortcut| {
                         let mut last_triggered = last_triggered.lock().unwrap();
                         if Instant::now().duration_since(*last_triggered)
@@ -119,7 +322,7 @@ pub fn set_global_shortcut(
                         }
                         *last_triggered = Instant::now();
 
-                        if shortcut_hotkey.eq(event) {
+                        if shortcut_hotkey.as_ref() == Some(event) {
                             if let Some(window) = app.get_webview_window("pake") {
                                 let is_visible = window.is_visible().unwrap();
                                 if is_visible {
@@ -127,6 +330,7 @@ pub fn set_global_shortcut(
                                 } else {
                                     window.show().unwrap();
                                     window.set_focus().unwrap();
+                                    emit_shown(&window);
                                     #[cfg(target_os = "linux")]
                                     if _init_fullscreen && !window.is_fullscreen().unwrap_or(false)
                                     {
@@ -134,6 +338,18 @@ pub fn set_global_shortcut(
                                     }
                                 }
                             }
+                        } else if let Some((_, position)) =
+                            snap_hotkeys.iter().find(|(hotkey, _)| hotkey == event)
+                        {
+                            if let Some(window) = app.get_webview_window("pake") {
+                                let _ = snap_window_to(&window, *position);
+                            }
+                        } else if let Some((_, action)) =
+                            media_hotkeys.iter().find(|(hotkey, _)| hotkey == event)
+                        {
+                            if let Err(e) = media::dispatch(app, *action) {
+                                log::error!("{}", e);
+                            }
                         }
                     }
                 })
@@ -141,7 +357,216 @@ pub fn set_global_shortcut(
         )
         .expect("Failed to set global shortcut");
 
-    app.global_shortcut().register(shortcut_hotkey).unwrap();
+    if let Some(shortcut_hotkey) = shortcut_hotkey {
+        app.global_shortcut().register(shortcut_hotkey).unwrap();
+        log::info!("Registered global shortcut: {}", shortcut);
+    }
+    for (hotkey, position) in snap_hotkeys {
+        if let Err(e) = app.global_shortcut().register(hotkey) {
+            log::warn!("Failed to register snap shortcut for {:?}: {}", position, e);
+        }
+    }
+    // Another player may already own the media keys
+    for (hotkey, action) in media_hotkeys {
+        if let Err(e) = app.global_shortcut().register(hotkey) {
+            log::warn!("Failed to register media key for {:?}: {}", action, e);
+        }
+    }
 
     Ok(())
 }
+
+static QUITTING: AtomicBool = AtomicBool::new(false);
+
+/// Whether `force_quit` is shutting the app down, so close requests shouldn't hide.
+pub fn is_quitting() -> bool {
+    QUITTING.load(Ordering::SeqCst)
+}
+
+/// Quit even when hide_on_close is set, without the `beforeunload` confirm. Unlike `std::process::exit`, `app.exit`
+/// lets the runtime and plugins shut down, and window state is saved first.
+pub fn force_quit(app: &AppHandle) {
+    QUITTING.store(true, Ordering::SeqCst);
+    if get_pake_config().0.windows[0].remember_window_state {
+        if let Err(e) = checkpoint_window_state(app) {
+            log::warn!("{}", e);
+        }
+    }
+    app.exit(0);
+}
+
+/// Write current window geometry through the window-state plugin, which is only
+/// registered when `remember_window_state` is on.
+pub fn checkpoint_window_state(app: &AppHandle) -> Result<(), String> {
+    if !get_pake_config().0.windows[0].remember_window_state {
+        return Err("remember_window_state is off".to_string());
+    }
+    app.save_window_state(StateFlags::all())
+        .map_err(|e| format!("Failed to save window state: {}", e))
+}
+
+/// Checkpoint window state every `autosave_state_secs` so a crash loses little.
+pub fn set_state_autosave(app: &AppHandle, window_config: &WindowConfig) {
+    let Some(secs) = window_config.autosave_state_secs else {
+        return;
+    };
+    if !window_config.remember_window_state {
+        log::warn!("autosave_state_secs needs remember_window_state, ignoring");
+        return;
+    }
+
+    let app_handle = app.clone();
+    let interval = Duration::from_secs(secs.max(1));
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(interval).await;
+            if let Err(e) = checkpoint_window_state(&app_handle) {
+                log::warn!("{}", e);
+            }
+        }
+    });
+}
+
+/// Periodically fetch `keepalive_url` from inside the page so the request carries
+/// the site's cookies and keeps the session warm.
+pub fn set_keepalive(app: &AppHandle, window_config: &WindowConfig) {
+    let Some(keepalive_url) = window_config.keepalive_url.as_deref() else {
+        return;
+    };
+
+    let Ok(url) = Url::from_str(keepalive_url) else {
+        log::warn!("Invalid keepalive_url: {}", keepalive_url);
+        return;
+    };
+
+    let allowed_host = Url::from_str(&window_config.url)
+        .ok()
+        .and_then(|u| u.host_str().map(str::to_string));
+    let is_allowed = match (url.host_str(), allowed_host.as_deref()) {
+        (Some(host), Some(allowed_host)) => host_matches(host, allowed_host),
+        _ => false,
+    };
+    if !is_allowed {
+        log::warn!(
+            "keepalive_url must stay on the configured host, ignoring: {}",
+            url
+        );
+        return;
+    }
+
+    let app_handle = app.clone();
+    let interval = Duration::from_secs(window_config.keepalive_interval_secs.max(1));
+    let skip_when_hidden = window_config.keepalive_skip_when_hidden;
+    let script = format!(
+        "fetch({}, {{ credentials: 'include', cache: 'no-store' }}).catch(() => {{}})",
+        serde_json::to_string(url.as_str()).unwrap()
+    );
+
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(interval).await;
+            let Some(window) = app_handle.get_webview_window("pake") else {
+                continue;
+            };
+            if skip_when_hidden && !window.is_visible().unwrap_or(false) {
+                continue;
+            }
+            let _ = window.eval(&script);
+        }
+    });
+}
+
+pub fn set_focus_on_show(app: &AppHandle, selector: Option<String>) {
+    let Some(selector) = selector.filter(|s| !s.is_empty()) else {
+        return;
+    };
+
+    let app_handle = app.clone();
+    let script = element_call_script(&selector, "focus");
+    app.listen(SHOWN_EVENT, move |_event| {
+        if let Some(window) = app_handle.get_webview_window("pake") {
+            let _ = window.eval(&script);
+        }
+    });
+}
+
+/// Return the window to its pinned monitor whenever it is brought back on screen.
+pub fn set_monitor_pinning(app: &AppHandle) {
+    let app_handle = app.clone();
+    app.listen(SHOWN_EVENT, move |_event| {
+        let Some(pinned) = pinned_monitor(&app_handle) else {
+            return;
+        };
+        if let Some(window) = app_handle.get_webview_window("pake") {
+            if let Err(e) = move_to_pinned_monitor(&window, &pinned) {
+                log::warn!("Failed to move window to monitor {}: {}", pinned, e);
+            }
+        }
+    });
+}
+
+/// Ping the page and, once it has stayed unresponsive for `hang_recovery_secs`,
+/// load the home URL again and emit `pake://hang-recovered`. Hidden windows are
+/// skipped since webviews throttle them, and so are running downloads and OAuth flows.
+///
+/// The ping answers through IPC, which pages outside the remote capability (plain
+/// `http`, hosts without a dot) don't get. A missed ping only counts once a ping
+/// has been answered on the current origin, so those pages are never reloaded.
+pub fn set_hang_watchdog(app: &AppHandle, window_config: &WindowConfig) {
+    let Some(threshold) = window_config.hang_recovery_secs.filter(|secs| *secs > 0) else {
+        return;
+    };
+    let threshold = Duration::from_secs(threshold);
+    let home_url = match window_config.url_type.as_str() {
+        "web" => Url::from_str(&window_config.url).ok(),
+        _ => None,
+    };
+
+    let app_handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut unresponsive_since: Option<Instant> = None;
+        let mut ipc_origins: HashSet<String> = HashSet::new();
+        loop {
+            tokio::time::sleep(HANG_PING_INTERVAL).await;
+            let Some(window) = app_handle.get_webview_window("pake") else {
+                continue;
+            };
+            if is_busy() || !window.is_visible().unwrap_or(false) {
+                unresponsive_since = None;
+                continue;
+            }
+            let Ok(origin) = window.url().map(|url| url.origin().ascii_serialization()) else {
+                continue;
+            };
+            if eval_with_result(&window, "true").await.is_ok() {
+                ipc_origins.insert(origin);
+                unresponsive_since = None;
+                continue;
+            }
+            if !ipc_origins.contains(&origin) {
+                unresponsive_since = None;
+                continue;
+            }
+
+            let since = *unresponsive_since.get_or_insert_with(Instant::now);
+            if since.elapsed() < threshold {
+                continue;
+            }
+            unresponsive_since = None;
+            log::warn!(
+                "Page unresponsive for {}s, reloading",
+                since.elapsed().as_secs()
+            );
+            let recovered = match &home_url {
+                Some(url) => window.navigate(url.clone()),
+                None => window.reload(),
+            };
+            match recovered {
+                Ok(()) => {
+                    let _ = app_handle.emit(HANG_RECOVERED_EVENT, ());
+                }
+                Err(e) => log::error!("Failed to recover unresponsive page: {}", e),
+            }
+        }
+    });
+}
@@ -0,0 +1,447 @@
+use crate::app::environments;
+use crate::util::{get_pake_config, host_matches, load_app_state, save_app_state};
+use serde::Serialize;
+use serde_json::Value;
+use tauri::webview::Cookie;
+use tauri::{Manager, Url, WebviewWindow};
+use tokio::sync::oneshot;
+
+type Reply<T> = oneshot::Sender<Result<T, String>>;
+
+// Set-Cookie strings carried across the restart that follows a full clear
+const PRESERVED_COOKIES_STATE_KEY: &str = "preserved_cookies";
+
+/// Site data stored for one origin. macOS and Linux group records by site,
+/// so they report bare hosts there. `size` is in bytes, `None` when the platform
+/// gives no estimate (always on macOS).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OriginData {
+    pub origin: String,
+    pub size: Option<u64>,
+}
+
+/// Host part of `https://host[:port]` or of a bare host.
+pub fn origin_host(origin: &str) -> Result<String, String> {
+    let origin = origin.trim();
+    let host = if origin.contains("://") {
+        Url::parse(origin)
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_string))
+    } else {
+        Some(origin.trim_start_matches('.').to_lowercase())
+            .filter(|host| !host.is_empty() && !host.contains(['/', ':', ' ']))
+    };
+    host.ok_or(format!("Invalid origin: {}", origin))
+}
+
+/// Every origin the webview's data store holds something for, sorted by name.
+pub async fn list_origins(window: &WebviewWindow) -> Result<Vec<OriginData>, String> {
+    let (sender, receiver) = oneshot::channel();
+    window
+        .with_webview(move |webview| platform::list(webview, sender))
+        .map_err(|e| format!("Failed to read site data: {}", e))?;
+
+    let mut origins = receiver
+        .await
+        .map_err(|_| "Site data request was dropped".to_string())??;
+    origins.sort_by(|a, b| a.origin.cmp(&b.origin));
+    origins.dedup_by(|a, b| a.origin == b.origin);
+    Ok(origins)
+}
+
+/// Remove cookies, storage and cache for `origin`. macOS and Linux also clear
+/// its subdomains since their records are grouped by site.
+pub async fn clear_origin(window: &WebviewWindow, origin: &str) -> Result<(), String> {
+    let host = origin_host(origin)?;
+    let (sender, receiver) = oneshot::channel();
+    window
+        .with_webview(move |webview| platform::clear(webview, host, sender))
+        .map_err(|e| format!("Failed to clear site data: {}", e))?;
+
+    receiver
+        .await
+        .map_err(|_| "Site data request was dropped".to_string())?
+}
+
+/// Cookies named in `preserve_cookies` that belong to the allowed host. Host-only
+/// cookies get that host as their domain so they can be set again later.
+fn preserved_cookies(window: &WebviewWindow) -> Result<Vec<Cookie<'static>>, String> {
+    let window_config = &get_pake_config().0.windows[0];
+    if window_config.preserve_cookies.is_empty() {
+        return Ok(Vec::new());
+    }
+    let allowed_host = environments::allowed_host(window_config).ok_or("No allowed host")?;
+    let cookies = window
+        .cookies()
+        .map_err(|e| format!("Failed to read cookies: {}", e))?;
+
+    Ok(cookies
+        .into_iter()
+        .filter(|cookie| {
+            window_config
+                .preserve_cookies
+                .iter()
+                .any(|name| name == cookie.name())
+        })
+        .filter_map(|mut cookie| {
+            match cookie.domain().map(|domain| domain.trim_start_matches('.')) {
+                Some(domain) if host_matches(domain, &allowed_host) => {}
+                Some(domain) => {
+                    log::warn!(
+                        "Not preserving cookie {} from {}, outside {}",
+                        cookie.name(),
+                        domain,
+                        allowed_host
+                    );
+                    return None;
+                }
+                None => cookie.set_domain(allowed_host.clone()),
+            }
+            Some(cookie)
+        })
+        .collect())
+}
+
+/// Clear all browsing data except the `preserve_cookies`, which are stashed in
+/// app state for `restore_preserved_cookies` to put back after the restart.
+pub async fn clear_all_keeping_cookies(window: &WebviewWindow) -> Result<(), String> {
+    let cookies: Vec<String> = preserved_cookies(window)?
+        .iter()
+        .map(|cookie| cookie.to_string())
+        .collect();
+    if !cookies.is_empty() {
+        save_app_state(
+            window.app_handle(),
+            PRESERVED_COOKIES_STATE_KEY,
+            cookies.into(),
+        )?;
+    }
+    window
+        .clear_all_browsing_data()
+        .map_err(|e| format!("Failed to clear browsing data: {}", e))
+}
+
+/// Set the cookies stashed by `clear_all_keeping_cookies` and reload so the
+/// page picks them up. Does nothing when none were stashed.
+pub async fn restore_preserved_cookies(window: &WebviewWindow) -> Result<(), String> {
+    let app = window.app_handle();
+    let Some(Value::Array(saved)) = load_app_state(app, PRESERVED_COOKIES_STATE_KEY) else {
+        return Ok(());
+    };
+    save_app_state(app, PRESERVED_COOKIES_STATE_KEY, Value::Null)?;
+
+    for raw in saved.iter().filter_map(Value::as_str) {
+        match Cookie::parse(raw.to_string()) {
+            Ok(cookie) => window
+                .set_cookie(cookie)
+                .map_err(|e| format!("Failed to restore cookie: {}", e))?,
+            Err(e) => log::warn!("Skipping unreadable preserved cookie: {}", e),
+        }
+    }
+    window
+        .eval("window.location.reload()")
+        .map_err(|e| format!("Failed to reload: {}", e))
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use super::{OriginData, Reply};
+    use crate::util::host_matches;
+    use tauri::webview::PlatformWebview;
+    use webkit2gtk::gio::Cancellable;
+    use webkit2gtk::{
+        WebContextExt, WebViewExt, WebsiteDataManager, WebsiteDataManagerExt, WebsiteDataTypes,
+    };
+
+    fn data_manager(webview: &PlatformWebview) -> Result<WebsiteDataManager, String> {
+        webview
+            .inner()
+            .context()
+            .and_then(|context| context.website_data_manager())
+            .ok_or("Webview has no website data manager".to_string())
+    }
+
+    pub fn list(webview: PlatformWebview, reply: Reply<Vec<OriginData>>) {
+        let manager = match data_manager(&webview) {
+            Ok(manager) => manager,
+            Err(e) => {
+                let _ = reply.send(Err(e));
+                return;
+            }
+        };
+        manager.fetch(WebsiteDataTypes::ALL, None::<&Cancellable>, move |result| {
+            let origins = result
+                .map(|records| {
+                    records
+                        .iter()
+                        .filter_map(|record| {
+                            Some(OriginData {
+                                origin: record.name()?.to_string(),
+                                size: Some(record.size(WebsiteDataTypes::ALL)),
+                            })
+                        })
+                        .collect()
+                })
+                .map_err(|e| format!("Failed to read site data: {}", e));
+            let _ = reply.send(origins);
+        });
+    }
+
+    pub fn clear(webview: PlatformWebview, host: String, reply: Reply<()>) {
+        let manager = match data_manager(&webview) {
+            Ok(manager) => manager,
+            Err(e) => {
+                let _ = reply.send(Err(e));
+                return;
+            }
+        };
+        let remover = manager.clone();
+        manager.fetch(WebsiteDataTypes::ALL, None::<&Cancellable>, move |result| {
+            let records: Vec<_> = match result {
+                Ok(records) => records
+                    .into_iter()
+                    .filter(|record| {
+                        record
+                            .name()
+                            .is_some_and(|name| host_matches(&host, name.as_str()))
+                    })
+                    .collect(),
+                Err(e) => {
+                    let _ = reply.send(Err(format!("Failed to read site data: {}", e)));
+                    return;
+                }
+            };
+            if records.is_empty() {
+                let _ = reply.send(Ok(()));
+                return;
+            }
+            remover.remove(
+                WebsiteDataTypes::ALL,
+                &records,
+                None::<&Cancellable>,
+                move |result| {
+                    let _ =
+                        reply.send(result.map_err(|e| format!("Failed to clear site data: {}", e)));
+                },
+            );
+        });
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::{OriginData, Reply};
+    use crate::util::host_matches;
+    use block2::RcBlock;
+    use objc2_foundation::NSArray;
+    use objc2_web_kit::{WKWebView, WKWebsiteDataRecord, WKWebsiteDataStore};
+    use std::ptr::NonNull;
+    use std::sync::{Arc, Mutex};
+    use tauri::webview::PlatformWebview;
+
+    // WebKit completion blocks are `Fn`, so the one-shot reply is taken on first call
+    fn once<T>(reply: Reply<T>) -> Arc<dyn Fn(Result<T, String>)> {
+        let reply = Mutex::new(Some(reply));
+        Arc::new(move |result| {
+            if let Some(reply) = reply.lock().unwrap().take() {
+                let _ = reply.send(result);
+            }
+        })
+    }
+
+    pub fn list(webview: PlatformWebview, reply: Reply<Vec<OriginData>>) {
+        let reply = once(reply);
+        unsafe {
+            let wk_webview: &WKWebView = &*webview.inner().cast();
+            let store = wk_webview.configuration().websiteDataStore();
+            let handler = RcBlock::new(move |records: NonNull<NSArray<WKWebsiteDataRecord>>| {
+                let origins = records
+                    .as_ref()
+                    .iter()
+                    .map(|record| OriginData {
+                        origin: record.displayName().to_string(),
+                        size: None,
+                    })
+                    .collect();
+                reply(Ok(origins));
+            });
+            store.fetchDataRecordsOfTypes_completionHandler(
+                &WKWebsiteDataStore::allWebsiteDataTypes(),
+                &handler,
+            );
+        }
+    }
+
+    pub fn clear(webview: PlatformWebview, host: String, reply: Reply<()>) {
+        let reply = once(reply);
+        unsafe {
+            let wk_webview: &WKWebView = &*webview.inner().cast();
+            let store = wk_webview.configuration().websiteDataStore();
+            let remover = store.clone();
+            let handler = RcBlock::new(move |records: NonNull<NSArray<WKWebsiteDataRecord>>| {
+                let matching: Vec<_> = records
+                    .as_ref()
+                    .iter()
+                    .filter(|record| host_matches(&host, &record.displayName().to_string()))
+                    .collect();
+                if matching.is_empty() {
+                    reply(Ok(()));
+                    return;
+                }
+                let reply = reply.clone();
+                let done = RcBlock::new(move || reply(Ok(())));
+                remover.removeDataOfTypes_forDataRecords_completionHandler(
+                    &WKWebsiteDataStore::allWebsiteDataTypes(),
+                    &NSArray::from_retained_slice(&matching),
+                    &done,
+                );
+            });
+            store.fetchDataRecordsOfTypes_completionHandler(
+                &WKWebsiteDataStore::allWebsiteDataTypes(),
+                &handler,
+            );
+        }
+    }
+}
+
+/// WebView2 exposes per-origin storage only through the DevTools protocol:
+/// origins come from the cookie jar and sizes from `Storage.getUsageAndQuota`.
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::{OriginData, Reply};
+    use serde_json::{json, Value};
+    use tauri::webview::PlatformWebview;
+    use webview2_com::CallDevToolsProtocolMethodCompletedHandler;
+    use webview2_com::Microsoft::Web::WebView2::Win32::ICoreWebView2;
+    use windows::core::HSTRING;
+
+    type Callback = Box<dyn FnOnce(Result<Value, String>)>;
+
+    unsafe fn call_cdp(core: &ICoreWebView2, method: &str, params: Value, callback: Callback) {
+        let name = method.to_string();
+        let handler =
+            CallDevToolsProtocolMethodCompletedHandler::create(Box::new(move |result, json| {
+                callback(
+                    result
+                        .map_err(|e| format!("{} failed: {}", name, e))
+                        .and_then(|_| serde_json::from_str(&json).map_err(|e| e.to_string())),
+                );
+                Ok(())
+            }));
+        if let Err(e) = core.CallDevToolsProtocolMethod(
+            &HSTRING::from(method),
+            &HSTRING::from(params.to_string()),
+            &handler,
+        ) {
+            log::error!("Failed to call {}: {}", method, e);
+        }
+    }
+
+    fn cookie_origins(cookies: &Value) -> Vec<String> {
+        let mut origins: Vec<String> = cookies["cookies"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|cookie| {
+                let domain = cookie["domain"].as_str()?.trim_start_matches('.');
+                let scheme = if cookie["secure"].as_bool() == Some(true) {
+                    "https"
+                } else {
+                    "http"
+                };
+                Some(format!("{}://{}", scheme, domain))
+            })
+            .collect();
+        origins.sort();
+        origins.dedup();
+        origins
+    }
+
+    // Sizes are fetched one origin at a time since every CDP call is a callback
+    fn fill_sizes(
+        core: ICoreWebView2,
+        mut pending: Vec<String>,
+        mut done: Vec<OriginData>,
+        reply: Reply<Vec<OriginData>>,
+    ) {
+        let Some(origin) = pending.pop() else {
+            let _ = reply.send(Ok(done));
+            return;
+        };
+        let next = core.clone();
+        unsafe {
+            call_cdp(
+                &core,
+                "Storage.getUsageAndQuota",
+                json!({ "origin": origin }),
+                Box::new(move |result| {
+                    let size = result.ok().and_then(|usage| usage["usage"].as_f64());
+                    done.push(OriginData {
+                        origin,
+                        size: size.map(|size| size as u64),
+                    });
+                    fill_sizes(next, pending, done, reply);
+                }),
+            );
+        }
+    }
+
+    pub fn list(webview: PlatformWebview, reply: Reply<Vec<OriginData>>) {
+        let core = match unsafe { webview.controller().CoreWebView2() } {
+            Ok(core) => core,
+            Err(e) => {
+                let _ = reply.send(Err(e.to_string()));
+                return;
+            }
+        };
+        let next = core.clone();
+        unsafe {
+            call_cdp(
+                &core,
+                "Storage.getCookies",
+                json!({}),
+                Box::new(move |result| match result {
+                    Ok(cookies) => fill_sizes(next, cookie_origins(&cookies), Vec::new(), reply),
+                    Err(e) => {
+                        let _ = reply.send(Err(e));
+                    }
+                }),
+            );
+        }
+    }
+
+    pub fn clear(webview: PlatformWebview, host: String, reply: Reply<()>) {
+        let core = match unsafe { webview.controller().CoreWebView2() } {
+            Ok(core) => core,
+            Err(e) => {
+                let _ = reply.send(Err(e.to_string()));
+                return;
+            }
+        };
+        let next = core.clone();
+        let http_origin = format!("http://{}", host);
+        unsafe {
+            call_cdp(
+                &core,
+                "Storage.clearDataForOrigin",
+                json!({ "origin": format!("https://{}", host), "storageTypes": "all" }),
+                Box::new(move |result| {
+                    if let Err(e) = result {
+                        let _ = reply.send(Err(e));
+                        return;
+                    }
+                    unsafe {
+                        call_cdp(
+                            &next,
+                            "Storage.clearDataForOrigin",
+                            json!({ "origin": http_origin, "storageTypes": "all" }),
+                            Box::new(move |result| {
+                                let _ = reply.send(result.map(|_| ()));
+                            }),
+                        );
+                    }
+                }),
+            );
+        }
+    }
+}
@@ -0,0 +1,121 @@
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, WebviewWindow};
+
+pub const NETWORK_THROTTLE_EVENT: &str = "pake://network-throttle";
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ThrottleProfile {
+    #[default]
+    None,
+    Slow3g,
+    Fast3g,
+    Offline,
+}
+
+/// Network conditions for a profile, close to Chrome DevTools' presets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct NetworkConditions {
+    pub offline: bool,
+    pub latency_ms: u64,
+    pub download_bytes_per_sec: u64,
+    pub upload_bytes_per_sec: u64,
+}
+
+impl ThrottleProfile {
+    pub fn conditions(self) -> Option<NetworkConditions> {
+        let (offline, latency_ms, download_bytes_per_sec, upload_bytes_per_sec) = match self {
+            ThrottleProfile::None => return None,
+            ThrottleProfile::Slow3g => (false, 2_000, 51_200, 51_200),
+            ThrottleProfile::Fast3g => (false, 563, 184_320, 86_400),
+            ThrottleProfile::Offline => (true, 0, 0, 0),
+        };
+        Some(NetworkConditions {
+            offline,
+            latency_ms,
+            download_bytes_per_sec,
+            upload_bytes_per_sec,
+        })
+    }
+}
+
+static ACTIVE_PROFILE: Mutex<ThrottleProfile> = Mutex::new(ThrottleProfile::None);
+
+pub fn active_profile() -> ThrottleProfile {
+    *ACTIVE_PROFILE.lock().unwrap()
+}
+
+/// Throttle every open window and emit the new profile as `pake://network-throttle`.
+/// Development builds only, so a shipped app can never be slowed down by a page.
+pub fn set_profile(app: &AppHandle, profile: ThrottleProfile) -> Result<(), String> {
+    if !cfg!(debug_assertions) {
+        return Err("Network throttling is only available in development builds".to_string());
+    }
+    *ACTIVE_PROFILE.lock().unwrap() = profile;
+    for window in app.webview_windows().into_values() {
+        apply(&window, profile);
+    }
+    let _ = app.emit(NETWORK_THROTTLE_EVENT, profile);
+    Ok(())
+}
+
+/// WKWebView and WebKitGTK have no network emulation, so `inject/throttle.js`
+/// wraps fetch and XHR instead. Page loads, images and the like are not slowed.
+#[cfg(not(target_os = "windows"))]
+fn apply(window: &WebviewWindow, profile: ThrottleProfile) {
+    let script = format!(
+        "window.pakeSetNetworkThrottle?.({})",
+        serde_json::to_string(&profile.conditions()).unwrap()
+    );
+    let _ = window.eval(&script);
+}
+
+/// WebView2 throttles every request natively through the DevTools protocol.
+#[cfg(target_os = "windows")]
+fn apply(window: &WebviewWindow, profile: ThrottleProfile) {
+    use serde_json::json;
+
+    let params = match profile.conditions() {
+        Some(conditions) => json!({
+            "offline": conditions.offline,
+            "latency": conditions.latency_ms,
+            "downloadThroughput": conditions.download_bytes_per_sec,
+            "uploadThroughput": conditions.upload_bytes_per_sec,
+        }),
+        None => json!({
+            "offline": false,
+            "latency": 0,
+            "downloadThroughput": -1,
+            "uploadThroughput": -1,
+        }),
+    };
+    let result = window.with_webview(move |webview| unsafe {
+        use webview2_com::CallDevToolsProtocolMethodCompletedHandler;
+        use windows::core::HSTRING;
+
+        let Ok(core) = webview.controller().CoreWebView2() else {
+            return;
+        };
+        for (method, params) in [
+            ("Network.enable", "{}".to_string()),
+            ("Network.emulateNetworkConditions", params.to_string()),
+        ] {
+            let handler =
+                CallDevToolsProtocolMethodCompletedHandler::create(Box::new(move |result, _| {
+                    if let Err(e) = result {
+                        log::warn!("{} failed: {}", method, e);
+                    }
+                    Ok(())
+                }));
+            let _ = core.CallDevToolsProtocolMethod(
+                &HSTRING::from(method),
+                &HSTRING::from(params),
+                &handler,
+            );
+        }
+    });
+    if let Err(e) = result {
+        log::error!("Failed to throttle network: {}", e);
+    }
+}
@@ -0,0 +1,154 @@
+use crate::app::config::TranslateConfig;
+use crate::util::{eval_with_result, get_pake_config};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::ops::Range;
+use std::time::Duration;
+use tauri::WebviewWindow;
+use tauri_plugin_http::reqwest::{header, Client, StatusCode};
+
+// Keeps each request under the item and character limits common to free tiers
+const MAX_CHUNK_TEXTS: usize = 50;
+const MAX_CHUNK_CHARS: usize = 4_000;
+
+const MAX_RETRIES: u32 = 4;
+const DEFAULT_RETRY_DELAY: Duration = Duration::from_secs(2);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+#[derive(Serialize)]
+struct TranslateRequest<'a> {
+    q: &'a [String],
+    source: &'static str,
+    target: &'a str,
+    format: &'static str,
+}
+
+#[derive(Deserialize)]
+struct TranslateResponse {
+    #[serde(rename = "translatedText")]
+    translated_text: Vec<String>,
+}
+
+/// Split `texts` into consecutive ranges of at most `MAX_CHUNK_TEXTS` items and
+/// `MAX_CHUNK_CHARS` characters. A single longer text gets a range of its own.
+pub fn chunk_ranges(texts: &[String]) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    let mut chars = 0;
+    for (index, text) in texts.iter().enumerate() {
+        let len = text.chars().count();
+        let full = index - start >= MAX_CHUNK_TEXTS || chars + len > MAX_CHUNK_CHARS;
+        if index > start && full {
+            ranges.push(start..index);
+            start = index;
+            chars = 0;
+        }
+        chars += len;
+    }
+    if start < texts.len() {
+        ranges.push(start..texts.len());
+    }
+    ranges
+}
+
+// Honors Retry-After (in seconds) on 429 and 503, backing off exponentially without it
+async fn request_translation(
+    client: &Client,
+    config: &TranslateConfig,
+    texts: &[String],
+) -> Result<Vec<String>, String> {
+    let body = serde_json::to_vec(&TranslateRequest {
+        q: texts,
+        source: "auto",
+        target: &config.target_lang,
+        format: "text",
+    })
+    .unwrap();
+
+    let mut delay = DEFAULT_RETRY_DELAY;
+    for attempt in 0..=MAX_RETRIES {
+        let response = client
+            .post(&config.endpoint)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(body.clone())
+            .send()
+            .await
+            .map_err(|e| format!("Translation request failed: {}", e))?;
+
+        let status = response.status();
+        let rate_limited =
+            status == StatusCode::TOO_MANY_REQUESTS || status == StatusCode::SERVICE_UNAVAILABLE;
+        if rate_limited && attempt < MAX_RETRIES {
+            let wait = response
+                .headers()
+                .get(header::RETRY_AFTER)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.trim().parse::<u64>().ok())
+                .map(Duration::from_secs)
+                .unwrap_or(delay)
+                .min(MAX_RETRY_DELAY);
+            log::info!("Translation endpoint rate limited, retrying in {:?}", wait);
+            tokio::time::sleep(wait).await;
+            delay = (delay * 2).min(MAX_RETRY_DELAY);
+            continue;
+        }
+        if !status.is_success() {
+            return Err(format!("Translation endpoint returned {}", status));
+        }
+
+        let bytes = response
+            .bytes()
+            .await
+            .map_err(|e| format!("Failed to read translation: {}", e))?;
+        let translated: TranslateResponse = serde_json::from_slice(&bytes)
+            .map_err(|e| format!("Unexpected translation response: {}", e))?;
+        if translated.translated_text.len() != texts.len() {
+            return Err(format!(
+                "Translation endpoint returned {} texts for {}",
+                translated.translated_text.len(),
+                texts.len()
+            ));
+        }
+        return Ok(translated.translated_text);
+    }
+    Err("Translation endpoint is rate limiting requests, try again later".to_string())
+}
+
+/// Translate the page's visible text in place, or put the original text back
+/// when it is already translated. Returns whether the page is now translated.
+pub async fn toggle(window: &WebviewWindow) -> Result<bool, String> {
+    let config = get_pake_config().0.windows[0]
+        .translate
+        .clone()
+        .ok_or("translate is not configured")?;
+
+    let active = eval_with_result(window, "window.pakeTranslate?.isActive() === true").await?;
+    if active == Value::Bool(true) {
+        window
+            .eval("window.pakeTranslate.restore()")
+            .map_err(|e| format!("Failed to restore page text: {}", e))?;
+        return Ok(false);
+    }
+
+    let texts: Vec<String> = serde_json::from_value(
+        eval_with_result(window, "window.pakeTranslate?.extract() ?? []").await?,
+    )
+    .map_err(|e| format!("Failed to read page text: {}", e))?;
+    if texts.is_empty() {
+        return Ok(false);
+    }
+
+    // Chunks are applied as they arrive so long pages fill in progressively
+    let client = Client::new();
+    for range in chunk_ranges(&texts) {
+        let translated = request_translation(&client, &config, &texts[range.clone()]).await?;
+        window
+            .eval(format!(
+                "window.pakeTranslate.apply({}, {})",
+                range.start,
+                serde_json::to_string(&translated).unwrap()
+            ))
+            .map_err(|e| format!("Failed to apply translation: {}", e))?;
+    }
+    Ok(true)
+}
@@ -0,0 +1,63 @@
+use crate::app::setup::refresh_tray_tooltip;
+use crate::util::{broadcast, get_pake_config, UNREAD_EVENT};
+use regex::Regex;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::LazyLock;
+use tauri::{Manager, WebviewWindow};
+
+static UNREAD_COUNT: AtomicU32 = AtomicU32::new(0);
+
+// Compiled once; an invalid pattern turns the feature off rather than failing startup
+static UNREAD_PATTERN: LazyLock<Option<Regex>> = LazyLock::new(|| {
+    let pattern = get_pake_config().0.windows[0].unread_title_regex.clone()?;
+    Regex::new(&pattern)
+        .inspect_err(|e| log::warn!("Invalid unread_title_regex {}: {}", pattern, e))
+        .ok()
+});
+
+pub fn enabled() -> bool {
+    UNREAD_PATTERN.is_some()
+}
+
+pub fn unread_count() -> u32 {
+    UNREAD_COUNT.load(Ordering::Relaxed)
+}
+
+/// Count in `title` per `pattern`: the first capture group, else the whole match,
+/// read as a number. Zero when nothing matches.
+pub fn count_from_title(pattern: &Regex, title: &str) -> u32 {
+    pattern
+        .captures(title)
+        .and_then(|captures| captures.get(1).or_else(|| captures.get(0)))
+        .and_then(|matched| {
+            let digits: String = matched
+                .as_str()
+                .chars()
+                .filter(char::is_ascii_digit)
+                .collect();
+            digits.parse().ok()
+        })
+        .unwrap_or(0)
+}
+
+/// Re-read the count after the page retitles itself and, when it changed, emit
+/// `pake://unread` and refresh the dock badge and tray tooltip.
+pub fn update_from_title(window: &WebviewWindow, title: &str) {
+    let Some(pattern) = UNREAD_PATTERN.as_ref() else {
+        return;
+    };
+    let count = count_from_title(pattern, title);
+    if UNREAD_COUNT.swap(count, Ordering::Relaxed) == count {
+        return;
+    }
+
+    let app = window.app_handle();
+    if let Err(e) = broadcast(app, UNREAD_EVENT, count) {
+        log::warn!("Failed to emit unread count: {}", e);
+    }
+    #[cfg(target_os = "macos")]
+    if let Err(e) = window.set_badge_count((count > 0).then_some(count as i64)) {
+        log::warn!("Failed to set dock badge: {}", e);
+    }
+    refresh_tray_tooltip(app);
+}
@@ -0,0 +1,89 @@
+use crate::util::get_pake_config;
+use tauri::{AppHandle, Url};
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+use tauri_plugin_updater::{Update, UpdaterExt};
+
+/// Check the configured endpoint. Updates are only offered when a public key
+/// is configured, since the plugin refuses unsigned artifacts anyway.
+pub async fn check_for_update(app: &AppHandle) -> Result<Option<Update>, String> {
+    let (pake_config, _) = get_pake_config();
+    let endpoint = pake_config
+        .updater_endpoint
+        .ok_or("No updater_endpoint configured")?;
+    if pake_config.updater_pubkey.is_none() {
+        return Err("updater_pubkey is required to verify updates".to_string());
+    }
+    let endpoint = Url::parse(&endpoint).map_err(|e| format!("Invalid updater_endpoint: {}", e))?;
+
+    app.updater_builder()
+        .endpoints(vec![endpoint])
+        .map_err(|e| format!("Failed to configure updater: {}", e))?
+        .build()
+        .map_err(|e| format!("Failed to build updater: {}", e))?
+        .check()
+        .await
+        .map_err(|e| format!("Failed to check for updates: {}", e))
+}
+
+async fn install_update(app: AppHandle, update: Update) {
+    log::info!("Installing update {}", update.version);
+    match update.download_and_install(|_, _| {}, || {}).await {
+        Ok(()) => app.restart(),
+        Err(e) => {
+            log::error!("Failed to install update: {}", e);
+            app.dialog()
+                .message(format!("Failed to install the update:\n{}", e))
+                .title("Update Failed")
+                .kind(MessageDialogKind::Error)
+                .show(|_| {});
+        }
+    }
+}
+
+/// Check and offer to install. `interactive` checks also report "up to date"
+/// and errors; launch checks stay quiet unless there is something to install.
+pub async fn prompt_for_update(app: AppHandle, interactive: bool) {
+    let update = match check_for_update(&app).await {
+        Ok(Some(update)) => update,
+        Ok(None) => {
+            if interactive {
+                app.dialog()
+                    .message("You're running the latest version.")
+                    .title("No Updates Available")
+                    .kind(MessageDialogKind::Info)
+                    .show(|_| {});
+            }
+            return;
+        }
+        Err(e) => {
+            log::warn!("{}", e);
+            if interactive {
+                app.dialog()
+                    .message(e)
+                    .title("Update Check Failed")
+                    .kind(MessageDialogKind::Error)
+                    .show(|_| {});
+            }
+            return;
+        }
+    };
+
+    let current_version = app.package_info().version.to_string();
+    let app_handle = app.clone();
+    app.dialog()
+        .message(format!(
+            "Version {} is available (you have {}). Install it now?",
+            update.version, current_version
+        ))
+        .title("Update Available")
+        .kind(MessageDialogKind::Info)
+        .buttons(MessageDialogButtons::OkCancelCustom(
+            "Install".to_string(),
+            "Later".to_string(),
+        ))
+        .show(move |install| {
+            if install {
+                tauri::async_runtime::spawn(install_update(app_handle, update));
+            }
+        });
+}
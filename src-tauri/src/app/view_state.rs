@@ -0,0 +1,412 @@
+use crate::app::config::TitleMode;
+use crate::util::{
+    eval_with_result, get_pake_config, identity_script, load_app_state, save_app_state,
+};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+use tauri::{AppHandle, Manager, WebviewWindow};
+
+pub const MIN_ZOOM: u32 = 30;
+pub const MAX_ZOOM: u32 = 200;
+
+const ZOOM_STATE_KEY: &str = "window_zoom";
+const TITLE_PREFIX_STATE_KEY: &str = "window_title_prefix";
+
+// Only un-mute elements we muted ourselves so page-muted autoplay stays silent
+const MUTE_SCRIPT: &str = r#"(() => {
+  window.pakeMuted = MUTED;
+  document.querySelectorAll("audio, video").forEach((media) => {
+    if (window.pakeMuted && !media.muted) {
+      media.muted = true;
+      media.pakeMuted = true;
+    } else if (!window.pakeMuted && media.pakeMuted) {
+      media.muted = false;
+      delete media.pakeMuted;
+    }
+  });
+})();"#;
+
+/// Zoom, mute and title for a single window, keyed by label so `pake-N` windows
+/// don't share them with the main one. Theme follows each page, so it has no state here.
+/// `base_title` is the title before any prefix so the prefix can change without stacking.
+/// `identity` is the signed-in account last read through `identity_selector`.
+#[derive(Debug, Default, Clone)]
+struct ViewState {
+    zoom: Option<u32>,
+    muted: bool,
+    base_title: Option<String>,
+    identity: Option<String>,
+}
+
+static VIEW_STATES: LazyLock<Mutex<HashMap<String, ViewState>>> = LazyLock::new(Default::default);
+
+fn saved_map(app: &AppHandle, key: &str) -> serde_json::Map<String, Value> {
+    match load_app_state(app, key) {
+        Some(Value::Object(map)) => map,
+        _ => Default::default(),
+    }
+}
+
+/// Zoom percent for a window: this session's value, then the persisted one, then `zoom` from pake.json.
+pub fn zoom(app: &AppHandle, label: &str) -> u32 {
+    resolve_zoom(
+        session_zoom(label),
+        || saved_zoom(&saved_map(app, ZOOM_STATE_KEY), label),
+        || get_pake_config().0.windows[0].zoom.percent(),
+    )
+}
+
+// The later sources are only read when the earlier ones have nothing
+fn resolve_zoom(
+    session: Option<u32>,
+    saved: impl FnOnce() -> Option<u32>,
+    configured: impl FnOnce() -> u32,
+) -> u32 {
+    session.or_else(saved).unwrap_or_else(configured)
+}
+
+fn saved_zoom(zooms: &serde_json::Map<String, Value>, label: &str) -> Option<u32> {
+    zooms
+        .get(label)
+        .and_then(Value::as_u64)
+        .map(|zoom| zoom as u32)
+}
+
+fn session_zoom(label: &str) -> Option<u32> {
+    VIEW_STATES
+        .lock()
+        .unwrap()
+        .get(label)
+        .and_then(|state| state.zoom)
+}
+
+// Clamp and record the zoom for this session and in the persisted map, under `label` only
+fn record_zoom(zooms: &mut serde_json::Map<String, Value>, label: &str, zoom: u32) -> u32 {
+    let zoom = clamp_zoom(zoom);
+    VIEW_STATES
+        .lock()
+        .unwrap()
+        .entry(label.to_string())
+        .or_default()
+        .zoom = Some(zoom);
+    zooms.insert(label.to_string(), zoom.into());
+    zoom
+}
+
+fn clamp_zoom(zoom: u32) -> u32 {
+    zoom.clamp(MIN_ZOOM, MAX_ZOOM)
+}
+
+/// Zoom one window natively and persist the level under its label.
+pub fn set_zoom(window: &WebviewWindow, zoom: u32) -> Result<u32, String> {
+    window
+        .set_zoom(zoom_factor(clamp_zoom(zoom)))
+        .map_err(|e| format!("Failed to set zoom: {}", e))?;
+
+    let app = window.app_handle();
+    let mut zooms = saved_map(app, ZOOM_STATE_KEY);
+    let zoom = record_zoom(&mut zooms, window.label(), zoom);
+    save_app_state(app, ZOOM_STATE_KEY, zooms.into())?;
+    Ok(zoom)
+}
+
+/// Webview zoom factor for a zoom percent. Off Windows `ui_scale` has no
+/// native hook, so it rides along with the page zoom there.
+fn zoom_factor(zoom: u32) -> f64 {
+    let factor = zoom as f64 / 100.0;
+    if cfg!(target_os = "windows") {
+        return factor;
+    }
+    factor * get_pake_config().0.windows[0].ui_scale().unwrap_or(1.0)
+}
+
+/// WebView2 can scale its whole rendering, scrollbars and native widgets
+/// included, instead of only the page zoom.
+#[cfg(target_os = "windows")]
+fn apply_ui_scale(window: &WebviewWindow) {
+    let Some(scale) = get_pake_config().0.windows[0].ui_scale() else {
+        return;
+    };
+    let monitor_scale = window.scale_factor().unwrap_or(1.0);
+    let _ = window.with_webview(move |webview| unsafe {
+        use webview2_com::Microsoft::Web::WebView2::Win32::ICoreWebView2Controller3;
+        use windows::core::Interface;
+
+        let Ok(controller) = webview.controller().cast::<ICoreWebView2Controller3>() else {
+            log::warn!("WebView2 runtime is too old for ui_scale");
+            return;
+        };
+        // Otherwise WebView2 resets the scale whenever the monitor DPI changes
+        let _ = controller.SetShouldDetectMonitorScaleChanges(false);
+        let _ = controller.SetRasterizationScale(monitor_scale * scale);
+    });
+}
+
+/// Re-apply the stored zoom, UI scale and title prefix after a window is built.
+/// Window sizes already include `ui_scale` through `WindowConfig::window_size`.
+pub fn restore_view_state(window: &WebviewWindow) {
+    #[cfg(target_os = "windows")]
+    apply_ui_scale(window);
+
+    let factor = zoom_factor(zoom(window.app_handle(), window.label()));
+    if (factor - 1.0).abs() > f64::EPSILON {
+        let _ = window.set_zoom(factor);
+    }
+    if title_prefix(window.app_handle(), window.label()).is_some() {
+        let _ = apply_title(window);
+    }
+}
+
+pub fn is_muted(label: &str) -> bool {
+    VIEW_STATES
+        .lock()
+        .unwrap()
+        .get(label)
+        .is_some_and(|state| state.muted)
+}
+
+/// Mute every media element in one window. Session only; `event.js` re-applies
+/// it after navigation through `get_muted`.
+pub fn set_muted(window: &WebviewWindow, muted: bool) -> Result<(), String> {
+    VIEW_STATES
+        .lock()
+        .unwrap()
+        .entry(window.label().to_string())
+        .or_default()
+        .muted = muted;
+    window
+        .eval(&MUTE_SCRIPT.replace("MUTED", &muted.to_string()))
+        .map_err(|e| format!("Failed to set muted: {}", e))
+}
+
+/// `"[Work]"` and `"Inbox"` become `"[Work] Inbox"`; either side may be empty.
+pub fn compose_title(prefix: Option<&str>, title: &str) -> String {
+    match prefix.map(str::trim).filter(|prefix| !prefix.is_empty()) {
+        Some(prefix) if title.is_empty() => prefix.to_string(),
+        Some(prefix) => format!("{} {}", prefix, title),
+        None => title.to_string(),
+    }
+}
+
+/// Title prefix for a window: the persisted one, then `title_prefix` from pake.json,
+/// then the detected account as `[name]`. An empty persisted prefix overrides the rest.
+pub fn title_prefix(app: &AppHandle, label: &str) -> Option<String> {
+    let prefix = match saved_map(app, TITLE_PREFIX_STATE_KEY).get(label) {
+        Some(Value::String(prefix)) => Some(prefix.clone()),
+        _ => get_pake_config().0.windows[0]
+            .title_prefix
+            .clone()
+            .or_else(|| identity(label).map(|identity| format!("[{}]", identity))),
+    };
+    prefix.filter(|prefix| !prefix.trim().is_empty())
+}
+
+// The base title is captured on first use, before any prefix has been applied
+fn apply_title(window: &WebviewWindow) -> Result<(), String> {
+    let base_title = {
+        let mut states = VIEW_STATES.lock().unwrap();
+        let state = states.entry(window.label().to_string()).or_default();
+        if state.base_title.is_none() {
+            state.base_title = Some(window.title().unwrap_or_default());
+        }
+        state.base_title.clone().unwrap_or_default()
+    };
+    let prefix = title_prefix(window.app_handle(), window.label());
+    window
+        .set_title(&compose_title(prefix.as_deref(), &base_title))
+        .map_err(|e| format!("Failed to set title: {}", e))
+}
+
+/// Title the prefix is composed onto once the page retitles itself: the page's
+/// title under `FollowPage`, otherwise whatever the window started with.
+pub fn base_title_after_change(
+    mode: TitleMode,
+    base_title: Option<String>,
+    page_title: &str,
+) -> Option<String> {
+    match mode {
+        TitleMode::FollowPage => Some(page_title.to_string()),
+        TitleMode::Fixed => base_title,
+    }
+}
+
+/// Registered on every window, so the prefix survives the page retitling itself
+/// and `FollowPage` windows pick up the new title.
+pub fn on_page_title_changed(window: &WebviewWindow, title: &str) {
+    let mode = get_pake_config().0.windows[0].title_mode;
+    if mode == TitleMode::Fixed && title_prefix(window.app_handle(), window.label()).is_none() {
+        return;
+    }
+    {
+        let mut states = VIEW_STATES.lock().unwrap();
+        let state = states.entry(window.label().to_string()).or_default();
+        state.base_title = base_title_after_change(mode, state.base_title.take(), title);
+    }
+    if let Err(e) = apply_title(window) {
+        log::warn!("{}", e);
+    }
+}
+
+pub fn identity(label: &str) -> Option<String> {
+    VIEW_STATES
+        .lock()
+        .unwrap()
+        .get(label)
+        .and_then(|state| state.identity.clone())
+}
+
+/// Read the signed-in account through `identity_selector` and relabel the window
+/// when it changed. `None` when signed out or no selector is configured.
+pub async fn refresh_identity(window: &WebviewWindow) -> Result<Option<String>, String> {
+    let Some(selector) = get_pake_config().0.windows[0].identity_selector.clone() else {
+        return Ok(None);
+    };
+    let identity = eval_with_result(window, &identity_script(&selector))
+        .await?
+        .as_str()
+        .map(str::to_string);
+
+    let changed = {
+        let mut states = VIEW_STATES.lock().unwrap();
+        let state = states.entry(window.label().to_string()).or_default();
+        let changed = state.identity != identity;
+        state.identity = identity.clone();
+        changed
+    };
+    if changed {
+        apply_title(window)?;
+    }
+    Ok(identity)
+}
+
+/// Prefix one window's title and persist the prefix under its label. Empty clears it.
+pub fn set_title_prefix(window: &WebviewWindow, prefix: &str) -> Result<(), String> {
+    let app = window.app_handle();
+    let mut prefixes = saved_map(app, TITLE_PREFIX_STATE_KEY);
+    prefixes.insert(window.label().to_string(), prefix.trim().into());
+    save_app_state(app, TITLE_PREFIX_STATE_KEY, prefixes.into())?;
+    apply_title(window)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn compose_title_joins_prefix_and_title() {
+        assert_eq!(compose_title(Some("[Work]"), "Inbox"), "[Work] Inbox");
+        assert_eq!(compose_title(None, "Inbox"), "Inbox");
+    }
+
+    #[test]
+    fn compose_title_drops_an_empty_prefix() {
+        assert_eq!(compose_title(Some(""), "Inbox"), "Inbox");
+        assert_eq!(compose_title(Some("   "), "Inbox"), "Inbox");
+    }
+
+    #[test]
+    fn compose_title_with_an_empty_title_is_the_prefix() {
+        assert_eq!(compose_title(Some("[Work]"), ""), "[Work]");
+        assert_eq!(compose_title(None, ""), "");
+    }
+
+    #[test]
+    fn compose_title_trims_the_prefix() {
+        assert_eq!(compose_title(Some("  [Work]  "), "Inbox"), "[Work] Inbox");
+        assert_eq!(compose_title(Some(" [Work] "), ""), "[Work]");
+    }
+
+    #[test]
+    fn zoom_is_clamped_to_the_supported_range() {
+        assert_eq!(clamp_zoom(0), MIN_ZOOM);
+        assert_eq!(clamp_zoom(29), 30);
+        assert_eq!(clamp_zoom(30), 30);
+        assert_eq!(clamp_zoom(125), 125);
+        assert_eq!(clamp_zoom(200), 200);
+        assert_eq!(clamp_zoom(500), MAX_ZOOM);
+    }
+
+    #[test]
+    fn session_zoom_wins_without_reading_the_rest() {
+        let zoom = resolve_zoom(
+            Some(120),
+            || panic!("persisted zoom read"),
+            || panic!("configured zoom read"),
+        );
+        assert_eq!(zoom, 120);
+    }
+
+    #[test]
+    fn persisted_zoom_comes_before_the_configured_one() {
+        assert_eq!(
+            resolve_zoom(None, || Some(90), || panic!("configured zoom read")),
+            90
+        );
+        assert_eq!(resolve_zoom(None, || None, || 110), 110);
+    }
+
+    #[test]
+    fn persisted_zoom_is_kept_per_label() {
+        let zooms = json!({ "pake": 150, "pake-2": 80, "pake-3": "big" });
+        let zooms = zooms.as_object().unwrap();
+        assert_eq!(saved_zoom(zooms, "pake"), Some(150));
+        assert_eq!(saved_zoom(zooms, "pake-2"), Some(80));
+        assert_eq!(saved_zoom(zooms, "pake-3"), None);
+        assert_eq!(saved_zoom(zooms, "pake-4"), None);
+    }
+
+    #[test]
+    fn setting_zoom_on_one_label_leaves_the_other_alone() {
+        // Labels unique to this test, since session state is process-wide
+        let (main, other) = ("zoom-test-main", "zoom-test-other");
+        let mut zooms = serde_json::Map::new();
+
+        assert_eq!(record_zoom(&mut zooms, main, 150), 150);
+        assert_eq!(session_zoom(main), Some(150));
+        assert_eq!(saved_zoom(&zooms, main), Some(150));
+        assert_eq!(session_zoom(other), None);
+        assert_eq!(saved_zoom(&zooms, other), None);
+        assert_eq!(
+            resolve_zoom(session_zoom(other), || saved_zoom(&zooms, other), || 100),
+            100
+        );
+
+        assert_eq!(record_zoom(&mut zooms, other, 500), MAX_ZOOM);
+        assert_eq!(session_zoom(main), Some(150));
+        assert_eq!(saved_zoom(&zooms, main), Some(150));
+        assert_eq!(session_zoom(other), Some(MAX_ZOOM));
+        assert_eq!(saved_zoom(&zooms, other), Some(MAX_ZOOM));
+    }
+
+    #[test]
+    fn prefix_composes_with_follow_page() {
+        let base = base_title_after_change(TitleMode::FollowPage, Some("Pake".into()), "Inbox (3)");
+        assert_eq!(
+            compose_title(Some("[Work]"), base.as_deref().unwrap()),
+            "[Work] Inbox (3)"
+        );
+
+        // The next retitle replaces the page title rather than stacking on the prefixed one
+        let base = base_title_after_change(TitleMode::FollowPage, base, "Inbox");
+        assert_eq!(
+            compose_title(Some("[Work]"), base.as_deref().unwrap()),
+            "[Work] Inbox"
+        );
+        assert_eq!(compose_title(None, base.as_deref().unwrap()), "Inbox");
+    }
+
+    #[test]
+    fn fixed_title_ignores_page_title_changes() {
+        let base = base_title_after_change(TitleMode::Fixed, Some("Pake".into()), "Inbox (3)");
+        assert_eq!(
+            compose_title(Some("[Work]"), base.as_deref().unwrap()),
+            "[Work] Pake"
+        );
+        assert_eq!(
+            base_title_after_change(TitleMode::Fixed, None, "Inbox"),
+            None
+        );
+    }
+}
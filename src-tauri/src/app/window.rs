@@ -1,11 +1,93 @@
-use crate::app::config::PakeConfig;
-use crate::util::get_data_dir;
+use crate::app::config::{
+    BeforeUnloadBehavior, ExternalProtocols, InitialPost, PakeConfig, PipCorner, ResizeEdge, RunAt,
+    ScrollbarStyle, SnapPosition, WindowConfig, WindowLevel, WindowSize,
+};
+use crate::app::content_rules;
+use crate::app::environments::{self, is_environment_host};
+use crate::app::home_urls::next_home_url;
+use crate::app::navigation::{notify_denied, NavigationDecision, NavigationRules};
+use crate::app::permissions::apply_permissions;
+use crate::app::preferences;
+use crate::app::site_data;
+use crate::app::unread;
+use crate::app::view_state;
+use crate::util::{
+    eval_with_result, get_data_dir, get_pake_config, host_matches, is_dark_color, load_app_state,
+    parse_hex_color, save_app_state, user_script, with_app_id_suffix, LOAD_END_EVENT,
+    LOAD_START_EVENT,
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::Duration;
 use std::{path::PathBuf, str::FromStr};
-use tauri::{App, Config, Url, WebviewUrl, WebviewWindow, WebviewWindowBuilder};
+use tauri::{
+    webview::{PageLoadEvent, PageLoadPayload},
+    window::{Color, ResizeDirection},
+    AppHandle, Config, Emitter, LogicalSize, Manager, PhysicalPosition, PhysicalSize, Url,
+    WebviewUrl, WebviewWindow, WebviewWindowBuilder, Window,
+};
+use tauri_plugin_dialog::{
+    DialogExt, MessageDialogButtons, MessageDialogKind, MessageDialogResult,
+};
+use tauri_plugin_opener::OpenerExt;
+use tokio::sync::{oneshot, Notify};
 
 #[cfg(target_os = "macos")]
 use tauri::{Theme, TitleBarStyle};
 
+static EXTERNAL_WINDOW_COUNTER: AtomicUsize = AtomicUsize::new(1);
+static RESIZE_GENERATION: AtomicU64 = AtomicU64::new(0);
+static LAST_FITTED_SIZE: Mutex<Option<(f64, f64)>> = Mutex::new(None);
+
+// Wait for the user to stop dragging before correcting, so set_size doesn't fight the drag
+const ASPECT_RATIO_DEBOUNCE_MS: u64 = 150;
+
+// Stand-in for "no limit" when only one max dimension is configured
+const UNBOUNDED_WINDOW_SIZE: f64 = 16_384.0;
+
+// Time for a single-page app to render its account menu after the load event
+const IDENTITY_DETECT_DELAY: Duration = Duration::from_secs(2);
+
+// How long after a load auto_click_on_load keeps waiting for its element
+const AUTO_CLICK_WAIT_MS: u64 = 10_000;
+
+// Longest a page can keep the window raised with one flash_to_front call
+const MAX_FLASH_TO_FRONT: Duration = Duration::from_secs(30);
+
+const PIP_STATE_KEY: &str = "pip_geometry";
+
+const READING_PROGRESS_STATE_KEY: &str = "reading_progress";
+
+// Gap between the picture-in-picture window and the screen edges, in logical pixels
+const PIP_MARGIN: f64 = 16.0;
+
+// Dim-on-blur fades take DIM_FADE_STEPS * DIM_FADE_STEP_MS, about 150ms
+const DIM_FADE_STEPS: u32 = 10;
+const DIM_FADE_STEP_MS: u64 = 15;
+const MIN_DIM_OPACITY: f64 = 0.2;
+
+const ALLOW_DOMAIN_LABEL: &str = "Always Allow";
+const OPEN_IN_BROWSER_LABEL: &str = "Open in Browser";
+const CANCEL_LABEL: &str = "Cancel";
+
+// Schemes the webview loads itself; anything else is an external protocol
+const WEBVIEW_SCHEMES: [&str; 10] = [
+    "http",
+    "https",
+    "about",
+    "blob",
+    "data",
+    "file",
+    "javascript",
+    "tauri",
+    "asset",
+    "ipc",
+];
+
 #[cfg(target_os = "windows")]
 fn build_proxy_browser_arg(url: &Url) -> Option<String> {
     let host = url.host_str()?;
@@ -22,9 +104,128 @@ fn build_proxy_browser_arg(url: &Url) -> Option<String> {
     }
 }
 
-pub fn set_window(app: &mut App, config: &PakeConfig, tauri_config: &Config) -> WebviewWindow {
-    let package_name = tauri_config.clone().product_name.unwrap();
-    let _data_dir = get_data_dir(app.handle(), package_name);
+/// Split `webview2_args` into switches, keeping double-quoted values together and
+/// dropping anything that isn't a `--switch`.
+#[cfg(target_os = "windows")]
+fn parse_webview2_args(args: &str) -> Vec<String> {
+    let mut parsed = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    for c in args.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                current.push(c);
+            }
+            c if c.is_whitespace() && !quoted => {
+                if !current.is_empty() {
+                    parsed.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if quoted {
+        log::warn!("Unbalanced quote in webview2_args, ignoring the last argument");
+        current.clear();
+    }
+    if !current.is_empty() {
+        parsed.push(current);
+    }
+
+    parsed
+        .into_iter()
+        .filter(|arg| {
+            let valid = arg.len() > 2 && arg.starts_with("--") && !arg.starts_with("---");
+            if !valid {
+                log::warn!("Ignoring invalid webview2_args entry: {}", arg);
+            }
+            valid
+        })
+        .collect()
+}
+
+fn escape_html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+/// Build a self-submitting form page for `initial_post`. Only form encodings
+/// are supported since that is all a plain HTML form can send.
+fn initial_post_url(window_config: &WindowConfig, post: &InitialPost) -> Result<Url, String> {
+    if window_config.url_type != "web" {
+        return Err("initial_post requires a web url".to_string());
+    }
+    let content_type = post.content_type.as_str();
+    if !matches!(
+        content_type,
+        "application/x-www-form-urlencoded" | "multipart/form-data"
+    ) {
+        return Err(format!(
+            "Unsupported initial_post content type: {content_type}"
+        ));
+    }
+
+    let main_url = Url::parse(&window_config.url).map_err(|e| format!("Invalid url: {e}"))?;
+    let target = match &post.url {
+        Some(url) => main_url
+            .join(url)
+            .map_err(|e| format!("Invalid initial_post url: {e}"))?,
+        None => main_url.clone(),
+    };
+    let main_host = main_url.host_str().unwrap_or_default();
+    let target_host = target.host_str().unwrap_or_default();
+    if !matches!(target.scheme(), "http" | "https") || !host_matches(target_host, main_host) {
+        return Err(format!(
+            "initial_post target {target} is not on {main_host}"
+        ));
+    }
+
+    let mut fields = Url::parse("http://localhost/").unwrap();
+    fields.set_query(Some(&post.body));
+    let inputs: String = fields
+        .query_pairs()
+        .map(|(name, value)| {
+            format!(
+                r#"<input type="hidden" name="{}" value="{}">"#,
+                escape_html(&name),
+                escape_html(&value)
+            )
+        })
+        .collect();
+    let html = format!(
+        r#"<!DOCTYPE html><html><body><form method="post" action="{}" enctype="{}">{}</form><script>document.forms[0].submit();</script></body></html>"#,
+        escape_html(target.as_str()),
+        content_type,
+        inputs
+    );
+
+    Url::parse(&format!("data:text/html;base64,{}", STANDARD.encode(html)))
+        .map_err(|e| format!("Failed to build initial_post page: {e}"))
+}
+
+pub fn set_window(app: &AppHandle, config: &PakeConfig, tauri_config: &Config) -> WebviewWindow {
+    build_main_window(app, config, tauri_config, None)
+}
+
+// `resume_url` reopens a page the main window was already on, so the start-up
+// only steps (environment restore, home URL rotation, initial_post) are skipped.
+fn build_main_window(
+    app: &AppHandle,
+    config: &PakeConfig,
+    tauri_config: &Config,
+    resume_url: Option<Url>,
+) -> WebviewWindow {
+    let package_name = with_app_id_suffix(
+        tauri_config.product_name.as_deref().unwrap(),
+        config.app_id_suffix.as_deref(),
+        '-',
+    );
+    let _data_dir = get_data_dir(app, package_name);
 
     let window_config = config
         .windows
@@ -33,15 +234,35 @@ pub fn set_window(app: &mut App, config: &PakeConfig, tauri_config: &Config) ->
 
     let user_agent = config.user_agent.get();
 
-    let url = match window_config.url_type.as_str() {
+    let mut url = match window_config.url_type.as_str() {
         "web" => WebviewUrl::App(window_config.url.parse().unwrap()),
         "local" => WebviewUrl::App(PathBuf::from(&window_config.url)),
         _ => panic!("url type can only be web or local"),
     };
 
+    if let Some(resume_url) = resume_url {
+        url = WebviewUrl::External(resume_url);
+    } else {
+        if window_config.url_type == "web" {
+            if let Some(environment_url) = environments::restore(app, window_config) {
+                url = WebviewUrl::External(environment_url);
+            } else if let Some(home_url) = next_home_url() {
+                url = WebviewUrl::External(home_url);
+            }
+        }
+
+        if let Some(post) = &window_config.initial_post {
+            match initial_post_url(window_config, post) {
+                Ok(post_url) => url = WebviewUrl::External(post_url),
+                Err(e) => log::warn!("Ignoring initial_post, falling back to GET: {}", e),
+            }
+        }
+    }
+
     let config_script = format!(
-        "window.pakeConfig = {}",
-        serde_json::to_string(&window_config).unwrap()
+        "window.pakeConfig = {}; window.pakeShortcuts = {}",
+        serde_json::to_string(&window_config).unwrap(),
+        serde_json::to_string(&window_config.resolved_shortcuts()).unwrap()
     );
 
     // Platform-specific title: macOS prefers empty, others fallback to product name
@@ -53,14 +274,19 @@ pub fn set_window(app: &mut App, config: &PakeConfig, tauri_config: &Config) ->
         }
     });
 
+    let size = window_config.window_size();
+
     let mut window_builder = WebviewWindowBuilder::new(app, "pake", url)
         .title(effective_title)
         .visible(false)
         .user_agent(user_agent)
         .resizable(window_config.resizable)
         .maximized(window_config.maximize)
-        .inner_size(window_config.width, window_config.height)
-        .always_on_top(window_config.always_on_top)
+        .inner_size(size.width, size.height)
+        .always_on_top(
+            window_config.always_on_top || window_config.window_level != WindowLevel::Normal,
+        )
+        .visible_on_all_workspaces(window_config.visible_on_all_workspaces)
         .incognito(window_config.incognito);
 
     #[cfg(any(target_os = "windows", target_os = "macos"))]
@@ -68,21 +294,40 @@ pub fn set_window(app: &mut App, config: &PakeConfig, tauri_config: &Config) ->
         window_builder = window_builder.fullscreen(window_config.fullscreen);
     }
 
-    if window_config.min_width > 0.0 || window_config.min_height > 0.0 {
-        let min_w = if window_config.min_width > 0.0 {
-            window_config.min_width
+    if !window_config.remember_window_state {
+        window_builder = window_builder.center();
+    }
+
+    if size.min_width > 0.0 || size.min_height > 0.0 {
+        let min_w = if size.min_width > 0.0 {
+            size.min_width
         } else {
-            window_config.width
+            size.width
         };
-        let min_h = if window_config.min_height > 0.0 {
-            window_config.min_height
+        let min_h = if size.min_height > 0.0 {
+            size.min_height
         } else {
-            window_config.height
+            size.height
         };
         window_builder = window_builder.min_inner_size(min_w, min_h);
     }
 
-    if !window_config.enable_drag_drop {
+    if size.max_width > 0.0 || size.max_height > 0.0 {
+        let max_w = if size.max_width > 0.0 {
+            size.max_width
+        } else {
+            UNBOUNDED_WINDOW_SIZE
+        };
+        let max_h = if size.max_height > 0.0 {
+            size.max_height
+        } else {
+            UNBOUNDED_WINDOW_SIZE
+        };
+        window_builder = window_builder.max_inner_size(max_w, max_h);
+    }
+
+    // File drop relies on Tauri's native handler to see the dropped paths
+    if !window_config.enable_drag_drop && !window_config.enable_file_drop {
         window_builder = window_builder.disable_drag_drop_handler();
     }
 
@@ -91,6 +336,63 @@ pub fn set_window(app: &mut App, config: &PakeConfig, tauri_config: &Config) ->
             .on_new_window(move |_url, _features| tauri::webview::NewWindowResponse::Allow);
     }
 
+    if let Some(script) = window_config
+        .accent_color
+        .as_deref()
+        .and_then(accent_color_script)
+    {
+        window_builder = window_builder.initialization_script(script);
+    }
+
+    // Painted before the first page load so dark sites don't flash white
+    if let Some(color) = &window_config.background_color {
+        match parse_hex_color(color) {
+            Some((r, g, b)) => {
+                window_builder = window_builder.background_color(Color(r, g, b, 255));
+            }
+            None => log::warn!("Invalid background_color, expected a hex color: {}", color),
+        }
+    }
+
+    if let Some(script) = scrollbar_script(window_config.scrollbar) {
+        window_builder = window_builder.initialization_script(script);
+    }
+
+    let javascript_enabled = javascript_enabled(window_config);
+    #[cfg(target_os = "macos")]
+    if !javascript_enabled {
+        window_builder = window_builder.initialization_script(BLOCK_PAGE_SCRIPTS_SCRIPT);
+    }
+
+    for script in &window_config.init_scripts {
+        match script.run_at {
+            RunAt::DocumentStart => {
+                window_builder = window_builder.initialization_script(user_script(&script.js));
+            }
+            RunAt::DocumentEnd => {
+                window_builder =
+                    window_builder.initialization_script(document_end_script(&script.js));
+            }
+            // Evaluated from on_page_load below
+            RunAt::Load => {}
+        }
+    }
+
+    if window_config.smooth_scrolling == Some(false) {
+        window_builder = window_builder.initialization_script(SCROLL_BEHAVIOR_SCRIPT);
+    }
+
+    if let Some(family) = window_config.font_family.as_deref() {
+        window_builder = window_builder.initialization_script(font_family_script(family));
+    }
+
+    // WebView2 throttles natively, see app::throttle
+    #[cfg(all(debug_assertions, not(target_os = "windows")))]
+    {
+        window_builder =
+            window_builder.initialization_script(include_str!("../inject/throttle.js"));
+    }
+
     // Add initialization scripts
     window_builder = window_builder
         .initialization_script(&config_script)
@@ -98,7 +400,22 @@ pub fn set_window(app: &mut App, config: &PakeConfig, tauri_config: &Config) ->
         .initialization_script(include_str!("../inject/event.js"))
         .initialization_script(include_str!("../inject/style.js"))
         .initialization_script(include_str!("../inject/theme_refresh.js"))
+        .initialization_script(include_str!("../inject/theme_sync.js"))
         .initialization_script(include_str!("../inject/auth.js"))
+        .initialization_script(include_str!("../inject/reader.js"))
+        .initialization_script(include_str!("../inject/translate.js"))
+        .initialization_script(include_str!("../inject/content_block.js"))
+        .initialization_script(include_str!("../inject/har.js"))
+        .initialization_script(include_str!("../inject/load_timing.js"))
+        .initialization_script(include_str!("../inject/site_data.js"))
+        .initialization_script(include_str!("../inject/permissions.js"))
+        .initialization_script(include_str!("../inject/clipboard.js"))
+        .initialization_script(include_str!("../inject/accessibility.js"))
+        .initialization_script(include_str!("../inject/resize.js"))
+        .initialization_script(include_str!("../inject/progress.js"))
+        .initialization_script(include_str!("../inject/first_paint.js"))
+        .initialization_script(include_str!("../inject/media.js"))
+        .initialization_script(include_str!("../inject/dialogs.js"))
         .initialization_script(include_str!("../inject/custom.js"));
 
     #[cfg(target_os = "windows")]
@@ -145,6 +462,28 @@ pub fn set_window(app: &mut App, config: &PakeConfig, tauri_config: &Config) ->
         }
     }
 
+    // WebView2 exposes no setting for it, Chromium's switch does the same job
+    #[cfg(target_os = "windows")]
+    if window_config.smooth_scrolling == Some(false) {
+        windows_browser_args.push_str(" --disable-smooth-scrolling");
+    }
+
+    // Chromium falls back to software rendering with these, WebView2 has no setting of its own
+    #[cfg(target_os = "windows")]
+    if window_config.disable_gpu {
+        windows_browser_args.push_str(" --disable-gpu --disable-gpu-compositing");
+    }
+
+    #[cfg(target_os = "windows")]
+    if let Some(args) = &window_config.webview2_args {
+        let args = parse_webview2_args(args);
+        if !args.is_empty() {
+            log::info!("Applying webview2_args: {}", args.join(" "));
+            windows_browser_args.push(' ');
+            windows_browser_args.push_str(&args.join(" "));
+        }
+    }
+
     let mut parsed_proxy_url: Option<Url> = None;
 
     // Platform-specific configuration must be set before proxy on Windows/Linux
@@ -208,10 +547,111 @@ pub fn set_window(app: &mut App, config: &PakeConfig, tauri_config: &Config) ->
         println!("Proxy configured: {}", config.proxy_url);
     }
 
+    let allowed_host = Url::from_str(&window_config.url)
+        .ok()
+        .and_then(|u| u.host_str().map(str::to_string));
+    let confirm_external_navigation = window_config.confirm_external_navigation;
+    let external_protocols = window_config.external_protocols;
+    let session_allowed_hosts: Arc<Mutex<HashSet<String>>> = Arc::default();
+    let app_handle = app.clone();
+    let navigation_rules = NavigationRules::new(
+        &window_config.navigation_allow,
+        &window_config.navigation_deny,
+    );
+    let initial_redirect_hosts = window_config.follow_initial_redirects_to.clone();
+    let environments = window_config.environments.clone();
+    let initial_load_pending = Arc::new(AtomicBool::new(!initial_redirect_hosts.is_empty()));
+
+    // Each finished load may end the startup redirect window and starts with fresh page state
+    {
+        let initial_load_pending = Arc::clone(&initial_load_pending);
+        let allowed_host = allowed_host.clone();
+        let environments = environments.clone();
+        let detect_identity = window_config.identity_selector.is_some();
+        let show_on_first_paint = window_config.show_on_first_paint;
+        let auto_click = window_config
+            .auto_click_on_load
+            .as_deref()
+            .map(auto_click_script);
+        let load_scripts: Vec<String> = window_config
+            .init_scripts
+            .iter()
+            .filter(|script| script.run_at == RunAt::Load)
+            .map(|script| user_script(&script.js))
+            .collect();
+        window_builder = window_builder.on_page_load(move |window, payload| {
+            track_loading(&window, &payload);
+            if payload.event() != PageLoadEvent::Finished {
+                return;
+            }
+            let on_environment_host = payload
+                .url()
+                .host_str()
+                .is_some_and(|host| is_environment_host(&environments, host));
+            if on_environment_host || is_on_allowed_host(payload.url(), allowed_host.as_deref()) {
+                initial_load_pending.store(false, Ordering::SeqCst);
+            }
+            sync_resize_handles(&window);
+            sync_reading_progress(&window);
+            preferences::sync_color_scheme_meta(&window);
+            for script in &load_scripts {
+                if let Err(e) = window.eval(script) {
+                    log::warn!("Failed to run init script: {}", e);
+                }
+            }
+            if let Some(script) = &auto_click {
+                let _ = window.eval(script);
+            }
+            // Pages that never paint anything contentful still count once loaded
+            if show_on_first_paint {
+                mark_first_paint();
+            }
+
+            if detect_identity {
+                tauri::async_runtime::spawn(async move {
+                    tokio::time::sleep(IDENTITY_DETECT_DELAY).await;
+                    if let Err(e) = view_state::refresh_identity(&window).await {
+                        log::warn!("Failed to detect signed-in account: {}", e);
+                    }
+                });
+            }
+        });
+    }
+
+    window_builder = window_builder.on_document_title_changed(|window, title| {
+        view_state::on_page_title_changed(&window, &title);
+        if unread::enabled() {
+            unread::update_from_title(&window, &title);
+        }
+    });
+
     // Allow navigation to OAuth/authentication domains
-    window_builder = window_builder.on_navigation(|url| {
+    window_builder = window_builder.on_navigation(move |url| {
         let url_str = url.as_str();
 
+        if is_external_protocol(url) {
+            open_external_protocol(&app_handle, url.clone(), external_protocols);
+            return false;
+        }
+
+        match navigation_rules.decide(url_str) {
+            NavigationDecision::Deny => {
+                notify_denied(&app_handle, url_str);
+                return false;
+            }
+            NavigationDecision::Allow => return true,
+            NavigationDecision::Default => {}
+        }
+
+        if initial_load_pending.load(Ordering::SeqCst)
+            && url
+                .host_str()
+                .is_some_and(|host| is_initial_redirect_host(host, &initial_redirect_hosts))
+        {
+            log::info!("Following startup redirect through: {}", url_str);
+            return true;
+        }
+
         // Always allow same-origin navigation
         if url_str.starts_with("http://localhost") || url_str.starts_with("http://127.0.0.1") {
             return true;
@@ -246,9 +686,1715 @@ pub fn set_window(app: &mut App, config: &PakeConfig, tauri_config: &Config) ->
             }
         }
 
+        if confirm_external_navigation && matches!(url.scheme(), "http" | "https") {
+            if let (Some(host), Some(allowed_host)) = (url.host_str(), allowed_host.as_deref()) {
+                let is_session_allowed = session_allowed_hosts.lock().unwrap().contains(host);
+                if !host_matches(host, allowed_host)
+                    && !is_environment_host(&environments, host)
+                    && !is_session_allowed
+                {
+                    log::info!("Intercepted external navigation to: {}", url);
+                    prompt_external_navigation(
+                        &app_handle,
+                        url.clone(),
+                        Arc::clone(&session_allowed_hosts),
+                    );
+                    return false;
+                }
+            }
+        }
+
         // Allow all other navigation by default
         true
     });
 
-    window_builder.build().expect("Failed to build window")
+    // GTK reads the Wayland app_id and X11 WM_CLASS when the window is realized
+    #[cfg(target_os = "linux")]
+    {
+        let app_id = window_config
+            .linux_app_id
+            .as_deref()
+            .unwrap_or(&tauri_config.identifier);
+        webkit2gtk::gtk::glib::set_prgname(Some(app_id));
+        webkit2gtk::gtk::gdk::set_program_class(app_id);
+    }
+
+    let window = window_builder.build().expect("Failed to build window");
+
+    // Fullscreen is set on the builder and a restored MAXIMIZED flag is already applied
+    let restored_maximized = window.is_maximized().unwrap_or(false);
+    if launch_placement(window_config, restored_maximized) == LaunchPlacement::Maximized
+        && !restored_maximized
+    {
+        let _ = window.maximize();
+    }
+
+    view_state::restore_view_state(&window);
+
+    // Off the main thread, where reading the cookie store can deadlock on Windows
+    {
+        let window = window.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = site_data::restore_preserved_cookies(&window).await {
+                log::warn!("{}", e);
+            }
+        });
+    }
+
+    if !javascript_enabled {
+        let window = window.clone();
+        tauri::async_runtime::spawn(async move {
+            if !apply_javascript_setting(&window, false).await {
+                log::warn!("Page JavaScript could not be turned off in the live webview");
+            }
+        });
+    }
+
+    if window_config.disable_password_manager {
+        disable_password_manager(&window);
+    }
+
+    #[cfg(target_os = "linux")]
+    if let Some(enabled) = window_config.smooth_scrolling {
+        set_smooth_scrolling(&window, enabled);
+    }
+
+    if window_config.min_font_size.is_some() || window_config.font_family.is_some() {
+        apply_font_settings(
+            &window,
+            window_config.min_font_size,
+            window_config.font_family.clone(),
+        );
+    }
+
+    apply_permissions(
+        &window,
+        window_config.permissions,
+        window_config.clipboard_access,
+    );
+
+    #[cfg(target_os = "macos")]
+    if window_config.window_level == WindowLevel::AboveFullscreen {
+        set_above_fullscreen(&window);
+    }
+
+    #[cfg(target_os = "macos")]
+    set_swipe_navigation(&window, window_config.allow_swipe_navigation);
+
+    #[cfg(target_os = "windows")]
+    content_rules::install_request_filter(&window);
+
+    if let Some(path) = &window_config.content_block_rules {
+        match content_rules::load_rules_file(path) {
+            Ok(rules) => content_rules::apply_rules(&window, rules),
+            Err(e) => log::warn!("{}", e),
+        }
+    }
+    if content_rules::adblock_enabled(window.app_handle()) {
+        content_rules::apply_adblock(&window, true);
+    }
+
+    let invalid_cert_hosts = window_config.invalid_cert_hosts();
+    if !invalid_cert_hosts.is_empty() {
+        allow_invalid_certs_for_hosts(&window, invalid_cert_hosts);
+    }
+
+    if let Some(color) = &window_config.titlebar_color {
+        if let Err(e) = apply_titlebar_color(&window, color) {
+            log::warn!("{}", e);
+        }
+    }
+
+    window
+}
+
+fn is_on_allowed_host(url: &Url, allowed_host: Option<&str>) -> bool {
+    match (url.host_str(), allowed_host) {
+        (Some(host), Some(allowed_host)) => host_matches(host, allowed_host),
+        _ => false,
+    }
+}
+
+/// Whether `host` is one of `follow_initial_redirects_to`, subdomains included.
+/// Blank entries are ignored so they can't match every host.
+pub fn is_initial_redirect_host(host: &str, redirect_hosts: &[String]) -> bool {
+    redirect_hosts
+        .iter()
+        .map(|redirect_host| redirect_host.trim().to_ascii_lowercase())
+        .filter(|redirect_host| !redirect_host.is_empty())
+        .any(|redirect_host| host_matches(host, &redirect_host))
+}
+
+/// Ask before leaving the configured host. "Always Allow" remembers the domain
+/// until the app quits and then resumes the navigation in-app.
+fn prompt_external_navigation(
+    app: &AppHandle,
+    url: Url,
+    allowed_hosts: Arc<Mutex<HashSet<String>>>,
+) {
+    let app_handle = app.clone();
+    app.dialog()
+        .message(format!("This page wants to navigate to:\n{}", url))
+        .title("Leave this app?")
+        .kind(MessageDialogKind::Warning)
+        .buttons(MessageDialogButtons::YesNoCancelCustom(
+            ALLOW_DOMAIN_LABEL.to_string(),
+            OPEN_IN_BROWSER_LABEL.to_string(),
+            CANCEL_LABEL.to_string(),
+        ))
+        .show_with_result(move |result| match result {
+            MessageDialogResult::Yes => allow_and_navigate(&app_handle, url, &allowed_hosts),
+            MessageDialogResult::Custom(label) if label == ALLOW_DOMAIN_LABEL => {
+                allow_and_navigate(&app_handle, url, &allowed_hosts)
+            }
+            MessageDialogResult::No => open_in_browser(&app_handle, &url),
+            MessageDialogResult::Custom(label) if label == OPEN_IN_BROWSER_LABEL => {
+                open_in_browser(&app_handle, &url)
+            }
+            _ => {}
+        });
+}
+
+/// Whether `url` uses a scheme the webview can't load, such as `mailto:` or `tel:`.
+fn is_external_protocol(url: &Url) -> bool {
+    !WEBVIEW_SCHEMES.contains(&url.scheme())
+}
+
+/// Route an external protocol link to the system handler as `mode` allows.
+fn open_external_protocol(app: &AppHandle, url: Url, mode: ExternalProtocols) {
+    match mode {
+        ExternalProtocols::Allow => open_in_browser(app, &url),
+        ExternalProtocols::Deny => log::info!("Blocked external protocol link: {}", url),
+        ExternalProtocols::Prompt => {
+            let app_handle = app.clone();
+            app.dialog()
+                .message(format!(
+                    "This page wants to open a {}: link:\n{}",
+                    url.scheme(),
+                    url
+                ))
+                .title("Open in another app?")
+                .buttons(MessageDialogButtons::OkCancelCustom(
+                    "Open".to_string(),
+                    CANCEL_LABEL.to_string(),
+                ))
+                .show(move |open| {
+                    if open {
+                        open_in_browser(&app_handle, &url);
+                    }
+                });
+        }
+    }
+}
+
+fn allow_and_navigate(app: &AppHandle, url: Url, allowed_hosts: &Mutex<HashSet<String>>) {
+    if let Some(host) = url.host_str() {
+        allowed_hosts.lock().unwrap().insert(host.to_string());
+    }
+    if let Some(window) = app.get_webview_window("pake") {
+        let _ = window.navigate(url);
+    }
+}
+
+fn open_in_browser(app: &AppHandle, url: &Url) {
+    if let Err(e) = app.opener().open_url(url.as_str(), None::<&str>) {
+        log::error!("Failed to open URL in browser: {}", e);
+    }
+}
+
+/// Ask the page whether it has unsaved changes and, if so, confirm before leaving.
+pub async fn confirm_unload(window: &WebviewWindow) -> bool {
+    let wants_prompt = eval_with_result(window, "window.pakeShouldConfirmUnload?.()")
+        .await
+        .map(|value| value.as_bool().unwrap_or(false))
+        .unwrap_or(false);
+    if !wants_prompt {
+        return true;
+    }
+
+    let (sender, receiver) = tokio::sync::oneshot::channel();
+    window
+        .dialog()
+        .message("Changes you made may not be saved.")
+        .title("Leave site?")
+        .kind(MessageDialogKind::Warning)
+        .buttons(MessageDialogButtons::OkCancelCustom(
+            "Leave".to_string(),
+            "Stay".to_string(),
+        ))
+        .show(move |confirmed| {
+            let _ = sender.send(confirmed);
+        });
+    receiver.await.unwrap_or(false)
+}
+
+/// `confirm_unload` on the main window when `beforeunload` is `confirm`, for the
+/// regular quit paths. `force_quit` deliberately skips it.
+pub async fn confirm_quit(app: &AppHandle) -> bool {
+    if get_pake_config().0.windows[0].beforeunload != BeforeUnloadBehavior::Confirm {
+        return true;
+    }
+    match app.get_webview_window("pake") {
+        Some(window) => confirm_unload(&window).await,
+        None => true,
+    }
+}
+
+/// Where the main window ends up on launch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LaunchPlacement {
+    Fullscreen,
+    Maximized,
+    /// The geometry the window-state plugin restored, or the configured size.
+    Restored,
+}
+
+// Window state is restored while building, so this runs on the built window.
+// Fullscreen wins, then start_maximized, then the restored MAXIMIZED flag.
+fn launch_placement(window_config: &WindowConfig, restored_maximized: bool) -> LaunchPlacement {
+    if window_config.fullscreen {
+        LaunchPlacement::Fullscreen
+    } else if window_config.start_maximized || restored_maximized {
+        LaunchPlacement::Maximized
+    } else {
+        LaunchPlacement::Restored
+    }
+}
+
+// Runtime choice from `set_javascript_enabled`, ahead of `javascript_enabled` in pake.json
+static JAVASCRIPT_OVERRIDE: Mutex<Option<bool>> = Mutex::new(None);
+
+// Set while the main window is torn down and rebuilt, so closing it doesn't quit the app
+static RECREATING_WINDOW: AtomicBool = AtomicBool::new(false);
+
+// How long to wait for the old main window to be gone before building its replacement
+const RECREATE_TIMEOUT: Duration = Duration::from_secs(2);
+
+// WKWebView only reads its script setting when it's created, so a recreated webview
+// blocks page scripts with a CSP at document start. Pake's own scripts are user
+// scripts and keep running.
+#[cfg(target_os = "macos")]
+const BLOCK_PAGE_SCRIPTS_SCRIPT: &str = r#"(function () {
+  const meta = document.createElement("meta");
+  meta.httpEquiv = "Content-Security-Policy";
+  meta.content = "script-src 'none'";
+  const head =
+    document.head ||
+    document.documentElement.insertBefore(
+      document.createElement("head"),
+      document.documentElement.firstChild,
+    );
+  head.prepend(meta);
+})();"#;
+
+/// Whether page JavaScript runs: the last `set_javascript_enabled` this session,
+/// else `javascript_enabled` from pake.json.
+pub fn javascript_enabled(window_config: &WindowConfig) -> bool {
+    JAVASCRIPT_OVERRIDE
+        .lock()
+        .unwrap()
+        .unwrap_or(window_config.javascript_enabled)
+}
+
+pub fn is_recreating_window() -> bool {
+    RECREATING_WINDOW.load(Ordering::SeqCst)
+}
+
+// Write the setting into the live webview. Returns whether it took effect, which
+// only a reload is then needed to show:
+// - Windows: `ICoreWebView2Settings::IsScriptEnabled`, applies from the next navigation
+// - Linux: WebKitGTK `enable-javascript-markup`, applies from the next navigation
+// - macOS: `WKWebView.configuration` hands back a copy, so the change is read back
+//   from a fresh copy and reported as not applied when it didn't stick
+async fn apply_javascript_setting(window: &WebviewWindow, enabled: bool) -> bool {
+    let (sender, receiver) = oneshot::channel();
+    let result = window.with_webview(move |_webview| {
+        #[cfg(target_os = "macos")]
+        let applied = unsafe {
+            let wk_webview: &objc2_web_kit::WKWebView = &*_webview.inner().cast();
+            wk_webview
+                .configuration()
+                .defaultWebpagePreferences()
+                .setAllowsContentJavaScript(enabled);
+            wk_webview
+                .configuration()
+                .defaultWebpagePreferences()
+                .allowsContentJavaScript()
+                == enabled
+        };
+
+        #[cfg(target_os = "windows")]
+        let applied = unsafe {
+            _webview
+                .controller()
+                .CoreWebView2()
+                .and_then(|core| core.Settings())
+                .and_then(|settings| settings.SetIsScriptEnabled(enabled))
+                .is_ok()
+        };
+
+        #[cfg(target_os = "linux")]
+        let applied = {
+            use webkit2gtk::{SettingsExt, WebViewExt};
+            match _webview.inner().settings() {
+                Some(settings) => {
+                    settings.set_enable_javascript_markup(enabled);
+                    true
+                }
+                None => false,
+            }
+        };
+
+        let _ = sender.send(applied);
+    });
+
+    if let Err(e) = result {
+        log::warn!("Failed to update JavaScript setting: {}", e);
+        return false;
+    }
+    receiver.await.unwrap_or(false)
+}
+
+/// Turn page JavaScript on or off for the main window for the rest of the session.
+///
+/// Windows and Linux change the live webview setting and reload natively. macOS
+/// does the same when the setting sticks; otherwise, and wherever the live setting
+/// can't be written, the main window is rebuilt with the new setting.
+pub async fn set_javascript_enabled(window: &WebviewWindow, enabled: bool) -> Result<(), String> {
+    *JAVASCRIPT_OVERRIDE.lock().unwrap() = Some(enabled);
+    if apply_javascript_setting(window, enabled).await {
+        return window
+            .reload()
+            .map_err(|e| format!("Failed to reload: {}", e));
+    }
+    log::info!("JavaScript setting needs a new webview, recreating the main window");
+    recreate_main_window(window).await
+}
+
+// Destroy the main window and build it again from pake.json, keeping its page and geometry
+async fn recreate_main_window(window: &WebviewWindow) -> Result<(), String> {
+    let app = window.app_handle().clone();
+    let current_url = window.url().ok();
+    let position = window.outer_position().ok();
+    let size = window.inner_size().ok();
+
+    RECREATING_WINDOW.store(true, Ordering::SeqCst);
+    let result = async {
+        window
+            .destroy()
+            .map_err(|e| format!("Failed to close window: {}", e))?;
+        let deadline = tokio::time::Instant::now() + RECREATE_TIMEOUT;
+        while app.get_webview_window("pake").is_some() {
+            if tokio::time::Instant::now() > deadline {
+                return Err("Timed out closing the main window".to_string());
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+
+        let (sender, receiver) = oneshot::channel();
+        let app_handle = app.clone();
+        app.run_on_main_thread(move || {
+            let (config, tauri_config) = get_pake_config();
+            let window = build_main_window(&app_handle, &config, &tauri_config, current_url);
+            if let Some(size) = size {
+                let _ = window.set_size(size);
+            }
+            if let Some(position) = position {
+                let _ = window.set_position(position);
+            }
+            let _ = window.show();
+            let _ = window.set_focus();
+            let _ = sender.send(());
+        })
+        .map_err(|e| format!("Failed to recreate window: {}", e))?;
+        receiver
+            .await
+            .map_err(|_| "Window recreation was dropped".to_string())
+    }
+    .await;
+    RECREATING_WINDOW.store(false, Ordering::SeqCst);
+    result
+}
+
+/// Turn off the webview's autofill and "save password" prompts.
+///
+/// - Windows: `ICoreWebView2Settings4` `IsGeneralAutofillEnabled` and
+///   `IsPasswordAutosaveEnabled` (WebView2 runtime 92+)
+/// - macOS: WKWebView never offers to save passwords outside Safari, nothing to do
+/// - Linux: WebKitGTK ships no password manager, nothing to do
+fn disable_password_manager(window: &WebviewWindow) {
+    let result = window.with_webview(|_webview| {
+        #[cfg(target_os = "windows")]
+        unsafe {
+            use webview2_com::Microsoft::Web::WebView2::Win32::ICoreWebView2Settings4;
+            use windows::core::Interface;
+
+            let Ok(settings) = _webview
+                .controller()
+                .CoreWebView2()
+                .and_then(|core| core.Settings())
+                .and_then(|settings| settings.cast::<ICoreWebView2Settings4>())
+            else {
+                log::warn!("WebView2 runtime is too old to disable the password manager");
+                return;
+            };
+            let _ = settings.SetIsGeneralAutofillEnabled(false);
+            let _ = settings.SetIsPasswordAutosaveEnabled(false);
+        }
+    });
+
+    if let Err(e) = result {
+        log::warn!("Failed to disable password manager: {}", e);
+    }
+}
+
+/// Kinetic wheel scrolling on WebKitGTK, which some sites make feel janky.
+/// macOS has no per-webview switch and Windows handles it with a browser argument.
+#[cfg(target_os = "linux")]
+fn set_smooth_scrolling(window: &WebviewWindow, enabled: bool) {
+    let result = window.with_webview(move |webview| {
+        use webkit2gtk::{SettingsExt, WebViewExt};
+        if let Some(settings) = webview.inner().settings() {
+            settings.set_enable_smooth_scrolling(enabled);
+        }
+    });
+
+    if let Err(e) = result {
+        log::warn!("Failed to update smooth scrolling: {}", e);
+    }
+}
+
+// CSS fallback for engines without a native switch; `!important` so a site's own
+// `scroll-behavior: smooth` can't bring the animation back
+const SCROLL_BEHAVIOR_SCRIPT: &str = r#"(function () {
+  const style = document.createElement("style");
+  style.id = "pake-scroll-behavior";
+  style.textContent = "html, body, * { scroll-behavior: auto !important; }";
+  const apply = () => document.documentElement.appendChild(style);
+  if (document.documentElement) apply();
+  else document.addEventListener("DOMContentLoaded", apply);
+})();"#;
+
+/// Native font overrides; the family is also forced through `font_family_script`,
+/// since engine defaults only reach text the page leaves unstyled.
+///
+/// - macOS: `WKPreferences.minimumFontSize`
+/// - Linux: WebKitGTK `minimum-font-size` and `default-font-family`
+/// - Windows: WebView2 has no minimum font size, only the CSS family applies
+fn apply_font_settings(window: &WebviewWindow, min_font_size: Option<u32>, family: Option<String>) {
+    let result = window.with_webview(move |_webview| {
+        #[cfg(target_os = "macos")]
+        unsafe {
+            let wk_webview: &objc2_web_kit::WKWebView = &*_webview.inner().cast();
+            if let Some(size) = min_font_size {
+                wk_webview
+                    .configuration()
+                    .preferences()
+                    .setMinimumFontSize(size as f64);
+            }
+        }
+
+        #[cfg(target_os = "linux")]
+        {
+            use webkit2gtk::{SettingsExt, WebViewExt};
+            if let Some(settings) = _webview.inner().settings() {
+                if let Some(size) = min_font_size {
+                    settings.set_minimum_font_size(size);
+                }
+                // WebKitGTK takes a single family name, not a CSS list
+                if let Some(first) = family.as_deref().and_then(|list| list.split(',').next()) {
+                    settings.set_default_font_family(first.trim().trim_matches(['"', '\'']));
+                }
+            }
+        }
+
+        #[cfg(not(target_os = "linux"))]
+        let _ = (min_font_size, family);
+    });
+
+    if let Err(e) = result {
+        log::warn!("Failed to apply font settings: {}", e);
+    }
+}
+
+// Monospace and icon fonts keep their own families so code and glyph icons still render
+fn font_family_script(family: &str) -> String {
+    let css = format!(
+        "body, body *:not(pre, code, kbd, samp, i, [class*=\"icon\"]) {{ font-family: {} !important; }}",
+        family
+    );
+    format!(
+        r#"(function () {{
+  const style = document.createElement("style");
+  style.id = "pake-font-family";
+  style.textContent = {};
+  const apply = () => document.documentElement.appendChild(style);
+  if (document.documentElement) apply();
+  else document.addEventListener("DOMContentLoaded", apply);
+}})();"#,
+        serde_json::to_string(&css).unwrap()
+    )
+}
+
+// A layered <style> loses to any unlayered scrollbar rules the site ships
+fn scrollbar_script(style: ScrollbarStyle) -> Option<String> {
+    let css = match style {
+        ScrollbarStyle::Default => return None,
+        ScrollbarStyle::Overlay => {
+            "::-webkit-scrollbar { width: 8px; height: 8px; background: transparent; }
+::-webkit-scrollbar-track, ::-webkit-scrollbar-corner { background: transparent; }
+::-webkit-scrollbar-thumb { background: rgba(128, 128, 128, 0.4); border-radius: 4px; }
+::-webkit-scrollbar-thumb:hover { background: rgba(128, 128, 128, 0.6); }"
+        }
+        ScrollbarStyle::Hidden => {
+            "html { scrollbar-width: none; }
+::-webkit-scrollbar { display: none; width: 0; height: 0; }"
+        }
+        ScrollbarStyle::Themed => {
+            "html { scrollbar-color: var(--pake-accent, rgba(128, 128, 128, 0.5)) transparent; }
+::-webkit-scrollbar { width: 10px; height: 10px; background: transparent; }
+::-webkit-scrollbar-thumb {
+  background: var(--pake-accent, rgba(128, 128, 128, 0.5));
+  border-radius: 5px;
+}"
+        }
+    };
+    Some(format!(
+        r#"(function () {{
+  const style = document.createElement("style");
+  style.id = "pake-scrollbar";
+  style.textContent = {};
+  const apply = () => document.documentElement.appendChild(style);
+  if (document.documentElement) apply();
+  else document.addEventListener("DOMContentLoaded", apply);
+}})();"#,
+        serde_json::to_string(&format!("@layer pake-scrollbar {{\n{}\n}}", css)).unwrap()
+    ))
+}
+
+// Expose the accent as `--pake-accent` on :root and `window.pakeAccentColor`.
+// Invalid colors are logged and skipped.
+fn accent_color_script(color: &str) -> Option<String> {
+    let Some((r, g, b)) = parse_hex_color(color) else {
+        log::warn!("Invalid accent_color, expected a hex color: {}", color);
+        return None;
+    };
+    Some(format!(
+        r##"window.pakeAccentColor = "#{r:02x}{g:02x}{b:02x}";
+(function () {{
+  const apply = () =>
+    document.documentElement.style.setProperty("--pake-accent", window.pakeAccentColor);
+  if (document.documentElement) apply();
+  else document.addEventListener("DOMContentLoaded", apply);
+}})();"##
+    ))
+}
+
+/// Open a standalone viewer window, e.g. for PDFs or images that should not be downloaded.
+pub fn build_external_window(app: &AppHandle, url: Url) -> tauri::Result<WebviewWindow> {
+    let window_id = EXTERNAL_WINDOW_COUNTER.fetch_add(1, Ordering::SeqCst);
+    WebviewWindowBuilder::new(
+        app,
+        format!("pake-external-{}", window_id),
+        WebviewUrl::External(url),
+    )
+    .title("")
+    .on_page_load(|window, payload| track_loading(&window, &payload))
+    .on_document_title_changed(|window, title| view_state::on_page_title_changed(&window, &title))
+    .inner_size(1200.0, 780.0)
+    .build()
+    .inspect(view_state::restore_view_state)
+}
+
+/// Open inline content (PDFs, images) in a viewer window. Its label is outside
+/// the `pake-*` capability, so the viewed content gets no access to Pake's commands.
+pub fn build_viewer_window(app: &AppHandle, url: Url) -> tauri::Result<WebviewWindow> {
+    let window_id = EXTERNAL_WINDOW_COUNTER.fetch_add(1, Ordering::SeqCst);
+    WebviewWindowBuilder::new(
+        app,
+        format!("viewer-{}", window_id),
+        WebviewUrl::External(url),
+    )
+    .title("")
+    .on_page_load(|window, payload| track_loading(&window, &payload))
+    .inner_size(1200.0, 780.0)
+    .build()
+}
+
+/// Content type of a `data:` URL, or else one guessed from the path's extension
+/// for the types `inline_content_types` is meant for.
+pub fn inline_content_type(url: &Url) -> Option<String> {
+    if url.scheme() == "data" {
+        let media_type = url.path().split([';', ',']).next()?.trim();
+        return (!media_type.is_empty()).then(|| media_type.to_ascii_lowercase());
+    }
+    let extension = url.path().rsplit_once('.')?.1.to_ascii_lowercase();
+    let content_type = match extension.as_str() {
+        "pdf" => "application/pdf",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        "bmp" => "image/bmp",
+        "avif" => "image/avif",
+        _ => return None,
+    };
+    Some(content_type.to_string())
+}
+
+/// Tint the native title bar and pick a contrasting title text color.
+/// Windows uses the DWM caption/text color attributes (Windows 11+), macOS colors
+/// the NSWindow background behind a transparent title bar, Linux is left untouched.
+pub fn apply_titlebar_color(window: &WebviewWindow, hex: &str) -> Result<(), String> {
+    let rgb = parse_hex_color(hex).ok_or(format!("Invalid titlebar color: {}", hex))?;
+    let _is_dark = is_dark_color(rgb);
+
+    #[cfg(target_os = "windows")]
+    {
+        use windows::Win32::Foundation::COLORREF;
+        use windows::Win32::Graphics::Dwm::{
+            DwmSetWindowAttribute, DWMWA_CAPTION_COLOR, DWMWA_TEXT_COLOR,
+        };
+
+        let hwnd = window.hwnd().map_err(|e| e.to_string())?;
+        let (r, g, b) = rgb;
+        let caption_color = COLORREF(r as u32 | (g as u32) << 8 | (b as u32) << 16);
+        let text_color = COLORREF(if _is_dark { 0x00FF_FFFF } else { 0 });
+        let size = std::mem::size_of::<COLORREF>() as u32;
+
+        unsafe {
+            DwmSetWindowAttribute(
+                hwnd,
+                DWMWA_CAPTION_COLOR,
+                &caption_color as *const _ as _,
+                size,
+            )
+            .map_err(|e| format!("Failed to set caption color: {}", e))?;
+            DwmSetWindowAttribute(hwnd, DWMWA_TEXT_COLOR, &text_color as *const _ as _, size)
+                .map_err(|e| format!("Failed to set caption text color: {}", e))?;
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        use objc2_app_kit::{NSColor, NSWindow};
+
+        let theme = if _is_dark { Theme::Dark } else { Theme::Light };
+        let _ = window.set_theme(Some(theme));
+
+        let ns_window = window.ns_window().map_err(|e| e.to_string())? as usize;
+        let (r, g, b) = rgb;
+        window
+            .run_on_main_thread(move || unsafe {
+                let ns_window: &NSWindow = &*(ns_window as *const NSWindow);
+                let color = NSColor::colorWithSRGBRed_green_blue_alpha(
+                    r as f64 / 255.0,
+                    g as f64 / 255.0,
+                    b as f64 / 255.0,
+                    1.0,
+                );
+                ns_window.setTitlebarAppearsTransparent(true);
+                ns_window.setBackgroundColor(Some(&color));
+            })
+            .map_err(|e| e.to_string())?;
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        let _ = window;
+        log::warn!("titlebar_color is not supported on Linux, ignoring");
+    }
+
+    Ok(())
+}
+
+/// Accept invalid TLS certificates, but only for the allowlisted hosts.
+/// Linux retries the failed load after trusting the certificate for that host,
+/// Windows allows it via `ServerCertificateErrorDetected`. WKWebView gives no hook
+/// outside wry's navigation delegate, so macOS keeps rejecting them.
+fn allow_invalid_certs_for_hosts(window: &WebviewWindow, hosts: Vec<String>) {
+    let result = window.with_webview(move |_webview| {
+        #[cfg(target_os = "linux")]
+        {
+            use crate::util::is_invalid_cert_allowed;
+            use webkit2gtk::{WebContextExt, WebViewExt};
+            _webview.inner().connect_load_failed_with_tls_errors(
+                move |webview, failing_uri, certificate, _errors| {
+                    let Some(host) = Url::parse(failing_uri)
+                        .ok()
+                        .and_then(|u| u.host_str().map(str::to_string))
+                    else {
+                        return false;
+                    };
+                    if !is_invalid_cert_allowed(&host, &hosts) {
+                        return false;
+                    }
+                    let Some(context) = webview.context() else {
+                        return false;
+                    };
+                    context.allow_tls_certificate_for_host(certificate, &host);
+                    webview.load_uri(failing_uri);
+                    true
+                },
+            );
+        }
+
+        #[cfg(target_os = "windows")]
+        unsafe {
+            use crate::util::is_invalid_cert_allowed;
+            use webview2_com::Microsoft::Web::WebView2::Win32::{
+                ICoreWebView2_14, COREWEBVIEW2_SERVER_CERTIFICATE_ERROR_ACTION_ALWAYS_ALLOW,
+            };
+            use webview2_com::{take_pwstr, ServerCertificateErrorDetectedEventHandler};
+            use windows::core::{Interface, PWSTR};
+
+            let Ok(core) = _webview
+                .controller()
+                .CoreWebView2()
+                .and_then(|core| core.cast::<ICoreWebView2_14>())
+            else {
+                log::warn!("WebView2 runtime is too old to accept invalid certificates");
+                return;
+            };
+            let handler =
+                ServerCertificateErrorDetectedEventHandler::create(Box::new(move |_, args| {
+                    let Some(args) = args else {
+                        return Ok(());
+                    };
+                    let mut uri = PWSTR::null();
+                    args.RequestUri(&mut uri)?;
+                    let is_allowed = Url::parse(&take_pwstr(uri))
+                        .ok()
+                        .and_then(|u| {
+                            u.host_str()
+                                .map(|host| is_invalid_cert_allowed(host, &hosts))
+                        })
+                        .unwrap_or(false);
+                    if is_allowed {
+                        args.SetAction(COREWEBVIEW2_SERVER_CERTIFICATE_ERROR_ACTION_ALWAYS_ALLOW)?;
+                    }
+                    Ok(())
+                }));
+            let mut token = 0;
+            let _ = core.add_ServerCertificateErrorDetected(&handler, &mut token);
+        }
+
+        #[cfg(target_os = "macos")]
+        {
+            let _ = &hosts;
+            log::warn!("accept_invalid_certs is not supported by WKWebView, ignoring");
+        }
+    });
+
+    if let Err(e) = result {
+        log::warn!("Failed to configure certificate handling: {}", e);
+    }
+}
+
+/// Raise the window to status-bar level and let it join fullscreen Spaces.
+#[cfg(target_os = "macos")]
+fn set_above_fullscreen(window: &WebviewWindow) {
+    use objc2_app_kit::{NSWindow, NSWindowCollectionBehavior};
+
+    // kCGStatusWindowLevel, above fullscreen apps but below the menu bar dropdowns
+    const STATUS_WINDOW_LEVEL: isize = 25;
+
+    let Ok(ns_window) = window.ns_window() else {
+        return;
+    };
+    let ns_window = ns_window as usize;
+    let _ = window.run_on_main_thread(move || unsafe {
+        let ns_window: &NSWindow = &*(ns_window as *const NSWindow);
+        ns_window.setLevel(STATUS_WINDOW_LEVEL);
+        ns_window.setCollectionBehavior(
+            ns_window.collectionBehavior() | NSWindowCollectionBehavior::FullScreenAuxiliary,
+        );
+    });
+}
+
+/// Toggle WKWebView's back/forward swipe gestures, which wry leaves off.
+#[cfg(target_os = "macos")]
+fn set_swipe_navigation(window: &WebviewWindow, enabled: bool) {
+    let result = window.with_webview(move |webview| unsafe {
+        let wk_webview: &objc2_web_kit::WKWebView = &*webview.inner().cast();
+        wk_webview.setAllowsBackForwardNavigationGestures(enabled);
+    });
+
+    if let Err(e) = result {
+        log::warn!("Failed to update swipe navigation: {}", e);
+    }
+}
+
+/// Move the window onto the active Space and activate the app. The collection
+/// behavior is only borrowed for the order-front, so later Space switches behave as before.
+#[cfg(target_os = "macos")]
+pub fn bring_to_active_space(window: &WebviewWindow) {
+    use objc2_app_kit::{
+        NSApplicationActivationOptions, NSRunningApplication, NSWindow, NSWindowCollectionBehavior,
+    };
+
+    let Ok(ns_window) = window.ns_window() else {
+        return;
+    };
+    let ns_window = ns_window as usize;
+    let _ = window.run_on_main_thread(move || unsafe {
+        let ns_window: &NSWindow = &*(ns_window as *const NSWindow);
+        let behavior = ns_window.collectionBehavior();
+        ns_window.setCollectionBehavior(behavior | NSWindowCollectionBehavior::MoveToActiveSpace);
+        ns_window.makeKeyAndOrderFront(None);
+        #[allow(deprecated)]
+        NSRunningApplication::currentApplication()
+            .activateWithOptions(NSApplicationActivationOptions::ActivateIgnoringOtherApps);
+        ns_window.setCollectionBehavior(behavior);
+    });
+}
+
+/// Target rect `(x, y, width, height)` for a snap position inside a monitor work area.
+pub fn snap_rect(area: (i32, i32, u32, u32), position: SnapPosition) -> (i32, i32, u32, u32) {
+    let (x, y, width, height) = area;
+    let (half_w, half_h) = (width / 2, height / 2);
+    let (right_x, bottom_y) = (x + half_w as i32, y + half_h as i32);
+
+    match position {
+        SnapPosition::Left => (x, y, half_w, height),
+        SnapPosition::Right => (right_x, y, width - half_w, height),
+        SnapPosition::Top => (x, y, width, half_h),
+        SnapPosition::Bottom => (x, bottom_y, width, height - half_h),
+        SnapPosition::TopLeft => (x, y, half_w, half_h),
+        SnapPosition::TopRight => (right_x, y, width - half_w, half_h),
+        SnapPosition::BottomLeft => (x, bottom_y, half_w, height - half_h),
+        SnapPosition::BottomRight => (right_x, bottom_y, width - half_w, height - half_h),
+    }
+}
+
+/// Snap within the work area of the monitor the window is currently on.
+pub fn snap_window_to(window: &WebviewWindow, position: SnapPosition) -> tauri::Result<()> {
+    let Some(monitor) = window.current_monitor()?.or(window.primary_monitor()?) else {
+        return Ok(());
+    };
+    let work_area = monitor.work_area();
+    let (x, y, width, height) = snap_rect(
+        (
+            work_area.position.x,
+            work_area.position.y,
+            work_area.size.width,
+            work_area.size.height,
+        ),
+        position,
+    );
+
+    if window.is_fullscreen()? {
+        window.set_fullscreen(false)?;
+    }
+    if window.is_maximized()? {
+        window.unmaximize()?;
+    }
+    window.set_position(PhysicalPosition::new(x, y))?;
+    window.set_size(PhysicalSize::new(width, height))
+}
+
+/// Show the page's resize handles only while the window has no native frame to drag.
+pub fn sync_resize_handles(window: &WebviewWindow) {
+    let frameless =
+        !window.is_decorated().unwrap_or(true) && window.is_resizable().unwrap_or(false);
+    let _ = window.eval(format!("window.pakeSetResizeHandles?.({})", frameless));
+}
+
+// Initialization scripts all run at document start, so later ones wait for the DOM
+fn document_end_script(js: &str) -> String {
+    format!(
+        r#"document.addEventListener("DOMContentLoaded", () => {{
+{}
+}}, {{ once: true }});"#,
+        user_script(js)
+    )
+}
+
+// Banners often render after the load event, so watch for the element a while
+fn auto_click_script(selector: &str) -> String {
+    format!(
+        r#"(() => {{
+  const find = () => {{
+    try {{
+      return document.querySelector({selector});
+    }} catch (_) {{
+      return null;
+    }}
+  }};
+  const element = find();
+  if (element) {{
+    element.click();
+    return;
+  }}
+  const observer = new MutationObserver(() => {{
+    const element = find();
+    if (!element) return;
+    observer.disconnect();
+    element.click();
+  }});
+  observer.observe(document.documentElement, {{ childList: true, subtree: true }});
+  setTimeout(() => observer.disconnect(), {timeout});
+}})()"#,
+        selector = serde_json::to_string(selector).unwrap(),
+        timeout = AUTO_CLICK_WAIT_MS
+    )
+}
+
+/// Whether the reading progress bar is shown: the last `set_reading_progress`,
+/// else `reading_progress` from pake.json.
+pub fn reading_progress_enabled(app: &AppHandle) -> bool {
+    load_app_state(app, READING_PROGRESS_STATE_KEY)
+        .and_then(|value| value.as_bool())
+        .unwrap_or_else(|| get_pake_config().0.windows[0].reading_progress)
+}
+
+/// Re-apply the toggle after a load, since the page starts from the config default.
+pub fn sync_reading_progress(window: &WebviewWindow) {
+    let enabled = reading_progress_enabled(window.app_handle());
+    let _ = window.eval(format!("window.pakeSetReadingProgress?.({})", enabled));
+}
+
+/// Show or hide the reading progress bar in every window and remember the choice.
+pub fn set_reading_progress(app: &AppHandle, enabled: bool) -> Result<(), String> {
+    save_app_state(app, READING_PROGRESS_STATE_KEY, enabled.into())?;
+    for window in app.webview_windows().values() {
+        sync_reading_progress(window);
+    }
+    Ok(())
+}
+
+/// Begin a native resize drag from `edge`, as if the user grabbed the window frame there.
+pub fn start_resize_from(window: &WebviewWindow, edge: ResizeEdge) -> tauri::Result<()> {
+    let direction = match edge {
+        ResizeEdge::Left => ResizeDirection::West,
+        ResizeEdge::Right => ResizeDirection::East,
+        ResizeEdge::Top => ResizeDirection::North,
+        ResizeEdge::Bottom => ResizeDirection::South,
+        ResizeEdge::TopLeft => ResizeDirection::NorthWest,
+        ResizeEdge::TopRight => ResizeDirection::NorthEast,
+        ResizeEdge::BottomLeft => ResizeDirection::SouthWest,
+        ResizeEdge::BottomRight => ResizeDirection::SouthEast,
+    };
+    window.start_resize_dragging(direction)
+}
+
+/// Index of the monitor `pinned` refers to, matching names first and then
+/// falling back to a zero-based index into `names`.
+pub fn match_monitor(names: &[Option<String>], pinned: &str) -> Option<usize> {
+    names
+        .iter()
+        .position(|name| name.as_deref() == Some(pinned))
+        .or_else(|| pinned.parse().ok().filter(|&index| index < names.len()))
+}
+
+/// Fit rect `(x, y, width, height)` inside a work area, shrinking it only when it is larger.
+pub fn clamp_rect(rect: (i32, i32, u32, u32), area: (i32, i32, u32, u32)) -> (i32, i32, u32, u32) {
+    let (x, y, width, height) = rect;
+    let (area_x, area_y, area_width, area_height) = area;
+    let (width, height) = (width.min(area_width), height.min(area_height));
+
+    (
+        x.clamp(area_x, area_x + (area_width - width) as i32),
+        y.clamp(area_y, area_y + (area_height - height) as i32),
+        width,
+        height,
+    )
+}
+
+/// Name recorded by `pin_to_current_monitor`, else the configured `pinned_monitor`.
+pub fn pinned_monitor(app: &AppHandle) -> Option<String> {
+    load_app_state(app, "pinned_monitor")
+        .and_then(|value| value.as_str().map(str::to_string))
+        .or_else(|| get_pake_config().0.windows[0].pinned_monitor.clone())
+}
+
+/// Remember the monitor the window is on, by name when the platform reports one.
+pub fn pin_current_monitor(window: &WebviewWindow) -> Result<String, String> {
+    let current = window
+        .current_monitor()
+        .map_err(|e| e.to_string())?
+        .ok_or("Window is not on any monitor")?;
+    let monitors = window.available_monitors().map_err(|e| e.to_string())?;
+    let pinned = match current.name() {
+        Some(name) => name.clone(),
+        None => monitors
+            .iter()
+            .position(|monitor| monitor.position() == current.position())
+            .unwrap_or_default()
+            .to_string(),
+    };
+    save_app_state(window.app_handle(), "pinned_monitor", pinned.clone().into())?;
+    Ok(pinned)
+}
+
+/// Move the window onto the pinned monitor's work area, keeping its offset from
+/// the work area it came from. A disconnected monitor falls back to the primary one.
+pub fn move_to_pinned_monitor(window: &WebviewWindow, pinned: &str) -> tauri::Result<()> {
+    if window.is_fullscreen()? {
+        return Ok(());
+    }
+
+    let monitors = window.available_monitors()?;
+    let names: Vec<Option<String>> = monitors.iter().map(|m| m.name().cloned()).collect();
+    let target = match match_monitor(&names, pinned) {
+        Some(index) => Some(monitors[index].clone()),
+        None => window.primary_monitor()?,
+    };
+    let Some(target) = target else {
+        return Ok(());
+    };
+
+    let work_area = target.work_area();
+    let area = (
+        work_area.position.x,
+        work_area.position.y,
+        work_area.size.width,
+        work_area.size.height,
+    );
+    let position = window.outer_position()?;
+    let size = window.outer_size()?;
+    let (offset_x, offset_y) = match window.current_monitor()? {
+        Some(current) => {
+            let current_area = current.work_area();
+            (
+                position.x - current_area.position.x,
+                position.y - current_area.position.y,
+            )
+        }
+        None => (0, 0),
+    };
+    let (x, y, width, height) = clamp_rect(
+        (
+            area.0 + offset_x,
+            area.1 + offset_y,
+            size.width,
+            size.height,
+        ),
+        area,
+    );
+
+    if (x, y) == (position.x, position.y) && (width, height) == (size.width, size.height) {
+        return Ok(());
+    }
+    let was_maximized = window.is_maximized()?;
+    if was_maximized {
+        window.unmaximize()?;
+    }
+    window.set_position(PhysicalPosition::new(x, y))?;
+    if (width, height) != (size.width, size.height) {
+        window.set_size(PhysicalSize::new(width, height))?;
+    }
+    if was_maximized {
+        window.maximize()?;
+    }
+    Ok(())
+}
+
+/// Logical size with width / height equal to `ratio`, driven by whichever edge
+/// moved most since `previous` and clamped to the min/max limits (max wins on conflict).
+pub fn aspect_fit(
+    size: (f64, f64),
+    previous: Option<(f64, f64)>,
+    ratio: f64,
+    limits: &WindowSize,
+) -> (f64, f64) {
+    let (width, height) = size;
+    let height_driven = previous.is_some_and(|(prev_width, prev_height)| {
+        (height - prev_height).abs() > (width - prev_width).abs()
+    });
+    let width = if height_driven { height * ratio } else { width };
+
+    let upper = |limit: f64| if limit > 0.0 { limit } else { f64::INFINITY };
+    let max_width = upper(limits.max_width).min(upper(limits.max_height) * ratio);
+    let min_width = limits
+        .min_width
+        .max(limits.min_height * ratio)
+        .min(max_width);
+    let width = width.clamp(min_width, max_width);
+
+    (width, width / ratio)
+}
+
+/// Restore `ratio` once resizing settles. Only the latest resize is acted on,
+/// and the resize caused by our own set_size is already in ratio, so it stops there.
+pub fn schedule_aspect_ratio(window: &Window, ratio: f64, limits: WindowSize) {
+    let generation = RESIZE_GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+    let window = window.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(ASPECT_RATIO_DEBOUNCE_MS)).await;
+        if RESIZE_GENERATION.load(Ordering::SeqCst) != generation {
+            return;
+        }
+        let is_free_sized = window.is_fullscreen().unwrap_or(false)
+            || window.is_maximized().unwrap_or(false)
+            || window.is_minimized().unwrap_or(false);
+        let (Ok(scale_factor), Ok(size)) = (window.scale_factor(), window.inner_size()) else {
+            return;
+        };
+        if is_free_sized {
+            return;
+        }
+
+        let size = size.to_logical::<f64>(scale_factor);
+        let mut last_fitted = LAST_FITTED_SIZE.lock().unwrap();
+        let (width, height) = aspect_fit((size.width, size.height), *last_fitted, ratio, &limits);
+        *last_fitted = Some((width, height));
+        if (width - size.width).abs() < 1.0 && (height - size.height).abs() < 1.0 {
+            return;
+        }
+        let _ = window.set_size(LogicalSize::new(width, height));
+    });
+}
+
+/// Where a window's dim-on-blur fade stands. `generation` is bumped per focus
+/// change so the newer fade stops any one still running.
+struct Fade {
+    generation: u64,
+    opacity: f64,
+}
+
+static FADES: LazyLock<Mutex<HashMap<String, Fade>>> = LazyLock::new(Default::default);
+
+/// Fade to `dim_opacity` (clamped to 0.2–1.0) when the window loses focus and
+/// back to opaque when it regains it, starting from wherever a previous fade stopped.
+pub fn fade_for_focus(window: &Window, focused: bool, dim_opacity: f64) {
+    let target = if focused {
+        1.0
+    } else {
+        dim_opacity.clamp(MIN_DIM_OPACITY, 1.0)
+    };
+    let label = window.label().to_string();
+    let (generation, start) = {
+        let mut fades = FADES.lock().unwrap();
+        let fade = fades.entry(label.clone()).or_insert(Fade {
+            generation: 0,
+            opacity: 1.0,
+        });
+        fade.generation += 1;
+        (fade.generation, fade.opacity)
+    };
+    if (target - start).abs() < f64::EPSILON {
+        return;
+    }
+
+    let window = window.clone();
+    tauri::async_runtime::spawn(async move {
+        for step in 1..=DIM_FADE_STEPS {
+            tokio::time::sleep(Duration::from_millis(DIM_FADE_STEP_MS)).await;
+            let opacity = start + (target - start) * step as f64 / DIM_FADE_STEPS as f64;
+            {
+                let mut fades = FADES.lock().unwrap();
+                match fades.get_mut(&label) {
+                    Some(fade) if fade.generation == generation => fade.opacity = opacity,
+                    _ => return,
+                }
+            }
+            if let Err(e) = set_window_opacity(&window, opacity) {
+                log::warn!("Failed to set window opacity: {}", e);
+                return;
+            }
+        }
+    });
+}
+
+/// Whole-window opacity, 0.0–1.0.
+///
+/// - macOS: `NSWindow.alphaValue`
+/// - Windows: a layered window with a constant alpha
+/// - Linux: `GtkWidget` opacity, which needs a compositing window manager
+fn set_window_opacity(window: &Window, opacity: f64) -> Result<(), String> {
+    #[cfg(target_os = "macos")]
+    {
+        use objc2_app_kit::NSWindow;
+
+        let ns_window = window.ns_window().map_err(|e| e.to_string())? as usize;
+        window
+            .run_on_main_thread(move || unsafe {
+                let ns_window: &NSWindow = &*(ns_window as *const NSWindow);
+                ns_window.setAlphaValue(opacity);
+            })
+            .map_err(|e| e.to_string())?;
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        use windows::Win32::Foundation::COLORREF;
+        use windows::Win32::UI::WindowsAndMessaging::{
+            GetWindowLongPtrW, SetLayeredWindowAttributes, SetWindowLongPtrW, GWL_EXSTYLE,
+            LWA_ALPHA, WS_EX_LAYERED,
+        };
+
+        let hwnd = window.hwnd().map_err(|e| e.to_string())?;
+        unsafe {
+            let style = GetWindowLongPtrW(hwnd, GWL_EXSTYLE);
+            if style & WS_EX_LAYERED.0 as isize == 0 {
+                SetWindowLongPtrW(hwnd, GWL_EXSTYLE, style | WS_EX_LAYERED.0 as isize);
+            }
+            SetLayeredWindowAttributes(
+                hwnd,
+                COLORREF(0),
+                (opacity * 255.0).round() as u8,
+                LWA_ALPHA,
+            )
+            .map_err(|e| e.to_string())?;
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        use webkit2gtk::gtk::prelude::WidgetExt;
+
+        let target = window.clone();
+        window
+            .run_on_main_thread(move || {
+                if let Ok(gtk_window) = target.gtk_window() {
+                    gtk_window.set_opacity(opacity);
+                }
+            })
+            .map_err(|e| e.to_string())?;
+    }
+
+    Ok(())
+}
+
+/// Window geometry from before picture-in-picture, persisted so it survives a relaunch.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct PipGeometry {
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+    maximized: bool,
+    always_on_top: bool,
+    decorated: bool,
+}
+
+/// Top-left position that tucks a `size` window into `corner` of a work area.
+pub fn pip_position(
+    area: (i32, i32, u32, u32),
+    size: (u32, u32),
+    corner: PipCorner,
+    margin: i32,
+) -> (i32, i32) {
+    let (x, y, width, height) = area;
+    let left = x + margin;
+    let top = y + margin;
+    let right = x + width as i32 - size.0 as i32 - margin;
+    let bottom = y + height as i32 - size.1 as i32 - margin;
+
+    match corner {
+        PipCorner::TopLeft => (left, top),
+        PipCorner::TopRight => (right, top),
+        PipCorner::BottomLeft => (left, bottom),
+        PipCorner::BottomRight => (right, bottom),
+    }
+}
+
+fn saved_pip_geometry(app: &AppHandle) -> Option<PipGeometry> {
+    load_app_state(app, PIP_STATE_KEY).and_then(|value| serde_json::from_value(value).ok())
+}
+
+/// Shrink into a small always-on-top window in a screen corner. Entering again
+/// only moves it, keeping the geometry saved the first time.
+pub fn enter_pip(
+    window: &WebviewWindow,
+    config: &WindowConfig,
+    corner: PipCorner,
+) -> tauri::Result<()> {
+    let app = window.app_handle();
+    if saved_pip_geometry(app).is_none() {
+        if window.is_fullscreen()? {
+            window.set_fullscreen(false)?;
+        }
+        let position = window.outer_position()?;
+        let size = window.inner_size()?;
+        let geometry = PipGeometry {
+            x: position.x,
+            y: position.y,
+            width: size.width,
+            height: size.height,
+            maximized: window.is_maximized()?,
+            always_on_top: window.is_always_on_top()?,
+            decorated: window.is_decorated()?,
+        };
+        if let Err(e) = save_app_state(app, PIP_STATE_KEY, serde_json::to_value(geometry)?) {
+            log::warn!("{}", e);
+        }
+    }
+
+    let Some(monitor) = window.current_monitor()?.or(window.primary_monitor()?) else {
+        return Ok(());
+    };
+    if window.is_maximized()? {
+        window.unmaximize()?;
+    }
+    if config.pip_hide_decorations {
+        window.set_decorations(false)?;
+    }
+    window.set_always_on_top(true)?;
+    window.set_size(LogicalSize::new(config.pip_width, config.pip_height))?;
+
+    // Place by outer size so the title bar, if kept, stays on screen too
+    let work_area = monitor.work_area();
+    let outer = window.outer_size()?;
+    let (x, y) = pip_position(
+        (
+            work_area.position.x,
+            work_area.position.y,
+            work_area.size.width,
+            work_area.size.height,
+        ),
+        (outer.width, outer.height),
+        corner,
+        (PIP_MARGIN * monitor.scale_factor()).round() as i32,
+    );
+    window.set_position(PhysicalPosition::new(x, y))?;
+    sync_resize_handles(window);
+    Ok(())
+}
+
+/// Put back the geometry saved by `enter_pip`. Returns `false` when not in picture-in-picture.
+pub fn exit_pip(window: &WebviewWindow) -> tauri::Result<bool> {
+    let app = window.app_handle();
+    let Some(geometry) = saved_pip_geometry(app) else {
+        return Ok(false);
+    };
+
+    window.set_decorations(geometry.decorated)?;
+    sync_resize_handles(window);
+    window.set_always_on_top(geometry.always_on_top)?;
+    window.set_size(PhysicalSize::new(geometry.width, geometry.height))?;
+    window.set_position(PhysicalPosition::new(geometry.x, geometry.y))?;
+    if geometry.maximized {
+        window.maximize()?;
+    }
+    if let Err(e) = save_app_state(app, PIP_STATE_KEY, Value::Null) {
+        log::warn!("{}", e);
+    }
+    Ok(true)
+}
+
+/// A window raised by `flash_to_front`: the always-on-top state to go back to,
+/// and a generation so only the latest call restores it.
+struct Raise {
+    generation: u64,
+    was_on_top: bool,
+}
+
+static RAISES: LazyLock<Mutex<HashMap<String, Raise>>> = LazyLock::new(Default::default);
+
+/// Bring the window in front of everything and focus it for `duration` (at most
+/// 30s), then put its always-on-top state back. Overlapping calls extend the
+/// raise and keep the state saved by the first one.
+pub fn flash_to_front(window: &WebviewWindow, duration: Duration) -> Result<(), String> {
+    let label = window.label().to_string();
+    let generation = {
+        let mut raises = RAISES.lock().unwrap();
+        let raise = raises.entry(label.clone()).or_insert_with(|| Raise {
+            generation: 0,
+            was_on_top: window.is_always_on_top().unwrap_or(false),
+        });
+        raise.generation += 1;
+        raise.generation
+    };
+
+    window
+        .set_always_on_top(true)
+        .map_err(|e| format!("Failed to raise window: {}", e))?;
+    let _ = window.unminimize();
+    let _ = window.show();
+    let _ = window.set_focus();
+
+    let window = window.clone();
+    let duration = duration.min(MAX_FLASH_TO_FRONT);
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(duration).await;
+        let raise = {
+            let mut raises = RAISES.lock().unwrap();
+            match raises.get(&label) {
+                Some(raise) if raise.generation == generation => raises.remove(&label),
+                _ => None,
+            }
+        };
+        if let Some(raise) = raise {
+            let _ = window.set_always_on_top(raise.was_on_top);
+        }
+    });
+    Ok(())
+}
+
+// Labels of windows whose page is between load start and load end
+static LOADING: LazyLock<Mutex<HashSet<String>>> = LazyLock::new(Default::default);
+
+/// Whether the window labelled `label` is in the middle of a page load.
+pub fn is_loading(label: &str) -> bool {
+    LOADING.lock().unwrap().contains(label)
+}
+
+/// `on_page_load` hook for every window the app builds, so `is_loading` and the
+/// load events cover `pake-N`, external, viewer and preferences windows too.
+pub fn track_loading(window: &WebviewWindow, payload: &PageLoadPayload) {
+    set_loading(
+        window,
+        payload.event() == PageLoadEvent::Started,
+        payload.url(),
+    );
+}
+
+// Track the load and tell the window's page, emitting `pake://load-start` or
+// `pake://load-end` with the URL
+fn set_loading(window: &WebviewWindow, loading: bool, url: &Url) {
+    let label = window.label();
+    {
+        let mut windows = LOADING.lock().unwrap();
+        if loading {
+            windows.insert(label.to_string());
+        } else {
+            windows.remove(label);
+        }
+    }
+    let event = if loading {
+        LOAD_START_EVENT
+    } else {
+        LOAD_END_EVENT
+    };
+    let _ = window.emit_to(label, event, url.as_str());
+}
+
+// Signalled on the main window's first paint or first finished load
+static FIRST_PAINT: LazyLock<Notify> = LazyLock::new(Notify::new);
+
+/// Record that the main window has something on screen, releasing `wait_for_first_paint`.
+pub fn mark_first_paint() {
+    FIRST_PAINT.notify_one();
+}
+
+/// Wait for `mark_first_paint`, returning at once if it already happened.
+pub async fn wait_for_first_paint() {
+    FIRST_PAINT.notified().await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::config::test_window_config;
+    use crate::util::test_logger;
+    use serde_json::json;
+
+    #[test]
+    fn accent_color_script_sets_the_css_variable() {
+        let script = accent_color_script("#0af").unwrap();
+        assert!(script.contains(r##"window.pakeAccentColor = "#00aaff""##));
+        assert!(script.contains("--pake-accent"));
+    }
+
+    #[test]
+    fn invalid_accent_color_is_skipped_with_a_warning() {
+        test_logger::init();
+        for color in ["#12", "zzz"] {
+            assert_eq!(parse_hex_color(color), None);
+            assert_eq!(accent_color_script(color), None);
+            assert!(test_logger::logged(
+                log::Level::Warn,
+                &format!("Invalid accent_color, expected a hex color: {color}")
+            ));
+        }
+    }
+
+    #[test]
+    fn snap_halves_split_odd_sizes_without_gaps() {
+        let area = (100, 50, 1001, 701);
+        assert_eq!(snap_rect(area, SnapPosition::Left), (100, 50, 500, 701));
+        assert_eq!(snap_rect(area, SnapPosition::Right), (600, 50, 501, 701));
+        assert_eq!(snap_rect(area, SnapPosition::Top), (100, 50, 1001, 350));
+        assert_eq!(snap_rect(area, SnapPosition::Bottom), (100, 400, 1001, 351));
+    }
+
+    #[test]
+    fn snap_quadrants_tile_the_area() {
+        let area = (-1920, 0, 1921, 1081);
+        let top_left = snap_rect(area, SnapPosition::TopLeft);
+        let top_right = snap_rect(area, SnapPosition::TopRight);
+        let bottom_left = snap_rect(area, SnapPosition::BottomLeft);
+        let bottom_right = snap_rect(area, SnapPosition::BottomRight);
+        assert_eq!(top_left, (-1920, 0, 960, 540));
+        assert_eq!(top_right, (-960, 0, 961, 540));
+        assert_eq!(bottom_left, (-1920, 540, 960, 541));
+        assert_eq!(bottom_right, (-960, 540, 961, 541));
+        // Adjacent quadrants meet exactly and cover the full width and height
+        assert_eq!(top_left.0 + top_left.2 as i32, top_right.0);
+        assert_eq!(top_left.1 + top_left.3 as i32, bottom_left.1);
+        assert_eq!(top_left.2 + top_right.2, area.2);
+        assert_eq!(top_left.3 + bottom_left.3, area.3);
+    }
+
+    #[test]
+    fn clamp_rect_keeps_fitting_rects_in_place() {
+        let area = (0, 25, 1440, 875);
+        assert_eq!(clamp_rect((100, 100, 800, 600), area), (100, 100, 800, 600));
+    }
+
+    #[test]
+    fn clamp_rect_moves_offscreen_rects_inside() {
+        let area = (-1920, 0, 1920, 1080);
+        assert_eq!(
+            clamp_rect((-2100, -50, 800, 600), area),
+            (-1920, 0, 800, 600)
+        );
+        assert_eq!(
+            clamp_rect((-200, 900, 800, 600), area),
+            (-800, 480, 800, 600)
+        );
+    }
+
+    #[test]
+    fn clamp_rect_shrinks_rects_larger_than_the_area() {
+        let area = (0, 25, 1440, 875);
+        assert_eq!(clamp_rect((300, 300, 2560, 1600), area), (0, 25, 1440, 875));
+    }
+
+    fn limits(min: (f64, f64), max: (f64, f64)) -> WindowSize {
+        WindowSize {
+            width: 0.0,
+            height: 0.0,
+            min_width: min.0,
+            min_height: min.1,
+            max_width: max.0,
+            max_height: max.1,
+        }
+    }
+
+    #[test]
+    fn aspect_fit_follows_the_edge_that_moved() {
+        let unbounded = limits((0.0, 0.0), (0.0, 0.0));
+        assert_eq!(
+            aspect_fit((1600.0, 700.0), None, 2.0, &unbounded),
+            (1600.0, 800.0)
+        );
+        // Dragging the bottom edge changes the height more than the width
+        assert_eq!(
+            aspect_fit((1600.0, 1000.0), Some((1600.0, 800.0)), 2.0, &unbounded),
+            (2000.0, 1000.0)
+        );
+    }
+
+    #[test]
+    fn aspect_fit_respects_limits_through_the_ratio() {
+        let bounded = limits((0.0, 500.0), (0.0, 600.0));
+        assert_eq!(
+            aspect_fit((400.0, 200.0), None, 2.0, &bounded),
+            (1000.0, 500.0)
+        );
+        assert_eq!(
+            aspect_fit((3000.0, 1500.0), None, 2.0, &bounded),
+            (1200.0, 600.0)
+        );
+    }
+
+    #[test]
+    fn aspect_fit_prefers_max_when_limits_conflict() {
+        let conflicting = limits((800.0, 0.0), (600.0, 0.0));
+        assert_eq!(
+            aspect_fit((700.0, 350.0), None, 2.0, &conflicting),
+            (600.0, 300.0)
+        );
+        let conflicting = limits((0.0, 400.0), (0.0, 300.0));
+        assert_eq!(
+            aspect_fit((100.0, 50.0), None, 2.0, &conflicting),
+            (600.0, 300.0)
+        );
+    }
+
+    fn redirect_hosts(hosts: &[&str]) -> Vec<String> {
+        hosts.iter().map(|host| host.to_string()).collect()
+    }
+
+    #[test]
+    fn initial_redirect_host_includes_subdomains() {
+        let hosts = redirect_hosts(&["sso.example.com"]);
+        assert!(is_initial_redirect_host("sso.example.com", &hosts));
+        assert!(is_initial_redirect_host("eu.sso.example.com", &hosts));
+        assert!(!is_initial_redirect_host("example.com", &hosts));
+    }
+
+    #[test]
+    fn initial_redirect_host_ignores_whitespace_and_case() {
+        let hosts = redirect_hosts(&["  Login.Example.com \n"]);
+        assert!(is_initial_redirect_host("login.example.com", &hosts));
+    }
+
+    #[test]
+    fn initial_redirect_host_rejects_lookalikes() {
+        let hosts = redirect_hosts(&["example.com"]);
+        for host in [
+            "notexample.com",
+            "example.com.evil.net",
+            "example.co",
+            "evil-example.com",
+        ] {
+            assert!(!is_initial_redirect_host(host, &hosts), "{host}");
+        }
+    }
+
+    #[test]
+    fn initial_redirect_host_skips_blank_entries() {
+        let hosts = redirect_hosts(&["", "   "]);
+        assert!(!is_initial_redirect_host("example.com.", &hosts));
+        assert!(!is_initial_redirect_host("", &hosts));
+    }
+
+    #[test]
+    fn inline_content_type_reads_data_urls() {
+        let url = Url::parse("data:application/pdf;base64,JVBERi0=").unwrap();
+        assert_eq!(
+            inline_content_type(&url).as_deref(),
+            Some("application/pdf")
+        );
+        let url = Url::parse("data:Image/PNG,abc").unwrap();
+        assert_eq!(inline_content_type(&url).as_deref(), Some("image/png"));
+        let url = Url::parse("data:,plain").unwrap();
+        assert_eq!(inline_content_type(&url), None);
+    }
+
+    #[test]
+    fn inline_content_type_guesses_from_the_extension() {
+        let url = Url::parse("https://example.com/files/Report.PDF?dl=1").unwrap();
+        assert_eq!(
+            inline_content_type(&url).as_deref(),
+            Some("application/pdf")
+        );
+        let url = Url::parse("https://example.com/archive.zip").unwrap();
+        assert_eq!(inline_content_type(&url), None);
+        let url = Url::parse("https://example.com/download").unwrap();
+        assert_eq!(inline_content_type(&url), None);
+    }
+
+    #[test]
+    fn fullscreen_wins_over_maximized() {
+        let config = test_window_config(json!({ "start_maximized": true, "fullscreen": true }));
+        assert_eq!(
+            launch_placement(&config, false),
+            LaunchPlacement::Fullscreen
+        );
+        assert_eq!(launch_placement(&config, true), LaunchPlacement::Fullscreen);
+    }
+
+    #[test]
+    fn start_maximized_overrides_restored_geometry() {
+        let config = test_window_config(json!({ "start_maximized": true }));
+        assert_eq!(launch_placement(&config, false), LaunchPlacement::Maximized);
+    }
+
+    #[test]
+    fn restored_maximized_flag_applies_without_start_maximized() {
+        let config = test_window_config(json!({}));
+        assert_eq!(launch_placement(&config, true), LaunchPlacement::Maximized);
+    }
+
+    #[test]
+    fn restored_geometry_applies_otherwise() {
+        let config = test_window_config(json!({}));
+        assert_eq!(launch_placement(&config, false), LaunchPlacement::Restored);
+    }
 }
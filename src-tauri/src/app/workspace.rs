@@ -0,0 +1,125 @@
+use tauri::WebviewWindow;
+
+/// Move `window` to virtual desktop `index`, counting from 0.
+///
+/// - Linux: `_NET_WM_DESKTOP` through GDK, X11 only since Wayland leaves
+///   workspaces to the compositor
+/// - Windows: `IVirtualDesktopManager`, with desktops in Task View order
+/// - macOS: unsupported, Spaces can't be assigned by apps
+#[cfg(not(target_os = "macos"))]
+pub async fn move_to_workspace(window: &WebviewWindow, index: u32) -> Result<(), String> {
+    let (sender, receiver) = tokio::sync::oneshot::channel();
+    let target = window.clone();
+    window
+        .run_on_main_thread(move || {
+            let _ = sender.send(platform::move_to(&target, index));
+        })
+        .map_err(|e| format!("Failed to move window: {}", e))?;
+    receiver
+        .await
+        .map_err(|_| "Workspace request was dropped".to_string())?
+}
+
+#[cfg(target_os = "macos")]
+pub async fn move_to_workspace(_window: &WebviewWindow, _index: u32) -> Result<(), String> {
+    Err("Moving windows between Spaces is not supported on macOS".to_string())
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use gdkx11::{X11Screen, X11Window};
+    use tauri::WebviewWindow;
+    use webkit2gtk::gtk::prelude::*;
+
+    pub fn move_to(window: &WebviewWindow, index: u32) -> Result<(), String> {
+        let gdk_window = window
+            .gtk_window()
+            .map_err(|e| e.to_string())?
+            .window()
+            .ok_or("Window is not realized yet")?;
+        let x11_window = gdk_window
+            .downcast::<X11Window>()
+            .map_err(|_| "Workspaces can only be assigned on X11".to_string())?;
+
+        let count = x11_window
+            .screen()
+            .downcast::<X11Screen>()
+            .map(|screen| screen.number_of_desktops())
+            .unwrap_or(0);
+        if index >= count {
+            return Err(format!(
+                "Workspace {} does not exist, there are {}",
+                index, count
+            ));
+        }
+        x11_window.move_to_desktop(index);
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use tauri::WebviewWindow;
+    use windows::core::{w, GUID};
+    use windows::Win32::System::Com::{CoCreateInstance, CLSCTX_ALL};
+    use windows::Win32::System::Registry::{RegGetValueW, HKEY_CURRENT_USER, RRF_RT_REG_BINARY};
+    use windows::Win32::UI::Shell::{IVirtualDesktopManager, VirtualDesktopManager};
+
+    // The public COM API can't list desktops; Explorer keeps their IDs in order here.
+    // The value is missing until a second desktop is created.
+    fn desktop_ids() -> Option<Vec<GUID>> {
+        let mut buffer = [0u8; 16 * 64];
+        let mut size = buffer.len() as u32;
+        unsafe {
+            RegGetValueW(
+                HKEY_CURRENT_USER,
+                w!("Software\\Microsoft\\Windows\\CurrentVersion\\Explorer\\VirtualDesktops"),
+                w!("VirtualDesktopIDs"),
+                RRF_RT_REG_BINARY,
+                None,
+                Some(buffer.as_mut_ptr().cast()),
+                Some(&mut size),
+            )
+        }
+        .ok()
+        .ok()?;
+        Some(
+            buffer[..size as usize]
+                .chunks_exact(16)
+                .map(|bytes| {
+                    GUID::from_values(
+                        u32::from_le_bytes(bytes[0..4].try_into().unwrap()),
+                        u16::from_le_bytes(bytes[4..6].try_into().unwrap()),
+                        u16::from_le_bytes(bytes[6..8].try_into().unwrap()),
+                        bytes[8..16].try_into().unwrap(),
+                    )
+                })
+                .collect(),
+        )
+    }
+
+    pub fn move_to(window: &WebviewWindow, index: u32) -> Result<(), String> {
+        let Some(ids) = desktop_ids() else {
+            // A single desktop, which the window is already on
+            return if index == 0 {
+                Ok(())
+            } else {
+                Err(format!("Workspace {} does not exist, there is 1", index))
+            };
+        };
+        let id = ids.get(index as usize).ok_or(format!(
+            "Workspace {} does not exist, there are {}",
+            index,
+            ids.len()
+        ))?;
+        let hwnd = window.hwnd().map_err(|e| e.to_string())?;
+        unsafe {
+            let manager: IVirtualDesktopManager =
+                CoCreateInstance(&VirtualDesktopManager, None, CLSCTX_ALL)
+                    .map_err(|e| format!("Virtual desktops are unavailable: {}", e))?;
+            manager
+                .MoveWindowToDesktop(hwnd, id)
+                .map_err(|e| format!("Failed to move window: {}", e))
+        }
+    }
+}
@@ -0,0 +1,92 @@
+// Grayscale and reduced-motion modes toggled by set_grayscale / set_reduced_motion
+(function () {
+  const STYLE_ID = "pake-accessibility";
+  const REDUCED_MOTION_QUERY = /prefers-reduced-motion:\s*reduce/;
+  const modes = { grayscale: false, reduced_motion: false };
+
+  const GRAYSCALE_CSS = "html { filter: grayscale(100%) !important; }";
+  const REDUCED_MOTION_CSS = `*, *::before, *::after {
+  animation-duration: 0.01ms !important;
+  animation-iteration-count: 1 !important;
+  transition-duration: 0.01ms !important;
+  scroll-behavior: auto !important;
+}`;
+
+  // Pages that check the media query from JS see the reduced-motion preference too
+  const matchMedia = window.matchMedia.bind(window);
+  window.matchMedia = (query) => {
+    const result = matchMedia(query);
+    if (!modes.reduced_motion || !REDUCED_MOTION_QUERY.test(query)) {
+      return result;
+    }
+    return new Proxy(result, {
+      get(target, property) {
+        if (property === "matches") return true;
+        const value = target[property];
+        return typeof value === "function" ? value.bind(target) : value;
+      },
+    });
+  };
+
+  function applyStyle() {
+    const css = [
+      modes.grayscale && GRAYSCALE_CSS,
+      modes.reduced_motion && REDUCED_MOTION_CSS,
+    ]
+      .filter(Boolean)
+      .join("\n");
+    let style = document.getElementById(STYLE_ID);
+    if (!css) {
+      style?.remove();
+      return;
+    }
+    if (!style) {
+      style = document.createElement("style");
+      style.id = STYLE_ID;
+    }
+    if (style.textContent !== css) style.textContent = css;
+    // Attach to <html> so SPAs that rebuild <head> don't drop it
+    if (style.parentNode !== document.documentElement) {
+      document.documentElement?.appendChild(style);
+    }
+  }
+
+  window.pakeSetAccessibility = (next) => {
+    Object.assign(modes, next);
+    applyStyle();
+  };
+
+  new MutationObserver(() => {
+    if (modes.grayscale || modes.reduced_motion) applyStyle();
+  }).observe(document, { childList: true, subtree: true });
+
+  document.addEventListener("DOMContentLoaded", () => {
+    const invoke = window.__TAURI__?.core.invoke;
+    if (!invoke) return;
+    invoke("get_accessibility_modes")
+      .then(window.pakeSetAccessibility)
+      .catch(() => {});
+
+    const shortcuts = [
+      ["grayscale", "set_grayscale", window.pakeConfig?.grayscale_shortcut],
+      [
+        "reduced_motion",
+        "set_reduced_motion",
+        window.pakeConfig?.reduced_motion_shortcut,
+      ],
+    ].filter(([, , accelerator]) => accelerator);
+    if (!shortcuts.length) return;
+
+    document.addEventListener("keydown", (event) => {
+      const match = shortcuts.find(([, , accelerator]) =>
+        matchesAccelerator(event, accelerator),
+      );
+      if (!match) return;
+      event.preventDefault();
+      const [mode, command] = match;
+      invoke(command, { enabled: !modes[mode] }).catch((error) =>
+        console.error("[Pake] Failed to toggle accessibility mode:", error),
+      );
+    });
+  });
+})();
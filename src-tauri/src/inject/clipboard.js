@@ -0,0 +1,49 @@
+// navigator.clipboard per pakeConfig.clipboard_access (allow | deny | prompt).
+// When the webview refuses text access, fall back to the native clipboard
+// commands, which check the policy and the page's host again.
+(function () {
+  const policy = window.pakeConfig?.clipboard_access || "prompt";
+  const notAllowed = () =>
+    new DOMException("Clipboard access denied", "NotAllowedError");
+  const invoke = (command, args) =>
+    window.__TAURI__?.core.invoke(command, args) ??
+    Promise.reject(notAllowed());
+
+  const clipboard = navigator.clipboard;
+  if (policy === "deny") {
+    if (!clipboard) return;
+    for (const method of ["read", "readText", "write", "writeText"]) {
+      clipboard[method] = () => Promise.reject(notAllowed());
+    }
+    return;
+  }
+
+  const readText = clipboard?.readText?.bind(clipboard);
+  const writeText = clipboard?.writeText?.bind(clipboard);
+  const bridge = {
+    readText: () =>
+      (readText ? readText() : Promise.reject(notAllowed())).catch(() =>
+        invoke("clipboard_read_text").catch((error) => {
+          throw new DOMException(String(error), "NotAllowedError");
+        }),
+      ),
+    writeText: (text) =>
+      (writeText ? writeText(text) : Promise.reject(notAllowed())).catch(() =>
+        invoke("clipboard_write_text", { text: String(text) }).catch(
+          (error) => {
+            throw new DOMException(String(error), "NotAllowedError");
+          },
+        ),
+      ),
+  };
+
+  if (clipboard) {
+    Object.assign(clipboard, bridge);
+  } else {
+    // Non-secure contexts have no navigator.clipboard at all
+    Object.defineProperty(navigator, "clipboard", {
+      value: bridge,
+      configurable: true,
+    });
+  }
+})();
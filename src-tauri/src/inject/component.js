@@ -18,6 +18,16 @@ document.addEventListener("DOMContentLoaded", () => {
   }
 
   window.pakeToast = pakeToast;
+
+  // Subtle native feedback for custom scripts: tap, send, receive, success, error
+  window.pakePlayFeedback = (kind) =>
+    window.__TAURI__?.core.invoke("play_feedback", { kind }).catch(() => {});
+
+  // Raise the window above everything for a moment, e.g. for an incoming call
+  window.pakeFlashToFront = (durationMs = 3000) =>
+    window.__TAURI__?.core
+      .invoke("flash_to_front", { durationMs })
+      .catch(() => {});
 });
 
 // Polyfill for HTML5 Fullscreen API in Tauri webview
@@ -0,0 +1,116 @@
+// Content blocking fallback. macOS and Windows block requests natively; this
+// hides matched elements everywhere and blocks what it can from page scripts.
+(function () {
+  let blockPatterns = [];
+  let hideStyle = null;
+  let blockedCount = 0;
+  let reportTimer = null;
+
+  // Batched so a page full of trackers doesn't flood the tray tooltip
+  function countBlocked() {
+    blockedCount += 1;
+    if (reportTimer) return;
+    reportTimer = setTimeout(() => {
+      const count = blockedCount;
+      blockedCount = 0;
+      reportTimer = null;
+      window.__TAURI__?.core
+        .invoke("report_blocked_requests", { count })
+        .catch(() => {});
+    }, 1000);
+  }
+
+  function isBlocked(url) {
+    if (!blockPatterns.length || !url) return false;
+    try {
+      const absoluteUrl = new URL(url, window.location.href).href;
+      return blockPatterns.some((pattern) => pattern.test(absoluteUrl));
+    } catch (e) {
+      return false;
+    }
+  }
+
+  function matchesDomain(domains) {
+    if (!domains || !domains.length) return true;
+    const host = window.location.hostname;
+    return domains.some((domain) => {
+      const clean = domain.replace(/^\*/, "");
+      return host === clean || host.endsWith(`.${clean}`);
+    });
+  }
+
+  function applyRules(rules) {
+    const selectors = [];
+    blockPatterns = [];
+
+    for (const rule of rules || []) {
+      if (!matchesDomain(rule.trigger["if-domain"])) continue;
+      if (rule.action.type === "block") {
+        try {
+          blockPatterns.push(new RegExp(rule.trigger["url-filter"]));
+        } catch (e) {
+          console.warn("[Pake] Invalid content rule:", rule, e);
+        }
+      } else if (rule.action.type === "css-display-none") {
+        selectors.push(rule.action.selector);
+      }
+    }
+
+    hideStyle?.remove();
+    hideStyle = null;
+    if (selectors.length) {
+      hideStyle = document.createElement("style");
+      // One rule per selector so an invalid selector doesn't drop the rest
+      hideStyle.textContent = selectors
+        .map((selector) => `${selector} { display: none !important; }`)
+        .join("\n");
+      (document.head || document.documentElement).appendChild(hideStyle);
+    }
+  }
+
+  const originalFetch = window.fetch;
+  window.fetch = function (input, init) {
+    const url = typeof input === "string" ? input : input?.url;
+    if (isBlocked(url)) {
+      countBlocked();
+      return Promise.reject(new TypeError("Blocked by Pake content rules"));
+    }
+    return originalFetch.call(this, input, init);
+  };
+
+  const originalOpen = XMLHttpRequest.prototype.open;
+  const originalSend = XMLHttpRequest.prototype.send;
+  XMLHttpRequest.prototype.open = function (method, url, ...rest) {
+    this.pakeBlocked = isBlocked(String(url));
+    return originalOpen.call(this, method, url, ...rest);
+  };
+  XMLHttpRequest.prototype.send = function (body) {
+    if (this.pakeBlocked) {
+      countBlocked();
+      setTimeout(() => this.dispatchEvent(new ProgressEvent("error")), 0);
+      return;
+    }
+    return originalSend.call(this, body);
+  };
+
+  new MutationObserver((mutations) => {
+    if (!blockPatterns.length) return;
+    for (const mutation of mutations) {
+      mutation.addedNodes.forEach((node) => {
+        if (node.nodeType === Node.ELEMENT_NODE && isBlocked(node.src)) {
+          node.remove();
+          countBlocked();
+        }
+      });
+    }
+  }).observe(document, { childList: true, subtree: true });
+
+  window.pakeSetContentRules = applyRules;
+
+  document.addEventListener("DOMContentLoaded", () => {
+    window.__TAURI__?.core
+      .invoke("get_content_rules")
+      .then(applyRules)
+      .catch(() => {});
+  });
+})();
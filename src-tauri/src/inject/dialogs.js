@@ -0,0 +1,92 @@
+// Overrides alert/confirm/prompt for `js_dialogs: custom` and `suppress`.
+// `custom` leaves confirm alone: callers read its answer synchronously, and an
+// override can only hand back a Promise, which is always truthy.
+(function () {
+  const mode = window.pakeConfig?.js_dialogs ?? "native";
+  if (mode === "native") return;
+
+  if (mode === "suppress") {
+    window.alert = () => {};
+    window.confirm = () => false;
+    window.prompt = () => null;
+    return;
+  }
+
+  function showNative(kind, message) {
+    const invoke = window.__TAURI__?.core.invoke;
+    if (!invoke) return Promise.resolve(false);
+    return invoke("show_js_dialog", {
+      kind,
+      message: String(message ?? ""),
+    }).catch((error) => {
+      console.error("[Pake] Failed to show dialog:", error);
+      return false;
+    });
+  }
+
+  const PROMPT_STYLE = `
+    :host { all: initial; }
+    .backdrop {
+      position: fixed; inset: 0; z-index: 2147483647;
+      display: flex; align-items: center; justify-content: center;
+      background: rgba(0, 0, 0, 0.3); color-scheme: light dark;
+      font: 13px -apple-system, BlinkMacSystemFont, "Segoe UI", Roboto, sans-serif;
+    }
+    form {
+      width: min(420px, calc(100vw - 48px)); padding: 20px;
+      border-radius: 10px; background: Canvas; color: CanvasText;
+      box-shadow: 0 12px 40px rgba(0, 0, 0, 0.3);
+    }
+    p { margin: 0 0 12px; white-space: pre-wrap; word-break: break-word; }
+    input { box-sizing: border-box; width: 100%; padding: 6px 8px; font: inherit; }
+    .buttons { display: flex; justify-content: flex-end; gap: 8px; margin-top: 16px; }
+    button { min-width: 72px; padding: 5px 12px; font: inherit; }
+  `;
+
+  // No native dialog takes text input, so prompt is drawn in the page
+  function showPrompt(message, defaultValue) {
+    return new Promise((resolve) => {
+      const host = document.createElement("div");
+      const root = host.attachShadow({ mode: "closed" });
+      root.innerHTML = `<style>${PROMPT_STYLE}</style>
+        <div class="backdrop">
+          <form>
+            <p></p>
+            <input type="text" />
+            <div class="buttons">
+              <button type="button" class="cancel">Cancel</button>
+              <button type="submit">OK</button>
+            </div>
+          </form>
+        </div>`;
+      const input = root.querySelector("input");
+      root.querySelector("p").textContent = message;
+      input.value = defaultValue;
+
+      const finish = (value) => {
+        host.remove();
+        resolve(value);
+      };
+      root.querySelector("form").addEventListener("submit", (event) => {
+        event.preventDefault();
+        finish(input.value);
+      });
+      root.querySelector(".cancel").addEventListener("click", () => {
+        finish(null);
+      });
+      input.addEventListener("keydown", (event) => {
+        if (event.key === "Escape") finish(null);
+      });
+
+      (document.body ?? document.documentElement).appendChild(host);
+      input.focus();
+      input.select();
+    });
+  }
+
+  window.alert = (message) => {
+    showNative("alert", message);
+  };
+  window.prompt = (message, defaultValue) =>
+    showPrompt(String(message ?? ""), String(defaultValue ?? ""));
+})();
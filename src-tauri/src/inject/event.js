@@ -1,24 +1,90 @@
-const shortcuts = {
-  "[": () => window.history.back(),
-  "]": () => window.history.forward(),
-  "-": () => zoomOut(),
-  "=": () => zoomIn(),
-  "+": () => zoomIn(),
-  0: () => setZoom("100%"),
-  r: () => window.location.reload(),
-  ArrowUp: () => scrollTo(0, 0),
-  ArrowDown: () => scrollTo(0, document.body.scrollHeight),
+// In-page actions, bound to the accelerators in window.pakeShortcuts
+const shortcutActions = {
+  back: () => window.history.back(),
+  forward: () => window.history.forward(),
+  zoom_out: () => zoomOut(),
+  zoom_in: () => zoomIn(),
+  zoom_reset: () => setZoom("100%"),
+  reload: () => window.location.reload(),
+  reader_mode: () => toggleReaderMode(),
+  scroll_top: () => scrollTo(0, 0),
+  scroll_bottom: () => scrollTo(0, document.body.scrollHeight),
 };
 
+// beforeunload handling, see pakeConfig.beforeunload ("allow" | "suppress" | "confirm")
+(function () {
+  const mode = window.pakeConfig?.beforeunload || "allow";
+  if (mode === "allow") return;
+
+  const handlers = new Set();
+  let propertyHandler = null;
+  const originalAddEventListener = window.addEventListener;
+  const originalRemoveEventListener = window.removeEventListener;
+
+  window.addEventListener = function (type, listener, options) {
+    if (type === "beforeunload") {
+      handlers.add(listener);
+      return;
+    }
+    return originalAddEventListener.call(this, type, listener, options);
+  };
+
+  window.removeEventListener = function (type, listener, options) {
+    if (type === "beforeunload") {
+      handlers.delete(listener);
+      return;
+    }
+    return originalRemoveEventListener.call(this, type, listener, options);
+  };
+
+  Object.defineProperty(window, "onbeforeunload", {
+    configurable: true,
+    get: () => propertyHandler,
+    set: (handler) => {
+      propertyHandler = typeof handler === "function" ? handler : null;
+    },
+  });
+
+  // Run the page's handlers against a synthetic event to see if it wants a prompt
+  window.pakeShouldConfirmUnload = () => {
+    if (mode !== "confirm") return false;
+
+    let requested = false;
+    const event = new Event("beforeunload", { cancelable: true });
+    Object.defineProperty(event, "returnValue", {
+      get: () => requested,
+      set: (value) => {
+        if (value) requested = true;
+      },
+    });
+
+    for (const handler of [...handlers, propertyHandler]) {
+      try {
+        const result =
+          typeof handler === "function"
+            ? handler.call(window, event)
+            : handler?.handleEvent?.(event);
+        if (result) requested = true;
+      } catch (e) {
+        console.error("[Pake] beforeunload handler failed:", e);
+      }
+    }
+    return requested || event.defaultPrevented;
+  };
+})();
+
+// Zoom is applied natively and stored per window label on the Rust side
 function setZoom(zoom) {
-  const html = document.getElementsByTagName("html")[0];
-  html.style.zoom = zoom;
-  window.localStorage.setItem("htmlZoom", zoom);
+  window.__TAURI__?.core
+    .invoke("set_zoom", { zoom: parseInt(zoom) })
+    .catch(() => {});
 }
 
 function zoomCommon(zoomChange) {
-  const currentZoom = window.localStorage.getItem("htmlZoom") || "100%";
-  setZoom(zoomChange(currentZoom));
+  window.__TAURI__?.core
+    .invoke("get_zoom")
+    .then((currentZoom) => setZoom(zoomChange(`${currentZoom}%`)))
+    .catch(() => {});
 }
 
 function zoomIn() {
@@ -39,10 +105,114 @@ function triggerPasteAsPlainText() {
   }, 100);
 }
 
+// Deliver a clipboard image from Rust as a synthetic paste, falling back to a drop
+function pakePasteImage(base64) {
+  const bytes = Uint8Array.from(atob(base64), (c) => c.charCodeAt(0));
+  const file = new File([bytes], `clipboard-${Date.now()}.png`, {
+    type: "image/png",
+  });
+  const dataTransfer = new DataTransfer();
+  dataTransfer.items.add(file);
+
+  const target = document.activeElement || document.body;
+  const pasteEvent = new ClipboardEvent("paste", {
+    clipboardData: dataTransfer,
+    bubbles: true,
+    cancelable: true,
+  });
+  target.dispatchEvent(pasteEvent);
+
+  if (!pasteEvent.defaultPrevented) {
+    target.dispatchEvent(
+      new DragEvent("drop", {
+        dataTransfer,
+        bubbles: true,
+        cancelable: true,
+      }),
+    );
+  }
+}
+
+// Rebuild files dropped on the native window and replay the drop on the element under the cursor
+function pakeDropFiles(files, x, y) {
+  const dataTransfer = new DataTransfer();
+  files.forEach(({ name, data }) => {
+    const bytes = Uint8Array.from(atob(data), (c) => c.charCodeAt(0));
+    const extension = name.includes(".")
+      ? name.split(".").pop().toLowerCase()
+      : "";
+    dataTransfer.items.add(
+      new File([bytes], name, {
+        type: INLINE_CONTENT_TYPE_BY_EXTENSION[extension] || "",
+      }),
+    );
+  });
+
+  const target = document.elementFromPoint(x, y) || document.body;
+  if (target instanceof HTMLInputElement && target.type === "file") {
+    target.files = dataTransfer.files;
+    target.dispatchEvent(new Event("change", { bubbles: true }));
+    return;
+  }
+  ["dragenter", "dragover", "drop"].forEach((type) => {
+    target.dispatchEvent(
+      new DragEvent(type, {
+        dataTransfer,
+        clientX: x,
+        clientY: y,
+        bubbles: true,
+        cancelable: true,
+      }),
+    );
+  });
+}
+
+function isEditableTarget(target) {
+  return (
+    target?.isContentEditable ||
+    ["INPUT", "TEXTAREA", "SELECT"].includes(target?.tagName)
+  );
+}
+
+// Match Tauri-style accelerators like "CmdOrCtrl+Shift+K" against a keydown
+function matchesAccelerator(event, accelerator) {
+  const parts = accelerator.toLowerCase().split("+");
+  const key = parts.pop();
+  const isMac = /macintosh|mac os x/i.test(navigator.userAgent);
+  const wanted = { ctrl: false, meta: false, alt: false, shift: false };
+  parts.forEach((part) => {
+    if (part === "cmdorctrl" || part === "commandorcontrol") {
+      wanted[isMac ? "meta" : "ctrl"] = true;
+    } else if (["cmd", "command", "meta", "super"].includes(part)) {
+      wanted.meta = true;
+    } else if (part === "ctrl" || part === "control") {
+      wanted.ctrl = true;
+    } else if (part === "alt" || part === "option") {
+      wanted.alt = true;
+    } else if (part === "shift") {
+      wanted.shift = true;
+    }
+  });
+
+  const pressed = event.key.toLowerCase();
+  const code = event.code.toLowerCase();
+  return (
+    event.ctrlKey === wanted.ctrl &&
+    event.metaKey === wanted.meta &&
+    event.altKey === wanted.alt &&
+    event.shiftKey === wanted.shift &&
+    (pressed === key || code === `key${key}` || code === `digit${key}`)
+  );
+}
+
 function handleShortcut(event) {
-  if (shortcuts[event.key]) {
+  const shortcuts = window.pakeShortcuts || {};
+  const action = Object.keys(shortcutActions).find(
+    (name) => shortcuts[name] && matchesAccelerator(event, shortcuts[name]),
+  );
+  if (action) {
     event.preventDefault();
-    shortcuts[event.key]();
+    shortcutActions[action]();
   }
 }
 
@@ -216,6 +386,39 @@ function isPreviewableMedia(url) {
   return PREVIEWABLE_MEDIA_EXTENSIONS.includes(extension);
 }
 
+const INLINE_CONTENT_TYPE_BY_EXTENSION = {
+  pdf: "application/pdf",
+  png: "image/png",
+  jpg: "image/jpeg",
+  jpeg: "image/jpeg",
+  gif: "image/gif",
+  webp: "image/webp",
+  svg: "image/svg+xml",
+  bmp: "image/bmp",
+  avif: "image/avif",
+};
+
+function guessContentType(url) {
+  if (url.startsWith("data:")) {
+    return url.substring(5, url.search(/[;,]/));
+  }
+  return INLINE_CONTENT_TYPE_BY_EXTENSION[getExtension(url)] || "";
+}
+
+// Match against pakeConfig.inline_content_types, supporting "image/*" wildcards
+function shouldOpenInline(contentType) {
+  const patterns = window.pakeConfig?.inline_content_types || [];
+  const type = (contentType || "").split(";")[0].trim().toLowerCase();
+  if (!type) return false;
+
+  return patterns.some((pattern) => {
+    const normalized = pattern.trim().toLowerCase();
+    return normalized.endsWith("/*")
+      ? type.startsWith(normalized.slice(0, -1))
+      : type === normalized;
+  });
+}
+
 // Unified file detection - replaces both isDownloadLink and isFileLink
 function isDownloadableFile(url) {
   try {
@@ -276,14 +479,46 @@ document.addEventListener("DOMContentLoaded", () => {
     });
   });
 
+  if (window.pakeConfig?.scroll_shortcuts) {
+    document.addEventListener("keydown", (event) => {
+      if (event.defaultPrevented || event.ctrlKey || event.metaKey) return;
+      if (event.altKey || isEditableTarget(event.target)) return;
+
+      const page = window.innerHeight * 0.9;
+      const actions = {
+        " ": () => window.scrollBy({ top: event.shiftKey ? -page : page }),
+        Home: () => window.scrollTo({ top: 0 }),
+        End: () =>
+          window.scrollTo({ top: document.documentElement.scrollHeight }),
+      };
+      const action = actions[event.key];
+      if (action) {
+        event.preventDefault();
+        action();
+      }
+    });
+  }
+
+  const namedActions = Object.entries(window.pakeConfig?.named_actions || {});
+  if (namedActions.some(([, action]) => action.shortcut)) {
+    document.addEventListener("keydown", (event) => {
+      const match = namedActions.find(
+        ([, action]) =>
+          action.shortcut && matchesAccelerator(event, action.shortcut),
+      );
+      if (!match) return;
+      event.preventDefault();
+      invoke("run_named_action", { name: match[0] }).catch((error) =>
+        console.error("[Pake] Action failed:", error),
+      );
+    });
+  }
+
   if (window["pakeConfig"]?.disabled_web_shortcuts !== true) {
     document.addEventListener("keyup", (event) => {
-      if (/windows|linux/i.test(navigator.userAgent) && event.ctrlKey) {
-        handleShortcut(event);
-      }
-      if (/macintosh|mac os x/i.test(navigator.userAgent) && event.metaKey) {
-        handleShortcut(event);
-      }
+      const modified = event.ctrlKey || event.metaKey || event.altKey;
+      if (!modified && isEditableTarget(event.target)) return;
+      handleShortcut(event);
     });
   }
 
@@ -379,6 +614,19 @@ document.addEventListener("DOMContentLoaded", () => {
       });
   }
 
+  function openInline(url) {
+    invoke("open_inline_content", { url }).catch((error) => {
+      console.error("Failed to open inline content:", url, error);
+    });
+  }
+
+  // Blob URLs are bound to this document, so hand the viewer a data URL instead
+  function openBlobInline(blobUrl) {
+    const reader = new FileReader();
+    reader.onload = () => openInline(reader.result);
+    reader.readAsDataURL(window.blobToUrlCaches.get(blobUrl));
+  }
+
   // detect blob download by createElement("a")
   function detectDownloadByCreateAnchor() {
     const createEle = document.createElement;
@@ -395,11 +643,19 @@ document.addEventListener("DOMContentLoaded", () => {
           if (window.blobToUrlCaches.has(url)) {
             e.preventDefault();
             e.stopImmediatePropagation();
+            if (shouldOpenInline(window.blobToUrlCaches.get(url).type)) {
+              openBlobInline(url);
+              return;
+            }
             downloadFromBlobUrl(url, filename);
             // case: download from dataURL -> convert dataURL ->
           } else if (url.startsWith("data:")) {
             e.preventDefault();
             e.stopImmediatePropagation();
+            if (shouldOpenInline(guessContentType(url))) {
+              openInline(url);
+              return;
+            }
             downloadFromDataUri(url, filename);
           }
         },
@@ -518,6 +774,14 @@ document.addEventListener("DOMContentLoaded", () => {
       ) {
         e.preventDefault();
         e.stopImmediatePropagation();
+        // The viewer only accepts files from this page's own origin
+        if (
+          new URL(absoluteUrl).origin === window.location.origin &&
+          shouldOpenInline(guessContentType(absoluteUrl))
+        ) {
+          openInline(absoluteUrl);
+          return;
+        }
         const userLanguage = getUserLanguage();
         invoke("download_file", {
           params: { url: absoluteUrl, filename, language: userLanguage },
@@ -555,6 +819,98 @@ document.addEventListener("DOMContentLoaded", () => {
   // Prevent some special websites from executing in advance, before the click event is triggered.
   document.addEventListener("click", detectAnchorElementClick, true);
 
+  // Submit without firing the submit event again, carrying over the submitter's
+  // name/value and its formaction/formmethod/formenctype overrides
+  function submitForm(form, submitter, target) {
+    const overrides = [["target", target]];
+    for (const name of ["action", "method", "enctype"]) {
+      const value = submitter?.getAttribute(`form${name}`);
+      if (value) overrides.push([name, value]);
+    }
+    const previous = overrides.map(([name]) => [name, form.getAttribute(name)]);
+    let submitterField = null;
+    if (submitter?.name) {
+      submitterField = document.createElement("input");
+      submitterField.type = "hidden";
+      submitterField.name = submitter.name;
+      submitterField.value = submitter.value;
+      form.appendChild(submitterField);
+    }
+
+    overrides.forEach(([name, value]) => form.setAttribute(name, value));
+    HTMLFormElement.prototype.submit.call(form);
+    previous.forEach(([name, value]) =>
+      value === null ? form.removeAttribute(name) : form.setAttribute(name, value),
+    );
+    submitterField?.remove();
+  }
+
+  // Forms targeting a new window follow the same rules as _blank links
+  const detectFormSubmit = (e) => {
+    const form = e.target;
+    if (e.defaultPrevented || !(form instanceof HTMLFormElement)) return;
+    const submitter = e.submitter;
+    // Attributes rather than properties, which inputs named "action" etc. shadow
+    const target =
+      submitter?.getAttribute("formtarget") || form.getAttribute("target");
+    if (target !== "_blank" && target !== "_new") return;
+
+    let actionUrl;
+    try {
+      actionUrl = new URL(
+        submitter?.getAttribute("formaction") ||
+          form.getAttribute("action") ||
+          window.location.href,
+        window.location.href,
+      );
+    } catch (error) {
+      console.warn("[Pake] Ignoring form with invalid action:", error);
+      return;
+    }
+    // Other schemes are left to the navigation handler
+    if (!["http:", "https:"].includes(actionUrl.protocol)) return;
+    if (window.isAuthLink(actionUrl.href)) return;
+
+    e.preventDefault();
+    if (forceInternalNavigation || isSameDomain(actionUrl.href)) {
+      submitForm(form, submitter, "_self");
+      return;
+    }
+
+    const method = (
+      submitter?.getAttribute("formmethod") ||
+      form.getAttribute("method") ||
+      "get"
+    ).toLowerCase();
+    const specs = "width=1200,height=800,scrollbars=yes,resizable=yes";
+    if (method === "get") {
+      actionUrl.search = new URLSearchParams(
+        new FormData(form, submitter),
+      ).toString();
+      const newWindow = originalWindowOpen.call(
+        window,
+        actionUrl.href,
+        "_blank",
+        specs,
+      );
+      if (!newWindow) handleExternalLink(actionUrl.href);
+      return;
+    }
+
+    // A POST body can't be handed to the browser, so it needs a window of ours
+    const name = `pake-form-${Date.now()}`;
+    const newWindow = originalWindowOpen.call(
+      window,
+      "about:blank",
+      name,
+      specs,
+    );
+    submitForm(form, submitter, newWindow ? name : "_self");
+  };
+
+  // Bubble phase, so sites that handle the submit themselves have already said so
+  window.addEventListener("submit", detectFormSubmit);
+
   collectUrlToBlobs();
   detectDownloadByCreateAnchor();
 
@@ -593,6 +949,24 @@ document.addEventListener("DOMContentLoaded", () => {
     console.log(e);
   }
 
+  // Keep media started after a set_muted call silent too
+  window.__TAURI__?.core
+    .invoke("get_muted")
+    .then((muted) => {
+      window.pakeMuted = muted;
+    })
+    .catch(() => {});
+  document.addEventListener(
+    "play",
+    (e) => {
+      if (window.pakeMuted && !e.target.muted) {
+        e.target.muted = true;
+        e.target.pakeMuted = true;
+      }
+    },
+    true,
+  );
+
   // Fix Chinese input method "Enter" on Safari
   document.addEventListener(
     "keydown",
@@ -612,6 +986,9 @@ document.addEventListener("DOMContentLoaded", () => {
     downloadFile: isChinese ? "下载文件" : "Download File",
     copyAddress: isChinese ? "复制地址" : "Copy Address",
     openInBrowser: isChinese ? "浏览器打开" : "Open in Browser",
+    copy: isChinese ? "复制" : "Copy",
+    lookUp:
+      window.pakeConfig?.lookup_label || (isChinese ? "查询" : "Look Up"),
   };
 
   // Menu theme configuration
@@ -891,6 +1268,19 @@ document.addEventListener("DOMContentLoaded", () => {
           ),
         );
         break;
+
+      case "selection":
+        items.push(
+          createMenuItem(menuTexts.copy, () =>
+            navigator.clipboard.writeText(data.text),
+          ),
+          createMenuItem(menuTexts.lookUp, () =>
+            invoke("lookup_selection", { text: data.text }).catch((error) =>
+              console.error("Failed to look up selection:", error),
+            ),
+          ),
+        );
+        break;
     }
 
     return items;
@@ -912,8 +1302,12 @@ document.addEventListener("DOMContentLoaded", () => {
           : null;
       const isLink = linkElement && linkElement.href && !mediaInfo.isMedia;
 
-      // Only show custom menu for media or links
-      if (mediaInfo.isMedia || isLink) {
+      const selection = window.pakeConfig?.lookup_url_template
+        ? window.getSelection()?.toString().trim()
+        : "";
+
+      // Only show custom menu for media, links or text to look up
+      if (mediaInfo.isMedia || isLink || selection) {
         event.preventDefault();
         event.stopPropagation();
 
@@ -927,6 +1321,8 @@ document.addEventListener("DOMContentLoaded", () => {
             url: linkUrl,
             isFile: isDownloadableFile(linkUrl),
           });
+        } else {
+          menuItems = buildMenuItems("selection", { text: selection });
         }
 
         showContextMenu(event.clientX, event.clientY, menuItems);
@@ -946,8 +1342,15 @@ document.addEventListener("DOMContentLoaded", () => {
 });
 
 document.addEventListener("DOMContentLoaded", function () {
-  let permVal = "granted";
+  const policy = window.pakeConfig?.permissions?.notifications || "allow";
+  const PERMISSION_BY_POLICY = {
+    allow: "granted",
+    deny: "denied",
+    prompt: "default",
+  };
+  let permVal = PERMISSION_BY_POLICY[policy] || "granted";
   window.Notification = function (title, options) {
+    if (permVal !== "granted") return;
     const { invoke } = window.__TAURI__.core;
     const body = options?.body || "";
     let icon = options?.icon || "";
@@ -964,9 +1367,33 @@ document.addEventListener("DOMContentLoaded", function () {
         icon,
       },
     });
+
+    if (!document.hasFocus()) {
+      invoke("request_attention", { critical: false }).catch(() => {});
+    }
   };
 
-  window.Notification.requestPermission = async () => "granted";
+  // Asked through a native dialog: window.confirm may be restyled or suppressed
+  // by js_dialogs, and a blocking confirm would freeze the page meanwhile
+  let permissionRequest = null;
+  window.Notification.requestPermission = async () => {
+    if (permVal !== "default") return permVal;
+    permissionRequest ??= window.__TAURI__.core
+      .invoke("show_js_dialog", {
+        kind: "confirm",
+        message: `Allow ${location.host} to show notifications?`,
+      })
+      .catch((error) => {
+        console.error("[Pake] Failed to ask for notifications:", error);
+        return false;
+      })
+      .then((allowed) => {
+        permVal = allowed ? "granted" : "denied";
+        permissionRequest = null;
+        return permVal;
+      });
+    return permissionRequest;
+  };
 
   Object.defineProperty(window.Notification, "permission", {
     enumerable: true,
@@ -977,12 +1404,13 @@ document.addEventListener("DOMContentLoaded", function () {
   });
 });
 
+// Rust restores each window's zoom when it is built; this only migrates
+// the zoom older versions kept in the (shared) localStorage.
 function setDefaultZoom() {
   const htmlZoom = window.localStorage.getItem("htmlZoom");
   if (htmlZoom) {
+    window.localStorage.removeItem("htmlZoom");
     setZoom(htmlZoom);
-  } else if (window.pakeConfig?.zoom && window.pakeConfig.zoom !== 100) {
-    setZoom(`${window.pakeConfig.zoom}%`);
   }
 }
 
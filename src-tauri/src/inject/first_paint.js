@@ -0,0 +1,33 @@
+// Tells the app the page has painted so a window with show_on_first_paint can appear
+(function () {
+  if (window.self !== window.top || !window.pakeConfig?.show_on_first_paint) {
+    return;
+  }
+
+  let reported = false;
+  function report() {
+    if (reported) return;
+    reported = true;
+    window.__TAURI__?.core.invoke("report_first_paint").catch(() => {});
+  }
+
+  // WebView2 and recent WebKit expose paint timing
+  if (PerformanceObserver.supportedEntryTypes?.includes("paint")) {
+    new PerformanceObserver((list) => {
+      const entries = list.getEntries();
+      if (entries.some((entry) => entry.name === "first-contentful-paint")) {
+        report();
+      }
+    }).observe({ type: "paint", buffered: true });
+    return;
+  }
+
+  // Older WebKitGTK: two frames after parsing, the first layout is on screen
+  const afterFrames = () =>
+    requestAnimationFrame(() => requestAnimationFrame(report));
+  if (document.readyState === "loading") {
+    document.addEventListener("DOMContentLoaded", afterFrames, { once: true });
+  } else {
+    afterFrames();
+  }
+})();
@@ -0,0 +1,135 @@
+// Records the main document plus fetch/XHR traffic for `export_har` when
+// `har_capture` is enabled. Only what page scripts can observe is captured.
+(function () {
+  if (!window.pakeConfig?.har_capture) return;
+
+  function report(entry) {
+    window.__TAURI__?.core
+      .invoke("record_har_entry", { entry })
+      .catch(() => {});
+  }
+
+  function bodySize(body) {
+    if (body == null) return 0;
+    if (typeof body === "string") return new Blob([body]).size;
+    if (body instanceof Blob) return body.size;
+    if (body instanceof ArrayBuffer) return body.byteLength;
+    if (ArrayBuffer.isView(body)) return body.byteLength;
+    return -1;
+  }
+
+  function contentLength(headers) {
+    const length = Number(headers.get("content-length"));
+    return Number.isFinite(length) && length >= 0 ? length : -1;
+  }
+
+  function absolute(url) {
+    try {
+      return new URL(url, window.location.href).href;
+    } catch (e) {
+      return String(url);
+    }
+  }
+
+  // Tauri IPC goes through fetch too; recording it would loop forever
+  function isIpc(url) {
+    return url.startsWith("ipc:") || url.startsWith("http://ipc.localhost");
+  }
+
+  const originalFetch = window.fetch;
+  window.fetch = function (input, init) {
+    const request = input instanceof Request ? input : null;
+    const url = absolute(request ? request.url : input);
+    if (isIpc(url)) return originalFetch.call(this, input, init);
+
+    const started = new Date();
+    const start = performance.now();
+    const entry = {
+      startedDateTime: started.toISOString(),
+      method: (init?.method || request?.method || "GET").toUpperCase(),
+      url,
+      resourceType: "fetch",
+      requestBodySize: bodySize(init?.body),
+    };
+
+    return originalFetch.call(this, input, init).then(
+      (response) => {
+        entry.time = performance.now() - start;
+        entry.status = response.status;
+        entry.statusText = response.statusText;
+        entry.mimeType = response.headers.get("content-type") || "";
+        entry.responseHeaders = [...response.headers.entries()];
+        entry.responseBodySize = contentLength(response.headers);
+        report(entry);
+        return response;
+      },
+      (error) => {
+        entry.time = performance.now() - start;
+        entry.status = 0;
+        entry.statusText = String(error?.message || error);
+        report(entry);
+        throw error;
+      },
+    );
+  };
+
+  const originalOpen = XMLHttpRequest.prototype.open;
+  const originalSend = XMLHttpRequest.prototype.send;
+  XMLHttpRequest.prototype.open = function (method, url, ...rest) {
+    this.pakeHar = {
+      method: String(method).toUpperCase(),
+      url: absolute(url),
+      resourceType: "xhr",
+    };
+    return originalOpen.call(this, method, url, ...rest);
+  };
+  XMLHttpRequest.prototype.send = function (body) {
+    const entry = this.pakeHar;
+    if (entry) {
+      const start = performance.now();
+      entry.startedDateTime = new Date().toISOString();
+      entry.requestBodySize = bodySize(body);
+      this.addEventListener("loadend", () => {
+        entry.time = performance.now() - start;
+        entry.status = this.status;
+        entry.statusText = this.statusText;
+        entry.mimeType = this.getResponseHeader("content-type") || "";
+        entry.responseHeaders = this.getAllResponseHeaders()
+          .trim()
+          .split(/[\r\n]+/)
+          .filter(Boolean)
+          .map((line) => {
+            const index = line.indexOf(":");
+            return [line.slice(0, index).trim(), line.slice(index + 1).trim()];
+          });
+        const length = this.getResponseHeader("content-length");
+        entry.responseBodySize = length ? Number(length) : -1;
+        report(entry);
+      });
+    }
+    return originalSend.call(this, body);
+  };
+
+  window.addEventListener("load", () => {
+    const [navigation] = performance.getEntriesByType("navigation");
+    if (!navigation) return;
+    report({
+      startedDateTime: new Date(
+        performance.timeOrigin + navigation.startTime,
+      ).toISOString(),
+      time: navigation.duration,
+      method: "GET",
+      url: window.location.href,
+      status: navigation.responseStatus || 200,
+      mimeType: document.contentType,
+      resourceType: "document",
+      requestBodySize: 0,
+      responseBodySize: navigation.transferSize || -1,
+      timings: {
+        send: Math.max(0, navigation.requestStart - navigation.connectEnd),
+        wait: Math.max(0, navigation.responseStart - navigation.requestStart),
+        receive: Math.max(0, navigation.responseEnd - navigation.responseStart),
+      },
+    });
+  });
+})();
@@ -0,0 +1,25 @@
+// Reports Navigation Timing for each document load, see `get_last_load_timing`
+(function () {
+  if (window.self !== window.top) return;
+
+  const round = (value) => Math.max(0, Math.round(value * 10) / 10);
+
+  function report() {
+    const [entry] = performance.getEntriesByType?.("navigation") ?? [];
+    if (!entry) return;
+    const timing = {
+      url: entry.name || window.location.href,
+      dns_ms: round(entry.domainLookupEnd - entry.domainLookupStart),
+      connect_ms: round(entry.connectEnd - entry.connectStart),
+      ttfb_ms: round(entry.responseStart - entry.requestStart),
+      dom_content_loaded_ms: round(entry.domContentLoadedEventEnd),
+      load_ms: round(entry.loadEventEnd),
+    };
+    window.__TAURI__?.core
+      .invoke("report_load_timing", { timing })
+      .catch(() => {});
+  }
+
+  // loadEventEnd is only filled in once the load handlers have returned
+  window.addEventListener("load", () => setTimeout(report, 0));
+})();
@@ -0,0 +1,111 @@
+// Playback bridge for the media commands, tray items and OS media keys
+(function () {
+  if (window.self !== window.top) return;
+
+  const session = navigator.mediaSession;
+  const handlers = new Map();
+
+  // Keep a copy of the site's Media Session handlers so they can be called directly
+  if (session?.setActionHandler) {
+    const setActionHandler = session.setActionHandler.bind(session);
+    session.setActionHandler = (action, handler) => {
+      if (handler) handlers.set(action, handler);
+      else handlers.delete(action);
+      return setActionHandler(action, handler);
+    };
+  }
+
+  const mediaElements = () =>
+    Array.from(document.querySelectorAll("audio, video"));
+
+  function isPlaying() {
+    if (session?.playbackState === "playing") return true;
+    if (session?.playbackState === "paused") return false;
+    return mediaElements().some((media) => !media.paused && !media.ended);
+  }
+
+  function callHandler(action) {
+    const handler = handlers.get(action);
+    if (!handler) return false;
+    handler({ action });
+    return true;
+  }
+
+  // Sites listening for the hardware keys themselves still get a chance
+  function pressKey(key) {
+    for (const type of ["keydown", "keyup"]) {
+      document.dispatchEvent(
+        new KeyboardEvent(type, { key, code: key, bubbles: true }),
+      );
+    }
+  }
+
+  function playPause() {
+    const playing = isPlaying();
+    if (callHandler(playing ? "pause" : "play")) return;
+    const elements = mediaElements();
+    const media =
+      elements.find((element) => !element.paused) ??
+      elements.find((element) => element.currentSrc);
+    if (!media) {
+      pressKey("MediaPlayPause");
+    } else if (playing) {
+      media.pause();
+    } else {
+      media.play().catch((error) => {
+        console.warn("[Pake] Failed to resume playback:", error);
+      });
+    }
+  }
+
+  window.pakeMediaAction = (action) => {
+    switch (action) {
+      case "play_pause":
+        playPause();
+        break;
+      case "next_track":
+        if (!callHandler("nexttrack")) pressKey("MediaTrackNext");
+        break;
+      case "previous_track":
+        if (!callHandler("previoustrack")) pressKey("MediaTrackPrevious");
+        break;
+    }
+  };
+
+  let reported = null;
+  function report() {
+    const playing = isPlaying();
+    if (playing === reported) return;
+    reported = playing;
+    window.__TAURI__?.core
+      .invoke("report_media_state", { playing })
+      .catch(() => {});
+  }
+
+  // Media events don't bubble, so listen in the capture phase
+  for (const type of ["play", "playing", "pause", "ended", "emptied"]) {
+    document.addEventListener(type, report, true);
+  }
+
+  const playbackState =
+    window.MediaSession &&
+    Object.getOwnPropertyDescriptor(MediaSession.prototype, "playbackState");
+  if (session && playbackState?.set) {
+    Object.defineProperty(session, "playbackState", {
+      configurable: true,
+      get: () => playbackState.get.call(session),
+      set: (value) => {
+        playbackState.set.call(session, value);
+        report();
+      },
+    });
+  }
+
+  window.addEventListener("pagehide", () => {
+    reported = null;
+    window.__TAURI__?.core
+      .invoke("report_media_state", { playing: false })
+      .catch(() => {});
+  });
+  document.addEventListener("DOMContentLoaded", report);
+})();
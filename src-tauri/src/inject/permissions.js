@@ -0,0 +1,38 @@
+// Refuse capabilities denied in pakeConfig.permissions inside the page.
+// Windows and Linux also deny natively; on macOS this is the only gate.
+(function () {
+  const permissions = window.pakeConfig?.permissions || {};
+  const denied = (name) => permissions[name] === "deny";
+  const notAllowed = () =>
+    new DOMException("Permission denied", "NotAllowedError");
+
+  const mediaDevices = navigator.mediaDevices;
+  if (
+    mediaDevices?.getUserMedia &&
+    (denied("camera") || denied("microphone"))
+  ) {
+    const getUserMedia = mediaDevices.getUserMedia.bind(mediaDevices);
+    mediaDevices.getUserMedia = (constraints = {}) => {
+      if (
+        (constraints.video && denied("camera")) ||
+        (constraints.audio && denied("microphone"))
+      ) {
+        return Promise.reject(notAllowed());
+      }
+      return getUserMedia(constraints);
+    };
+  }
+
+  if (navigator.geolocation && denied("geolocation")) {
+    const refuse = (_success, error) => {
+      error?.({
+        code: 1,
+        message: "User denied Geolocation",
+        PERMISSION_DENIED: 1,
+      });
+      return 0;
+    };
+    navigator.geolocation.getCurrentPosition = refuse;
+    navigator.geolocation.watchPosition = refuse;
+  }
+})();
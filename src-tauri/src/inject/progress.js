@@ -0,0 +1,65 @@
+// Thin reading progress bar along the top edge, toggled by pakeSetReadingProgress
+(function () {
+  if (window.self !== window.top) return;
+
+  const HOST_ID = "pake-reading-progress";
+  let enabled = Boolean(window.pakeConfig?.reading_progress);
+  let host = null;
+  let bar = null;
+  let frame = 0;
+
+  // Shadow root keeps site styles out; the whole thing ignores the pointer
+  function createHost() {
+    const element = document.createElement("div");
+    element.id = HOST_ID;
+    element.style.cssText =
+      "position:fixed;top:0;left:0;right:0;height:3px;" +
+      "pointer-events:none;z-index:2147483647;";
+    const root = element.attachShadow({ mode: "closed" });
+    bar = document.createElement("div");
+    bar.style.cssText =
+      "height:100%;width:100%;transform-origin:0 50%;transform:scaleX(0);" +
+      "background:var(--pake-accent, #3b82f6);transition:transform 80ms linear;";
+    root.appendChild(bar);
+    return element;
+  }
+
+  function update() {
+    frame = 0;
+    if (!bar) return;
+    const root = document.scrollingElement || document.documentElement;
+    const scrollable = root.scrollHeight - root.clientHeight;
+    const progress = scrollable > 0 ? root.scrollTop / scrollable : 0;
+    bar.style.transform = `scaleX(${Math.min(1, Math.max(0, progress))})`;
+  }
+
+  function scheduleUpdate() {
+    if (enabled && !frame) frame = requestAnimationFrame(update);
+  }
+
+  function render() {
+    if (!enabled) {
+      host?.remove();
+      return;
+    }
+    host ??= createHost();
+    if (!host.isConnected && document.documentElement) {
+      document.documentElement.appendChild(host);
+    }
+    scheduleUpdate();
+  }
+
+  window.pakeSetReadingProgress = (value) => {
+    enabled = Boolean(value);
+    render();
+  };
+
+  window.addEventListener("scroll", scheduleUpdate, { passive: true });
+  window.addEventListener("resize", scheduleUpdate, { passive: true });
+  // Pages that rebuild the document would otherwise drop the bar
+  new MutationObserver(() => enabled && render()).observe(document, {
+    childList: true,
+    subtree: true,
+  });
+  render();
+})();
@@ -0,0 +1,91 @@
+// Lightweight reader mode: extracts the main article into a clean overlay
+(function () {
+  const READER_ID = "pake-reader-view";
+  const MIN_ARTICLE_TEXT = 500;
+
+  function scoreCandidate(element) {
+    const paragraphs = element.querySelectorAll("p");
+    let textLength = 0;
+    paragraphs.forEach((p) => {
+      textLength += p.innerText.trim().length;
+    });
+    return textLength;
+  }
+
+  function findArticle() {
+    const candidates = [
+      ...document.querySelectorAll("article, main, [role='main']"),
+      ...Array.from(document.querySelectorAll("p"), (p) => p.parentElement),
+    ];
+
+    let best = null;
+    let bestScore = 0;
+    for (const candidate of new Set(candidates)) {
+      if (!candidate) continue;
+      const score = scoreCandidate(candidate);
+      if (score > bestScore) {
+        best = candidate;
+        bestScore = score;
+      }
+    }
+    return bestScore >= MIN_ARTICLE_TEXT ? best : null;
+  }
+
+  function buildReaderView(article) {
+    const content = article.cloneNode(true);
+    content
+      .querySelectorAll(
+        "script, style, iframe, nav, aside, form, button, [aria-hidden='true']",
+      )
+      .forEach((el) => el.remove());
+
+    const container = document.createElement("div");
+    container.id = READER_ID;
+    container.style.cssText =
+      "position:fixed;inset:0;z-index:2147483646;overflow-y:auto;background:Canvas;color:CanvasText;";
+
+    const inner = document.createElement("div");
+    inner.style.cssText =
+      "max-width:720px;margin:0 auto;padding:48px 24px;font:18px/1.7 Georgia,serif;";
+
+    const title = document.createElement("h1");
+    title.textContent = document.title;
+    title.style.cssText = "font-size:32px;line-height:1.3;margin-bottom:24px;";
+
+    inner.appendChild(title);
+    inner.appendChild(content);
+    container.appendChild(inner);
+    container
+      .querySelectorAll("img, video")
+      .forEach((el) => (el.style.maxWidth = "100%"));
+    return container;
+  }
+
+  function toggleReaderMode() {
+    const existing = document.getElementById(READER_ID);
+    if (existing) {
+      existing.remove();
+      document.documentElement.style.overflow =
+        window.pakeReaderOverflow || "";
+      window.pakeReaderMode = false;
+      return false;
+    }
+
+    const article = findArticle();
+    if (!article) {
+      const isChinese = /^zh|CN|TW|HK/.test(navigator.language || "");
+      window.pakeToast?.(
+        isChinese ? "未找到可阅读的文章" : "No readable article found",
+      );
+      return false;
+    }
+
+    window.pakeReaderOverflow = document.documentElement.style.overflow;
+    document.documentElement.style.overflow = "hidden";
+    document.body.appendChild(buildReaderView(article));
+    window.pakeReaderMode = true;
+    return true;
+  }
+
+  window.toggleReaderMode = toggleReaderMode;
+})();
@@ -0,0 +1,66 @@
+// Edge resize handles for undecorated windows, enabled by pakeSetResizeHandles
+(function () {
+  const size = window.pakeConfig?.resize_handle_size ?? 6;
+  if (!(size > 0) || window.self !== window.top) return;
+
+  const HOST_ID = "pake-resize-handles";
+  const px = `${size}px`;
+  const HANDLES = {
+    top: `top:0;left:${px};right:${px};height:${px};cursor:ns-resize`,
+    bottom: `bottom:0;left:${px};right:${px};height:${px};cursor:ns-resize`,
+    left: `left:0;top:${px};bottom:${px};width:${px};cursor:ew-resize`,
+    right: `right:0;top:${px};bottom:${px};width:${px};cursor:ew-resize`,
+    top_left: `top:0;left:0;width:${px};height:${px};cursor:nwse-resize`,
+    top_right: `top:0;right:0;width:${px};height:${px};cursor:nesw-resize`,
+    bottom_left: `bottom:0;left:0;width:${px};height:${px};cursor:nesw-resize`,
+    bottom_right: `bottom:0;right:0;width:${px};height:${px};cursor:nwse-resize`,
+  };
+  let enabled = false;
+  let host = null;
+
+  // Handles live in a shadow root so site styles can't reach them and
+  // only the thin edge strips take pointer events
+  function createHost() {
+    const element = document.createElement("div");
+    element.id = HOST_ID;
+    element.style.cssText =
+      "position:fixed;inset:0;pointer-events:none;z-index:2147483647;";
+    const root = element.attachShadow({ mode: "closed" });
+    for (const [edge, css] of Object.entries(HANDLES)) {
+      const handle = document.createElement("div");
+      handle.style.cssText = `position:fixed;pointer-events:auto;${css}`;
+      handle.addEventListener("mousedown", (event) => {
+        if (event.button !== 0) return;
+        event.preventDefault();
+        event.stopPropagation();
+        window.__TAURI__?.core
+          .invoke("start_resize", { direction: edge })
+          .catch((error) => console.error("[Pake] Failed to resize:", error));
+      });
+      root.appendChild(handle);
+    }
+    return element;
+  }
+
+  function render() {
+    if (!enabled) {
+      host?.remove();
+      return;
+    }
+    host ??= createHost();
+    if (!host.isConnected && document.documentElement) {
+      document.documentElement.appendChild(host);
+    }
+  }
+
+  window.pakeSetResizeHandles = (value) => {
+    enabled = Boolean(value);
+    render();
+  };
+
+  // Pages that rebuild the document would otherwise drop the handles
+  new MutationObserver(() => enabled && render()).observe(document, {
+    childList: true,
+    subtree: true,
+  });
+})();
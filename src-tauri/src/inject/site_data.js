@@ -0,0 +1,86 @@
+// Site data panel: lists origins with stored data and clears them one at a time
+(function () {
+  const PANEL_ID = "pake-site-data";
+
+  function formatSize(size) {
+    if (size == null) return "";
+    if (size < 1024) return `${size} B`;
+    if (size < 1024 * 1024) return `${(size / 1024).toFixed(1)} KB`;
+    return `${(size / 1024 / 1024).toFixed(1)} MB`;
+  }
+
+  function buildRow(invoke, entry) {
+    const row = document.createElement("li");
+    row.style.cssText =
+      "display:flex;align-items:center;gap:12px;padding:8px 0;border-bottom:1px solid rgba(128,128,128,.2);";
+
+    const name = document.createElement("span");
+    name.textContent = entry.origin;
+    name.style.cssText =
+      "flex:1;overflow:hidden;text-overflow:ellipsis;white-space:nowrap;";
+
+    const size = document.createElement("span");
+    size.textContent = formatSize(entry.size);
+    size.style.cssText = "opacity:.6;font-size:12px;";
+
+    const clear = document.createElement("button");
+    clear.textContent = "Clear";
+    clear.addEventListener("click", () => {
+      clear.disabled = true;
+      invoke("clear_origin_data", { origin: entry.origin })
+        .then(() => row.remove())
+        .catch((error) => {
+          clear.disabled = false;
+          window.pakeToast?.(String(error));
+        });
+    });
+
+    row.append(name, size, clear);
+    return row;
+  }
+
+  function showSiteData() {
+    const invoke = window.__TAURI__?.core.invoke;
+    if (!invoke) return;
+    document.getElementById(PANEL_ID)?.remove();
+
+    const panel = document.createElement("div");
+    panel.id = PANEL_ID;
+    panel.style.cssText =
+      "position:fixed;inset:0;z-index:2147483646;display:flex;align-items:center;justify-content:center;background:rgba(0,0,0,.4);font:14px -apple-system,system-ui,sans-serif;";
+    panel.addEventListener("click", (event) => {
+      if (event.target === panel) panel.remove();
+    });
+
+    const dialog = document.createElement("div");
+    dialog.style.cssText =
+      "width:min(520px,90vw);max-height:70vh;overflow-y:auto;padding:20px 24px;border-radius:12px;background:Canvas;color:CanvasText;box-shadow:0 8px 32px rgba(0,0,0,.3);";
+
+    const title = document.createElement("h2");
+    title.textContent = "Site Data";
+    title.style.cssText = "margin:0 0 12px;font-size:18px;";
+
+    const list = document.createElement("ul");
+    list.style.cssText = "list-style:none;margin:0;padding:0;";
+    list.textContent = "Loading…";
+
+    dialog.append(title, list);
+    panel.appendChild(dialog);
+    document.body.appendChild(panel);
+
+    invoke("list_origins_with_data")
+      .then((origins) => {
+        list.textContent = origins.length ? "" : "No site data stored.";
+        origins.forEach((entry) => list.appendChild(buildRow(invoke, entry)));
+      })
+      .catch((error) => {
+        list.textContent = String(error);
+      });
+  }
+
+  document.addEventListener("keydown", (event) => {
+    if (event.key === "Escape") document.getElementById(PANEL_ID)?.remove();
+  });
+
+  window.pakeShowSiteData = showSiteData;
+})();
@@ -30,6 +30,9 @@ document.addEventListener("DOMContentLoaded", () => {
     if (isDark) mode = "dark";
     else if (isLight) mode = "light";
 
+    // A class set by theme_sync.js follows the OS, it isn't the site's choice
+    if (mode && mode === window.pakeAppliedTheme) return;
+
     // Only invoke Rust command if an explicit theme override is detected
     if (mode && window.__TAURI__?.core) {
       window.__TAURI__.core.invoke("update_theme_mode", { mode });
@@ -0,0 +1,60 @@
+// Mirrors the OS appearance into the page while the app theme is "system".
+// `dark_mode_class` covers sites that ignore prefers-color-scheme, and
+// `color_scheme_meta` those that only read <meta name="color-scheme">.
+(function () {
+  const className = window.pakeConfig?.dark_mode_class?.trim();
+  const useMeta = window.pakeConfig?.color_scheme_meta === true;
+  const STYLE_ID = "pake-color-scheme";
+  const query = window.matchMedia("(prefers-color-scheme: dark)");
+  // Light or dark theme picked in the app; null follows the OS
+  let forcedMode = null;
+
+  // Reuses the page's own tag if it has one so there is only ever one
+  function setMeta(mode) {
+    if (!useMeta || !document.head) return;
+    let meta = document.head.querySelector('meta[name="color-scheme"]');
+    if (!meta) {
+      meta = document.createElement("meta");
+      meta.name = "color-scheme";
+      document.head.appendChild(meta);
+    }
+    meta.content = mode;
+  }
+
+  function apply(mode) {
+    const dark = mode === "dark";
+    // Read by theme_refresh.js so our own class isn't mistaken for the site's theme
+    window.pakeAppliedTheme = className ? mode : null;
+
+    let style = document.getElementById(STYLE_ID);
+    if (!style) {
+      style = document.createElement("style");
+      style.id = STYLE_ID;
+      document.documentElement.appendChild(style);
+    }
+    style.textContent = `:root { color-scheme: ${dark ? "dark" : "light"}; }`;
+
+    if (className) {
+      document.documentElement.classList.toggle(className, dark);
+      document.body?.classList.toggle(className, dark);
+    }
+    setMeta(forcedMode ?? mode);
+  }
+
+  window.pakeApplySystemTheme = (mode) => {
+    if (document.documentElement) apply(mode);
+  };
+
+  window.pakeSetColorSchemeMeta = (mode) => {
+    forcedMode = mode === "light" || mode === "dark" ? mode : null;
+    setMeta(forcedMode ?? (query.matches ? "dark" : "light"));
+  };
+
+  const applyCurrent = () => apply(query.matches ? "dark" : "light");
+  query.addEventListener("change", applyCurrent);
+  if (document.readyState === "loading") {
+    document.addEventListener("DOMContentLoaded", applyCurrent);
+  } else {
+    applyCurrent();
+  }
+})();
@@ -0,0 +1,48 @@
+// Development-only network throttling for fetch and XHR, see set_network_throttle
+(function () {
+  let conditions = null;
+  const wait = (ms) => new Promise((resolve) => setTimeout(resolve, ms));
+  const transferTime = (bytes, rate) => (rate ? (bytes / rate) * 1000 : 0);
+
+  window.pakeSetNetworkThrottle = (next) => {
+    conditions = next;
+  };
+  window.__TAURI__?.core
+    .invoke("get_network_throttle")
+    .then(window.pakeSetNetworkThrottle)
+    .catch(() => {});
+
+  const originalFetch = window.fetch;
+  window.fetch = async function (...args) {
+    const active = conditions;
+    if (!active) return originalFetch.apply(this, args);
+    if (active.offline) throw new TypeError("Failed to fetch");
+
+    await wait(active.latency_ms);
+    const response = await originalFetch.apply(this, args);
+    // Opaque responses can't be rebuilt, so they only get the latency
+    if (response.type === "opaque" || !response.body) return response;
+
+    const body = await response.arrayBuffer();
+    await wait(transferTime(body.byteLength, active.download_bytes_per_sec));
+    return new Response(body, {
+      status: response.status,
+      statusText: response.statusText,
+      headers: response.headers,
+    });
+  };
+
+  const originalSend = XMLHttpRequest.prototype.send;
+  XMLHttpRequest.prototype.send = function (body) {
+    const active = conditions;
+    if (!active) return originalSend.call(this, body);
+    if (active.offline) {
+      setTimeout(() => this.dispatchEvent(new ProgressEvent("error")), 0);
+      return;
+    }
+    const size = typeof body === "string" ? body.length : body?.size || 0;
+    const delay =
+      active.latency_ms + transferTime(size, active.upload_bytes_per_sec);
+    setTimeout(() => originalSend.call(this, body), delay);
+  };
+})();
@@ -0,0 +1,78 @@
+// In-place page translation driven by `toggle_translate`: Rust asks for the
+// visible text, sends it to the configured endpoint and hands back translations.
+(function () {
+  const SKIPPED_TAGS = new Set([
+    "SCRIPT",
+    "STYLE",
+    "NOSCRIPT",
+    "TEXTAREA",
+    "INPUT",
+    "SELECT",
+    "CODE",
+    "PRE",
+    "SVG",
+  ]);
+
+  let nodes = [];
+  let originals = [];
+
+  function isVisible(element) {
+    if (element.checkVisibility) return element.checkVisibility();
+    return element.offsetParent !== null || element === document.body;
+  }
+
+  function acceptNode(node) {
+    const parent = node.parentElement;
+    if (!parent || SKIPPED_TAGS.has(parent.tagName)) {
+      return NodeFilter.FILTER_REJECT;
+    }
+    if (parent.closest("[contenteditable], [translate='no'], .notranslate")) {
+      return NodeFilter.FILTER_REJECT;
+    }
+    // Skip whitespace, numbers and punctuation, which there's nothing to translate in
+    if (!/\p{L}/u.test(node.nodeValue) || !isVisible(parent)) {
+      return NodeFilter.FILTER_REJECT;
+    }
+    return NodeFilter.FILTER_ACCEPT;
+  }
+
+  // Returns the trimmed text of every visible text node, in document order
+  function extract() {
+    restore();
+    const walker = document.createTreeWalker(
+      document.body,
+      NodeFilter.SHOW_TEXT,
+      { acceptNode },
+    );
+    while (walker.nextNode()) nodes.push(walker.currentNode);
+    originals = nodes.map((node) => node.nodeValue);
+    return originals.map((text) => text.trim());
+  }
+
+  // Surrounding whitespace is kept so inline text doesn't run together
+  function apply(start, translations) {
+    translations.forEach((translation, i) => {
+      const node = nodes[start + i];
+      if (!node || typeof translation !== "string") return;
+      const original = originals[start + i];
+      const leading = original.match(/^\s*/)[0];
+      const trailing = original.match(/\s*$/)[0];
+      node.nodeValue = leading + translation.trim() + trailing;
+    });
+  }
+
+  function restore() {
+    nodes.forEach((node, i) => {
+      node.nodeValue = originals[i];
+    });
+    nodes = [];
+    originals = [];
+  }
+
+  window.pakeTranslate = {
+    extract,
+    apply,
+    restore,
+    isActive: () => nodes.length > 0,
+  };
+})();
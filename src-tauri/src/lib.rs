@@ -3,7 +3,7 @@ mod app;
 mod util;
 
 use std::sync::atomic::{AtomicUsize, Ordering};
-use tauri::{Manager, WebviewUrl, WebviewWindowBuilder};
+use tauri::{DragDropEvent, Manager, Url, WebviewUrl, WebviewWindowBuilder};
 use tauri_plugin_window_state::Builder as WindowStatePlugin;
 use tauri_plugin_window_state::StateFlags;
 
@@ -14,39 +14,74 @@ static WINDOW_COUNTER: AtomicUsize = AtomicUsize::new(1);
 
 const WINDOW_SHOW_DELAY: u64 = 50;
 
+// With show_on_first_paint, the longest the window stays hidden waiting for the page
+const FIRST_PAINT_TIMEOUT: u64 = 5000;
+
 use app::{
+    config::{BeforeUnloadBehavior, HideOnCloseMode, ShortcutAction},
+    file_drop::{self, FileDropRules},
+    har,
     invoke::{
-        clear_cache_and_restart, download_file, download_file_by_binary, send_notification,
-        update_theme_mode,
+        blur_element, broadcast_event, capture_screenshot, check_for_updates,
+        clear_cache_and_restart, clear_content_rules, clear_download_history, clear_har,
+        clear_origin_data, click_element, clipboard_read_text, clipboard_write_text, cycle_urls,
+        download_file, download_file_by_binary, enter_pip_mode, exit_pip_mode, export_har,
+        export_logs, flash_to_front, focus_element, force_quit, get_accessibility_modes,
+        get_content_rules, get_dnd, get_download_history, get_identity, get_last_load_timing,
+        get_muted, get_network_throttle, get_page_metadata, get_preferences, get_reading_progress,
+        get_selection, get_setting, get_unread_count, get_version_info, get_viewport, get_zoom,
+        is_loading, list_origins_with_data, lookup_selection, media_next, media_play_pause,
+        media_prev, move_to_workspace, open_inline_content, open_preferences,
+        open_relative_in_new_window, paste_image_from_clipboard, pin_to_current_monitor,
+        play_feedback, record_har_entry, report_blocked_requests, report_eval_result,
+        report_first_paint, report_load_timing, report_media_state, request_attention,
+        restore_dnd_state, run_named_action, save_page, save_window_state, scroll_page,
+        send_notification, set_content_rules, set_dnd, set_grayscale, set_javascript_enabled,
+        set_muted, set_network_throttle, set_preferences, set_reading_progress, set_reduced_motion,
+        set_scroll, set_setting, set_title_prefix, set_titlebar_color, set_zoom, show_js_dialog,
+        snap_window, start_oauth, start_resize, switch_environment, toggle_adblock,
+        toggle_reader_mode, toggle_translate, update_theme_mode,
+    },
+    monitor_state,
+    preferences::{self, PREFERENCES_LABEL},
+    setup::{
+        is_quitting, set_focus_on_show, set_global_shortcut, set_hang_watchdog, set_keepalive,
+        set_monitor_pinning, set_state_autosave, set_system_tray,
+    },
+    updater, view_state,
+    window::{
+        confirm_unload, fade_for_focus, is_recreating_window, schedule_aspect_ratio, set_window,
+        track_loading, wait_for_first_paint,
     },
-    setup::{set_global_shortcut, set_system_tray},
-    window::set_window,
+    workspace,
 };
-use util::get_pake_config;
+use util::{check_data_dir, emit_shown, get_pake_config};
+
+#[cfg(target_os = "macos")]
+use app::window::bring_to_active_space;
+#[cfg(target_os = "macos")]
+use util::FULLSCREEN_EXIT_DELAY_MS;
 
-/// Extract a valid URL from arguments that matches the configured domain
+/// Extract a valid URL from arguments that matches the configured domain.
+/// Path (`/dashboard`) or query (`?tab=inbox`) arguments are resolved against the configured URL.
 fn extract_url_arg(args: &[String], config_url: &str) -> Option<String> {
-    let allowed_host = config_url
-        .strip_prefix("https://")
-        .or_else(|| config_url.strip_prefix("http://"))
-        .and_then(|s| s.split('/').next())
-        .unwrap_or("");
-
-    args.iter()
-        .skip(1)
-        .find(|arg| {
-            if arg.starts_with("https://") || arg.starts_with("http://") {
-                if let Some(host) = arg
-                    .strip_prefix("https://")
-                    .or_else(|| arg.strip_prefix("http://"))
-                    .and_then(|s| s.split('/').next())
-                {
-                    return host == allowed_host;
-                }
-            }
-            false
-        })
-        .cloned()
+    let base_url = Url::parse(config_url).ok()?;
+    let allowed_host = base_url.host_str()?;
+
+    args.iter().skip(1).find_map(|arg| {
+        let url = if arg.starts_with("https://") || arg.starts_with("http://") {
+            Url::parse(arg).ok()?
+        } else if arg.starts_with('/') || arg.starts_with('?') {
+            base_url.join(arg).ok()?
+        } else {
+            return None;
+        };
+
+        // Resolved paths like `//other.com` must not escape the configured host
+        let is_allowed = url.host_str() == Some(allowed_host)
+            && url.port_or_known_default() == base_url.port_or_known_default();
+        is_allowed.then(|| url.to_string())
+    })
 }
 
 pub fn run_app() {
@@ -58,38 +93,79 @@ pub fn run_app() {
     }
 
     let (pake_config, tauri_config) = get_pake_config();
+
+    // WebKitGTK reads this once at startup, so it has to be set before any webview exists
+    #[cfg(target_os = "linux")]
+    if pake_config.windows[0].disable_gpu
+        && std::env::var("WEBKIT_DISABLE_COMPOSITING_MODE").is_err()
+    {
+        std::env::set_var("WEBKIT_DISABLE_COMPOSITING_MODE", "1");
+    }
+
     let tauri_app = tauri::Builder::default();
 
     let show_system_tray = pake_config.show_system_tray();
     let hide_on_close = pake_config.windows[0].hide_on_close;
-    let activation_shortcut = pake_config.windows[0].activation_shortcut.clone();
+    let hide_on_close_mode = pake_config.windows[0].hide_on_close_mode;
+    let activation_shortcut = pake_config.windows[0]
+        .shortcut(ShortcutAction::Activate)
+        .unwrap_or_default();
     let init_fullscreen = pake_config.windows[0].fullscreen;
     let start_to_tray = pake_config.windows[0].start_to_tray && show_system_tray; // Only valid when tray is enabled
     let multi_instance = pake_config.multi_instance;
+    let beforeunload = pake_config.windows[0].beforeunload;
+    let aspect_ratio = pake_config.windows[0].aspect_ratio();
+    let file_drop = FileDropRules::from_config(&pake_config.windows[0]);
+    let window_limits = pake_config.windows[0].window_size();
 
-    let window_state_plugin = WindowStatePlugin::default()
-        .with_state_flags(if init_fullscreen {
-            StateFlags::FULLSCREEN
-        } else {
-            // Prevent flickering on the first open.
-            StateFlags::all() & !StateFlags::VISIBLE
-        })
-        .build();
+    let remember_window_state = pake_config.windows[0].remember_window_state;
+    let focus_on_active_space = pake_config.windows[0].focus_on_active_space;
+    let dim_on_blur = pake_config.windows[0].dim_on_blur;
+    let show_on_first_paint = pake_config.windows[0].show_on_first_paint;
+    let start_workspace = pake_config.windows[0].start_workspace;
+    let per_monitor_state = pake_config.windows[0].per_monitor_state;
+
+    let mut app_builder = tauri_app;
+
+    // Zoom is kept per window label in the app state file, so it persists either way
+    if remember_window_state {
+        let window_state_plugin = WindowStatePlugin::default()
+            .with_state_flags(if init_fullscreen {
+                StateFlags::FULLSCREEN
+            } else {
+                // Prevent flickering on the first open.
+                StateFlags::all() & !StateFlags::VISIBLE
+            })
+            .build();
+        app_builder = app_builder.plugin(window_state_plugin);
+    }
 
     #[allow(deprecated)]
-    let mut app_builder = tauri_app
-        .plugin(window_state_plugin)
+    let mut app_builder = app_builder
         .plugin(tauri_plugin_oauth::init())
         .plugin(tauri_plugin_http::init())
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_notification::init())
+        .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_clipboard_manager::init())
+        .plugin(
+            tauri_plugin_log::Builder::new()
+                .level(log::LevelFilter::Info)
+                .build(),
+        )
         .plugin(tauri_plugin_opener::init()); // Add this
 
+    if let Some(pubkey) = &pake_config.updater_pubkey {
+        app_builder =
+            app_builder.plugin(tauri_plugin_updater::Builder::new().pubkey(pubkey).build());
+    }
+
     // Only add single instance plugin if multiple instances are not allowed
     if !multi_instance {
         let config_url_for_callback = pake_config.windows[0].url.clone();
-        let window_width = pake_config.windows[0].width;
-        let window_height = pake_config.windows[0].height;
+        let window_size = pake_config.windows[0].window_size();
+        let window_width = window_size.width;
+        let window_height = window_size.height;
         app_builder = app_builder.plugin(tauri_plugin_single_instance::init(move |app, args, _cwd| {
             // If URL argument provided, open in a new window
             if let Some(url) = extract_url_arg(&args, &config_url_for_callback) {
@@ -101,9 +177,14 @@ pub fn run_app() {
                     WebviewUrl::External(url.parse().unwrap()),
                 )
                 .title("")
+                .on_page_load(|window, payload| track_loading(&window, &payload))
+                .on_document_title_changed(|window, title| {
+                    view_state::on_page_title_changed(&window, &title)
+                })
                 .inner_size(window_width, window_height)
                 .build()
                 {
+                    view_state::restore_view_state(&new_window);
                     let _ = new_window.show();
                     let _ = new_window.set_focus();
                 }
@@ -112,19 +193,142 @@ pub fn run_app() {
                 let _ = window.unminimize();
                 let _ = window.show();
                 let _ = window.set_focus();
+                #[cfg(target_os = "macos")]
+                if focus_on_active_space {
+                    bring_to_active_space(&window);
+                }
+                #[cfg(not(target_os = "macos"))]
+                let _ = focus_on_active_space;
+                emit_shown(&window);
             }
         }));
     }
 
+    // Single-instance keys and app dirs (including the window-state file) all
+    // derive from the identifier, so an app_id_suffix has to land in the context
+    let mut context = tauri::generate_context!();
+    context.config_mut().identifier = tauri_config.identifier.clone();
+
     app_builder
         .invoke_handler(tauri::generate_handler![
             download_file,
             download_file_by_binary,
+            get_download_history,
+            clear_download_history,
             send_notification,
             update_theme_mode,
+            set_zoom,
+            get_zoom,
+            set_muted,
+            set_title_prefix,
+            get_muted,
+            set_grayscale,
+            set_reading_progress,
+            get_reading_progress,
+            is_loading,
+            set_reduced_motion,
+            get_accessibility_modes,
             clear_cache_and_restart,
+            list_origins_with_data,
+            clear_origin_data,
+            set_javascript_enabled,
+            open_inline_content,
+            open_relative_in_new_window,
+            show_js_dialog,
+            switch_environment,
+            get_selection,
+            get_identity,
+            lookup_selection,
+            set_titlebar_color,
+            request_attention,
+            flash_to_front,
+            play_feedback,
+            export_logs,
+            focus_element,
+            blur_element,
+            click_element,
+            paste_image_from_clipboard,
+            clipboard_read_text,
+            clipboard_write_text,
+            report_eval_result,
+            get_setting,
+            save_window_state,
+            force_quit,
+            open_preferences,
+            get_preferences,
+            set_preferences,
+            set_network_throttle,
+            get_network_throttle,
+            set_setting,
+            get_page_metadata,
+            scroll_page,
+            capture_screenshot,
+            save_page,
+            get_viewport,
+            set_scroll,
+            start_oauth,
+            toggle_reader_mode,
+            toggle_translate,
+            set_content_rules,
+            clear_content_rules,
+            get_content_rules,
+            toggle_adblock,
+            get_unread_count,
+            report_blocked_requests,
+            snap_window,
+            enter_pip_mode,
+            exit_pip_mode,
+            start_resize,
+            run_named_action,
+            pin_to_current_monitor,
+            set_dnd,
+            get_dnd,
+            broadcast_event,
+            cycle_urls,
+            media_play_pause,
+            media_next,
+            media_prev,
+            report_media_state,
+            report_first_paint,
+            move_to_workspace,
+            report_load_timing,
+            get_last_load_timing,
+            record_har_entry,
+            export_har,
+            clear_har,
+            get_version_info,
+            check_for_updates,
         ])
         .setup(move |app| {
+            log::info!(
+                "Loaded config for {} ({})",
+                tauri_config.product_name.as_deref().unwrap_or("pake"),
+                pake_config.windows[0].url
+            );
+            for (accelerator, actions) in pake_config.windows[0].shortcut_conflicts() {
+                log::warn!(
+                    "Shortcut {} is bound to more than one action: {:?}",
+                    accelerator,
+                    actions
+                );
+            }
+
+            if let Err(e) = check_data_dir() {
+                log::error!("{}", e);
+                return Err(e.into());
+            }
+
+            let invalid_cert_hosts = pake_config.windows[0].invalid_cert_hosts();
+            if !invalid_cert_hosts.is_empty() {
+                log::warn!(
+                    "!!! TLS certificate validation is DISABLED for: {} !!!",
+                    invalid_cert_hosts.join(", ")
+                );
+            }
+            if pake_config.updater_endpoint.is_some() && pake_config.updater_pubkey.is_none() {
+                log::warn!("updater_endpoint is set without updater_pubkey; updates are disabled");
+            }
+
             // --- Menu Construction Start ---
             #[cfg(target_os = "macos")]
             {
@@ -138,7 +342,10 @@ pub fn run_app() {
             }
             // --- Menu Construction End ---
 
-            let window = set_window(app, &pake_config, &tauri_config);
+            let window = set_window(app.handle(), &pake_config, &tauri_config);
+            if per_monitor_state {
+                monitor_state::watch(&window);
+            }
 
             // Handle URL argument on initial launch
             let launch_args: Vec<String> = std::env::args().collect();
@@ -152,22 +359,72 @@ pub fn run_app() {
                 });
             }
 
+            har::set_enabled(pake_config.windows[0].har_capture);
+            restore_dnd_state(app.app_handle());
+            preferences::restore_theme(app.app_handle());
             set_system_tray(
                 app.app_handle(),
                 show_system_tray,
                 &pake_config.system_tray_path,
+                pake_config.tray_icon_template,
+                init_fullscreen,
+                pake_config.windows[0].tray_fullscreen_behavior,
+                remember_window_state,
+                pake_config.updates_enabled(),
+            )
+            .unwrap();
+            set_global_shortcut(
+                app.app_handle(),
+                activation_shortcut,
                 init_fullscreen,
+                &pake_config.windows[0].snap_shortcuts,
+                pake_config.windows[0].media_controls,
             )
             .unwrap();
-            set_global_shortcut(app.app_handle(), activation_shortcut, init_fullscreen).unwrap();
+            set_keepalive(app.app_handle(), &pake_config.windows[0]);
+            set_hang_watchdog(app.app_handle(), &pake_config.windows[0]);
+            set_state_autosave(app.app_handle(), &pake_config.windows[0]);
+            set_focus_on_show(
+                app.app_handle(),
+                pake_config.windows[0].focus_selector_on_show.clone(),
+            );
+            set_monitor_pinning(app.app_handle());
+
+            if pake_config.updates_enabled() && pake_config.check_updates_on_launch {
+                tauri::async_runtime::spawn(updater::prompt_for_update(
+                    app.app_handle().clone(),
+                    false,
+                ));
+            }
 
             // Show window after state restoration to prevent position flashing
             // Unless start_to_tray is enabled, then keep it hidden
             if !start_to_tray {
                 let window_clone = window.clone();
                 tauri::async_runtime::spawn(async move {
-                    tokio::time::sleep(tokio::time::Duration::from_millis(WINDOW_SHOW_DELAY)).await;
+                    if show_on_first_paint {
+                        let timeout = tokio::time::Duration::from_millis(FIRST_PAINT_TIMEOUT);
+                        if tokio::time::timeout(timeout, wait_for_first_paint())
+                            .await
+                            .is_err()
+                        {
+                            log::warn!(
+                                "No first paint after {}ms, showing window",
+                                FIRST_PAINT_TIMEOUT
+                            );
+                        }
+                    } else {
+                        tokio::time::sleep(tokio::time::Duration::from_millis(WINDOW_SHOW_DELAY))
+                            .await;
+                    }
                     window_clone.show().unwrap();
+                    emit_shown(&window_clone);
+
+                    if let Some(index) = start_workspace {
+                        if let Err(e) = workspace::move_to_workspace(&window_clone, index).await {
+                            log::warn!("Failed to move to workspace {}: {}", index, e);
+                        }
+                    }
 
                     // Fixed: Linux fullscreen issue with virtual keyboard
                     #[cfg(target_os = "linux")]
@@ -190,8 +447,56 @@ pub fn run_app() {
             Ok(())
         })
         .on_window_event(move |_window, _event| {
+            if let tauri::WindowEvent::Focused(true) = _event {
+                let _ = _window.request_user_attention(None);
+            }
+            // Every window reports the change, the main one speaks for all of them
+            if let tauri::WindowEvent::ThemeChanged(theme) = _event {
+                if _window.label() == "pake" {
+                    preferences::sync_system_theme(_window.app_handle(), *theme);
+                }
+            }
+            if let (tauri::WindowEvent::Focused(focused), Some(opacity)) = (_event, dim_on_blur) {
+                if _window.label() != PREFERENCES_LABEL {
+                    fade_for_focus(_window, *focused, opacity);
+                }
+            }
+            if let (tauri::WindowEvent::Resized(_), Some(ratio)) = (_event, aspect_ratio) {
+                if _window.label() == "pake" {
+                    schedule_aspect_ratio(_window, ratio, window_limits);
+                }
+            }
+            if let (tauri::WindowEvent::Moved(_) | tauri::WindowEvent::Resized(_), true) =
+                (_event, per_monitor_state && _window.label() == "pake")
+            {
+                if let Some(window) = _window.app_handle().get_webview_window("pake") {
+                    monitor_state::schedule_save(&window);
+                }
+            }
+            if let (
+                tauri::WindowEvent::DragDrop(DragDropEvent::Drop { paths, position }),
+                Some(rules),
+            ) = (_event, &file_drop)
+            {
+                if let Some(window) = _window.app_handle().get_webview_window(_window.label()) {
+                    tauri::async_runtime::spawn(file_drop::deliver(
+                        window,
+                        rules.clone(),
+                        paths.clone(),
+                        *position,
+                    ));
+                }
+            }
             if let tauri::WindowEvent::CloseRequested { api, .. } = _event {
-                if hide_on_close {
+                let other_windows_open = _window.app_handle().webview_windows().len() > 1;
+                if _window.label() == PREFERENCES_LABEL || is_quitting() {
+                    // Preferences always close for real, as does everything during force_quit
+                } else if hide_on_close
+                    && hide_on_close_mode == HideOnCloseMode::LastWindowOnly
+                    && other_windows_open
+                {
+                    // Let extra windows close normally; the last one still hides below
+                } else if hide_on_close {
                     // Hide window when hide_on_close is enabled (regardless of tray status)
                     let window = _window.clone();
                     tauri::async_runtime::spawn(async move {
@@ -199,7 +504,8 @@ pub fn run_app() {
                         {
                             if window.is_fullscreen().unwrap_or(false) {
                                 window.set_fullscreen(false).unwrap();
-                                tokio::time::sleep(Duration::from_millis(900)).await;
+                                tokio::time::sleep(Duration::from_millis(FULLSCREEN_EXIT_DELAY_MS))
+                                    .await;
                             }
                         }
                         #[cfg(target_os = "linux")]
@@ -216,15 +522,37 @@ pub fn run_app() {
                         window.hide().unwrap();
                     });
                     api.prevent_close();
+                } else if beforeunload == BeforeUnloadBehavior::Confirm {
+                    api.prevent_close();
+                    let app_handle = _window.app_handle().clone();
+                    let label = _window.label().to_string();
+                    tauri::async_runtime::spawn(async move {
+                        let confirmed = match app_handle.get_webview_window(&label) {
+                            Some(window) => confirm_unload(&window).await,
+                            None => true,
+                        };
+                        if confirmed {
+                            std::process::exit(0);
+                        }
+                    });
                 } else {
                     // Exit app completely when hide_on_close is false
                     std::process::exit(0);
                 }
             }
         })
-        .build(tauri::generate_context!())
+        .build(context)
         .expect("error while building tauri application")
-        .run(|_app, _event| {
+        .run(move |_app, _event| {
+            // The main window is briefly gone while set_javascript_enabled rebuilds it
+            if let tauri::RunEvent::ExitRequested {
+                code: None, api, ..
+            } = &_event
+            {
+                if is_recreating_window() {
+                    api.prevent_exit();
+                }
+            }
             // Handle macOS dock icon click to reopen hidden window
             #[cfg(target_os = "macos")]
             if let tauri::RunEvent::Reopen {
@@ -236,6 +564,10 @@ pub fn run_app() {
                     if let Some(window) = _app.get_webview_window("pake") {
                         let _ = window.show();
                         let _ = window.set_focus();
+                        if focus_on_active_space {
+                            bring_to_active_space(&window);
+                        }
+                        emit_shown(&window);
                     }
                 }
             }
@@ -245,3 +577,56 @@ pub fn run_app() {
 pub fn run() {
     run_app()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE: &str = "https://example.com/app/";
+
+    fn args(list: &[&str]) -> Vec<String> {
+        std::iter::once("pake")
+            .chain(list.iter().copied())
+            .map(String::from)
+            .collect()
+    }
+
+    #[test]
+    fn resolves_path_and_query_against_base() {
+        assert_eq!(
+            extract_url_arg(&args(&["/dashboard"]), BASE).as_deref(),
+            Some("https://example.com/dashboard")
+        );
+        assert_eq!(
+            extract_url_arg(&args(&["?tab=inbox"]), BASE).as_deref(),
+            Some("https://example.com/app/?tab=inbox")
+        );
+    }
+
+    #[test]
+    fn accepts_full_urls_on_the_configured_host() {
+        assert_eq!(
+            extract_url_arg(&args(&["--flag", "https://example.com/a?b=1"]), BASE).as_deref(),
+            Some("https://example.com/a?b=1")
+        );
+    }
+
+    #[test]
+    fn rejects_other_hosts_and_ports() {
+        for arg in [
+            "https://evil.com/dashboard",
+            "https://example.com.evil.com/",
+            "https://example.com:8443/",
+            "//evil.com/dashboard",
+            "dashboard",
+        ] {
+            assert_eq!(extract_url_arg(&args(&[arg]), BASE), None, "{arg}");
+        }
+    }
+
+    #[test]
+    fn ignores_the_executable_path() {
+        let args = vec!["/dashboard".to_string()];
+        assert_eq!(extract_url_arg(&args, BASE), None);
+    }
+}
@@ -1,7 +1,40 @@
 use crate::app::config::PakeConfig;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
 use std::env;
-use std::path::PathBuf;
-use tauri::{AppHandle, Config, Manager, WebviewWindow};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{LazyLock, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Config, Emitter, EventTarget, Manager, Url, WebviewWindow};
+use tokio::sync::oneshot;
+
+pub const SHOWN_EVENT: &str = "pake://shown";
+
+pub const HANG_RECOVERED_EVENT: &str = "pake://hang-recovered";
+
+pub const THEME_CHANGED_EVENT: &str = "pake://theme-changed";
+
+pub const DND_CHANGED_EVENT: &str = "pake://dnd-changed";
+
+pub const LOAD_START_EVENT: &str = "pake://load-start";
+
+pub const LOAD_END_EVENT: &str = "pake://load-end";
+
+pub const UNREAD_EVENT: &str = "pake://unread";
+
+const APP_STATE_FILE: &str = "pake-state.json";
+const SETTINGS_FILE: &str = "pake-settings.json";
+
+const EVAL_RESULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+static EVAL_COUNTER: AtomicU64 = AtomicU64::new(1);
+static PENDING_EVALS: LazyLock<Mutex<HashMap<u64, oneshot::Sender<Value>>>> =
+    LazyLock::new(Default::default);
+
+// macOS needs the fullscreen exit animation to finish before the window can be hidden
+pub const FULLSCREEN_EXIT_DELAY_MS: u64 = 900;
 
 pub fn get_pake_config() -> (PakeConfig, Config) {
     #[cfg(feature = "cli-build")]
@@ -13,17 +46,41 @@ pub fn get_pake_config() -> (PakeConfig, Config) {
         serde_json::from_str(include_str!("../pake.json")).expect("Failed to parse pake config");
 
     #[cfg(feature = "cli-build")]
-    let tauri_config: Config = serde_json::from_str(include_str!("../.pake/tauri.conf.json"))
+    let mut tauri_config: Config = serde_json::from_str(include_str!("../.pake/tauri.conf.json"))
         .expect("Failed to parse tauri config");
 
     #[cfg(not(feature = "cli-build"))]
-    let tauri_config: Config = serde_json::from_str(include_str!("../tauri.conf.json"))
+    let mut tauri_config: Config = serde_json::from_str(include_str!("../tauri.conf.json"))
         .expect("Failed to parse tauri config");
 
+    tauri_config.identifier = with_app_id_suffix(
+        &tauri_config.identifier,
+        pake_config.app_id_suffix.as_deref(),
+        '.',
+    );
+
     (pake_config, tauri_config)
 }
 
+/// `base` with the sanitized `app_id_suffix` appended after `separator`.
+/// Anything but ASCII letters, digits and `-` becomes `-`, as bundle identifiers require.
+pub fn with_app_id_suffix(base: &str, suffix: Option<&str>, separator: char) -> String {
+    let suffix: String = suffix
+        .unwrap_or_default()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    match suffix.trim_matches('-') {
+        "" => base.to_string(),
+        suffix => format!("{}{}{}", base, separator, suffix),
+    }
+}
+
 pub fn get_data_dir(app: &AppHandle, package_name: String) -> PathBuf {
+    // Already created and checked by check_data_dir during setup
+    if let Ok(Some(data_dir)) = configured_data_dir() {
+        return data_dir;
+    }
     {
         let data_dir = app
             .path()
@@ -39,6 +96,518 @@ pub fn get_data_dir(app: &AppHandle, package_name: String) -> PathBuf {
     }
 }
 
+/// Expand a leading `~` and `$VAR` / `${VAR}` references (plus `%VAR%` on
+/// Windows) in a configured path.
+pub fn expand_path(path: &str) -> Result<PathBuf, String> {
+    let mut expanded = String::new();
+    let mut rest = path;
+    if rest == "~" || rest.starts_with("~/") || rest.starts_with("~\\") {
+        let home = env::var("HOME")
+            .or_else(|_| env::var("USERPROFILE"))
+            .map_err(|_| "Cannot expand ~, the home directory is unknown".to_string())?;
+        expanded.push_str(&home);
+        rest = &rest[1..];
+    }
+
+    let markers: &[char] = if cfg!(windows) { &['$', '%'] } else { &['$'] };
+    while let Some(start) = rest.find(markers) {
+        expanded.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let (name, consumed) = if rest[start..].starts_with("${") {
+            let end = after
+                .find('}')
+                .ok_or_else(|| format!("Unclosed ${{ in {}", path))?;
+            (&after[1..end], end + 1)
+        } else if rest[start..].starts_with('%') {
+            let end = after.find('%').unwrap_or(0);
+            (&after[..end], end + 1)
+        } else {
+            let end = after
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(after.len());
+            (&after[..end], end)
+        };
+        if name.is_empty() {
+            // A lone `$` or `%` is kept as written
+            expanded.push_str(&rest[start..start + 1]);
+            rest = after;
+            continue;
+        }
+        let value =
+            env::var(name).map_err(|_| format!("Environment variable {} is not set", name))?;
+        expanded.push_str(&value);
+        rest = &after[consumed..];
+    }
+    expanded.push_str(rest);
+
+    let expanded = PathBuf::from(expanded);
+    if expanded.is_absolute() {
+        return Ok(expanded);
+    }
+    let exe_dir = env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(PathBuf::from))
+        .ok_or("Cannot resolve a relative path, the executable's directory is unknown")?;
+    Ok(exe_dir.join(expanded))
+}
+
+/// The expanded `data_dir` from pake.json, if one is configured.
+pub fn configured_data_dir() -> Result<Option<PathBuf>, String> {
+    get_pake_config()
+        .0
+        .data_dir
+        .as_deref()
+        .map(|dir| expand_path(dir).map_err(|e| format!("Invalid data_dir {}: {}", dir, e)))
+        .transpose()
+}
+
+/// Create the configured `data_dir` and make sure it accepts writes, so a bad
+/// path fails at startup with a clear message instead of losing state later.
+pub fn check_data_dir() -> Result<(), String> {
+    match configured_data_dir()? {
+        Some(data_dir) => ensure_writable_dir(&data_dir),
+        None => Ok(()),
+    }
+}
+
+fn ensure_writable_dir(dir: &Path) -> Result<(), String> {
+    std::fs::create_dir_all(dir)
+        .map_err(|e| format!("Cannot create data_dir {}: {}", dir.display(), e))?;
+    let probe = dir.join(".pake-write-test");
+    std::fs::write(&probe, b"")
+        .and_then(|_| std::fs::remove_file(&probe))
+        .map_err(|e| format!("data_dir {} is not writable: {}", dir.display(), e))
+}
+
+// Whether `host` is the allowed host itself or one of its subdomains
+pub fn host_matches(host: &str, allowed_host: &str) -> bool {
+    host == allowed_host || host.ends_with(&format!(".{allowed_host}"))
+}
+
+/// Parse `#RRGGBB` or `#RGB` (leading `#` optional) into RGB components.
+pub fn parse_hex_color(hex: &str) -> Option<(u8, u8, u8)> {
+    let hex = hex.trim().trim_start_matches('#');
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    match hex.len() {
+        6 => Some((
+            u8::from_str_radix(&hex[0..2], 16).ok()?,
+            u8::from_str_radix(&hex[2..4], 16).ok()?,
+            u8::from_str_radix(&hex[4..6], 16).ok()?,
+        )),
+        3 => {
+            let expand = |i: usize| u8::from_str_radix(&hex[i..i + 1].repeat(2), 16).ok();
+            Some((expand(0)?, expand(1)?, expand(2)?))
+        }
+        _ => None,
+    }
+}
+
+// WCAG relative luminance; dark backgrounds need light text for contrast
+pub fn is_dark_color((r, g, b): (u8, u8, u8)) -> bool {
+    let channel = |c: u8| {
+        let c = c as f64 / 255.0;
+        if c <= 0.03928 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    let luminance = 0.2126 * channel(r) + 0.7152 * channel(g) + 0.0722 * channel(b);
+    luminance < 0.179
+}
+
+pub fn is_invalid_cert_allowed(host: &str, allowed_hosts: &[String]) -> bool {
+    allowed_hosts
+        .iter()
+        .any(|allowed_host| host_matches(host, allowed_host))
+}
+
+// Let listeners react whenever the main window is brought back on screen
+pub fn emit_shown(window: &WebviewWindow) {
+    let _ = window.emit(SHOWN_EVENT, ());
+}
+
+/// Emit `event` to the main window and every `pake-*` window, returning the
+/// labels it was delivered to. Other webviews (e.g. plugin windows) are skipped.
+pub fn broadcast<S: Serialize + Clone>(
+    app: &AppHandle,
+    event: &str,
+    payload: S,
+) -> Result<Vec<String>, String> {
+    let labels = broadcast_labels(app.webview_windows().into_keys());
+    for label in &labels {
+        app.emit_to(
+            EventTarget::webview_window(label.as_str()),
+            event,
+            payload.clone(),
+        )
+        .map_err(|e| format!("Failed to broadcast {}: {}", event, e))?;
+    }
+    Ok(labels)
+}
+
+// The main window and `pake-*` windows, sorted; plugin webviews are left out
+fn broadcast_labels(labels: impl IntoIterator<Item = String>) -> Vec<String> {
+    let mut labels: Vec<String> = labels
+        .into_iter()
+        .filter(|label| label == "pake" || label.starts_with("pake-"))
+        .collect();
+    labels.sort();
+    labels
+}
+
+// Build a script calling `method` on the first match, as a no-op when nothing matches
+pub fn element_call_script(selector: &str, method: &str) -> String {
+    format!(
+        "document.querySelector({})?.{}()",
+        serde_json::to_string(selector).unwrap(),
+        method
+    )
+}
+
+/// Resolves to the account shown by `identity_selector`, `null` when signed out.
+/// A valid CSS selector reads the element's text (or its `aria-label`/`title`),
+/// anything else runs as a JS expression, which a CSP without `unsafe-eval` blocks.
+pub fn identity_script(selector: &str) -> String {
+    format!(
+        r#"(async () => {{
+  const source = {};
+  let value;
+  try {{
+    const element = document.querySelector(source);
+    value =
+      element &&
+      (element.textContent.trim() ||
+        element.getAttribute("aria-label") ||
+        element.getAttribute("title"));
+  }} catch (_) {{
+    try {{
+      value = await (0, eval)(source);
+    }} catch (_) {{
+      return null;
+    }}
+  }}
+  if (value == null || value === false) return null;
+  const text = String(value).replace(/\s+/g, " ").trim();
+  return text ? text.slice(0, 200) : null;
+}})()"#,
+        serde_json::to_string(selector).unwrap()
+    )
+}
+
+// Run a menu script in its own function scope so its errors and `return`s stay
+// contained. Bookmarklets are percent-decoded after their `javascript:` prefix.
+pub fn user_script(js: &str) -> String {
+    let js = match js.trim().strip_prefix("javascript:") {
+        Some(bookmarklet) => percent_decode(bookmarklet).unwrap_or_else(|| bookmarklet.to_string()),
+        None => js.to_string(),
+    };
+    format!(
+        r#"(() => {{
+  try {{
+{}
+  }} catch (error) {{
+    console.error("[Pake] Script failed:", error);
+  }}
+}})();"#,
+        js
+    )
+}
+
+const DEFAULT_SCROLL_AMOUNT: f64 = 100.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ScrollDirection {
+    Top,
+    Bottom,
+    PageUp,
+    PageDown,
+    Up,
+    Down,
+}
+
+/// Smoothly scroll the page. `amount` is in pixels and only used by `up`/`down`.
+pub fn scroll_script(direction: ScrollDirection, amount: Option<f64>) -> String {
+    let amount = amount
+        .filter(|amount| amount.is_finite())
+        .unwrap_or(DEFAULT_SCROLL_AMOUNT);
+    let (method, top) = match direction {
+        ScrollDirection::Top => ("scrollTo", "0".to_string()),
+        ScrollDirection::Bottom => (
+            "scrollTo",
+            "document.documentElement.scrollHeight".to_string(),
+        ),
+        ScrollDirection::PageUp => ("scrollBy", "-window.innerHeight * 0.9".to_string()),
+        ScrollDirection::PageDown => ("scrollBy", "window.innerHeight * 0.9".to_string()),
+        ScrollDirection::Up => ("scrollBy", (-amount).to_string()),
+        ScrollDirection::Down => ("scrollBy", amount.to_string()),
+    };
+    format!(r#"window.{method}({{ top: {top}, behavior: "smooth" }})"#)
+}
+
+// Resolves to whether the selector matched, invalid selectors count as no match
+pub fn scroll_into_view_script(selector: &str) -> String {
+    format!(
+        r#"(() => {{
+  const element = document.querySelector({});
+  element?.scrollIntoView({{ behavior: "smooth", block: "start" }});
+  return !!element;
+}})()"#,
+        serde_json::to_string(selector).unwrap()
+    )
+}
+
+pub fn encode_png(rgba: &[u8], width: u32, height: u32) -> Result<Vec<u8>, String> {
+    let mut buffer = Vec::new();
+    let mut encoder = png::Encoder::new(&mut buffer, width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    let mut writer = encoder
+        .write_header()
+        .map_err(|e| format!("Failed to encode PNG: {}", e))?;
+    writer
+        .write_image_data(rgba)
+        .map_err(|e| format!("Failed to encode PNG: {}", e))?;
+    writer
+        .finish()
+        .map_err(|e| format!("Failed to encode PNG: {}", e))?;
+
+    Ok(buffer)
+}
+
+/// Evaluate a JS expression in the page and wait for its (awaited) value.
+/// `eval` is fire-and-forget, so the page reports back through `report_eval_result`.
+pub async fn eval_with_result(window: &WebviewWindow, expression: &str) -> Result<Value, String> {
+    eval_with_result_timeout(window, expression, EVAL_RESULT_TIMEOUT).await
+}
+
+/// `eval_with_result` for expressions that may take longer than the default 5s.
+pub async fn eval_with_result_timeout(
+    window: &WebviewWindow,
+    expression: &str,
+    timeout: Duration,
+) -> Result<Value, String> {
+    let id = EVAL_COUNTER.fetch_add(1, Ordering::SeqCst);
+    let (sender, receiver) = oneshot::channel();
+    PENDING_EVALS.lock().unwrap().insert(id, sender);
+
+    let script = format!(
+        r#"(async () => {{
+  let value = null;
+  try {{
+    value = await ({expression});
+  }} catch (error) {{
+    console.error("[Pake] eval failed:", error);
+  }}
+  window.__TAURI__.core.invoke("report_eval_result", {{ id: {id}, value: value ?? null }});
+}})();"#
+    );
+
+    if let Err(e) = window.eval(&script) {
+        PENDING_EVALS.lock().unwrap().remove(&id);
+        return Err(format!("Failed to evaluate script: {}", e));
+    }
+
+    match tokio::time::timeout(timeout, receiver).await {
+        Ok(Ok(value)) => Ok(value),
+        _ => {
+            PENDING_EVALS.lock().unwrap().remove(&id);
+            Err("Timed out waiting for the page to respond".to_string())
+        }
+    }
+}
+
+pub fn resolve_eval_result(id: u64, value: Value) {
+    if let Some(sender) = PENDING_EVALS.lock().unwrap().remove(&id) {
+        let _ = sender.send(value);
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VersionInfo {
+    pub app_name: String,
+    pub app_version: String,
+    pub pake_version: String,
+    pub tauri_version: String,
+    pub os: String,
+    pub os_version: String,
+    pub arch: String,
+    pub webview_engine: String,
+    pub webview_version: String,
+}
+
+impl VersionInfo {
+    pub fn summary(&self) -> String {
+        format!(
+            "App: {} {}\nPake: {}\nTauri: {}\nOS: {} {} ({})\nWebview: {} {}\n",
+            self.app_name,
+            self.app_version,
+            self.pake_version,
+            self.tauri_version,
+            self.os,
+            self.os_version,
+            self.arch,
+            self.webview_engine,
+            self.webview_version,
+        )
+    }
+}
+
+pub fn version_info(app: &AppHandle) -> VersionInfo {
+    let package_info = app.package_info();
+    let webview_engine = if cfg!(target_os = "windows") {
+        "WebView2"
+    } else if cfg!(target_os = "macos") {
+        "WKWebView"
+    } else {
+        "WebKitGTK"
+    };
+
+    VersionInfo {
+        app_name: package_info.name.clone(),
+        app_version: package_info.version.to_string(),
+        pake_version: env!("CARGO_PKG_VERSION").to_string(),
+        tauri_version: tauri::VERSION.to_string(),
+        os: env::consts::OS.to_string(),
+        os_version: os_version().unwrap_or_else(|| "unknown".to_string()),
+        arch: env::consts::ARCH.to_string(),
+        webview_engine: webview_engine.to_string(),
+        webview_version: tauri::webview_version().unwrap_or_else(|_| "unknown".to_string()),
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn os_version() -> Option<String> {
+    use objc2_foundation::NSProcessInfo;
+
+    let version = NSProcessInfo::processInfo().operatingSystemVersion();
+    Some(format!(
+        "{}.{}.{}",
+        version.majorVersion, version.minorVersion, version.patchVersion
+    ))
+}
+
+#[cfg(target_os = "windows")]
+fn os_version() -> Option<String> {
+    use windows::core::{w, HSTRING};
+    use windows::Win32::System::Registry::{RegGetValueW, HKEY_LOCAL_MACHINE, RRF_RT_REG_SZ};
+
+    let read = |name: &str| -> Option<String> {
+        let mut buffer = [0u16; 256];
+        let mut size = std::mem::size_of_val(&buffer) as u32;
+        unsafe {
+            RegGetValueW(
+                HKEY_LOCAL_MACHINE,
+                w!("SOFTWARE\\Microsoft\\Windows NT\\CurrentVersion"),
+                &HSTRING::from(name),
+                RRF_RT_REG_SZ,
+                None,
+                Some(buffer.as_mut_ptr().cast()),
+                Some(&mut size),
+            )
+        }
+        .ok()
+        .ok()?;
+        let len = (size as usize / 2).saturating_sub(1);
+        Some(String::from_utf16_lossy(&buffer[..len]))
+    };
+
+    let build = read("CurrentBuild")?;
+    Some(match read("DisplayVersion") {
+        Some(display_version) => format!("{} (build {})", display_version, build),
+        None => format!("build {}", build),
+    })
+}
+
+#[cfg(target_os = "linux")]
+fn os_version() -> Option<String> {
+    let kernel = std::fs::read_to_string("/proc/sys/kernel/osrelease").ok();
+    let distro = std::fs::read_to_string("/etc/os-release")
+        .ok()
+        .and_then(|content| {
+            content.lines().find_map(|line| {
+                line.strip_prefix("PRETTY_NAME=")
+                    .map(|name| name.trim_matches('"').to_string())
+            })
+        });
+
+    match (distro, kernel) {
+        (Some(distro), Some(kernel)) => Some(format!("{} (kernel {})", distro, kernel.trim())),
+        (distro, kernel) => distro.or(kernel.map(|k| k.trim().to_string())),
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+fn os_version() -> Option<String> {
+    None
+}
+
+fn config_file_path(app: &AppHandle, name: &str) -> Option<PathBuf> {
+    match configured_data_dir() {
+        Ok(Some(data_dir)) => Some(data_dir.join(name)),
+        _ => app.path().app_config_dir().ok().map(|dir| dir.join(name)),
+    }
+}
+
+fn read_store(path: Option<PathBuf>) -> serde_json::Map<String, Value> {
+    path.and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+// Serializes read-modify-write cycles so concurrent saves don't drop each other's keys
+static STORE_LOCK: Mutex<()> = Mutex::new(());
+
+/// Set `key` in the JSON store `name`, `null` removing it. Written to a temp
+/// file and renamed over the old one so a crash never leaves it half-written.
+fn update_store(app: &AppHandle, name: &str, key: &str, value: Value) -> Result<(), String> {
+    let path = config_file_path(app, name).ok_or("Failed to get app config dir")?;
+    update_store_file(&path, key, value)
+}
+
+fn update_store_file(path: &Path, key: &str, value: Value) -> Result<(), String> {
+    let _guard = STORE_LOCK.lock().unwrap();
+    let mut store = read_store(Some(path.to_path_buf()));
+    if value.is_null() {
+        store.remove(key);
+    } else {
+        store.insert(key.to_string(), value);
+    }
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let temp_path = path.with_extension("json.tmp");
+    std::fs::write(&temp_path, serde_json::to_string_pretty(&store).unwrap())
+        .and_then(|_| std::fs::rename(&temp_path, path))
+        .map_err(|e| format!("Failed to save {}: {}", path.display(), e))
+}
+
+/// Small JSON store for runtime state that should survive restarts.
+pub fn load_app_state(app: &AppHandle, key: &str) -> Option<Value> {
+    read_store(config_file_path(app, APP_STATE_FILE)).remove(key)
+}
+
+pub fn save_app_state(app: &AppHandle, key: &str, value: Value) -> Result<(), String> {
+    update_store(app, APP_STATE_FILE, key, value)
+}
+
+/// Key/value settings owned by page integrations, kept apart from Pake's own
+/// state and out of the webview so clearing site data leaves them alone.
+pub fn load_setting(app: &AppHandle, key: &str) -> Option<Value> {
+    read_store(config_file_path(app, SETTINGS_FILE)).remove(key)
+}
+
+pub fn save_setting(app: &AppHandle, key: &str, value: Value) -> Result<(), String> {
+    update_store(app, SETTINGS_FILE, key, value)
+}
+
 pub fn show_toast(window: &WebviewWindow, message: &str) {
     let script = format!(r#"pakeToast("{message}");"#);
     window.eval(&script).unwrap();
@@ -101,6 +670,91 @@ pub fn get_download_message_with_lang(
     .to_string()
 }
 
+/// Filename from a `Content-Disposition` header, preferring the RFC 5987
+/// `filename*=UTF-8''...` form. Any directory part is dropped.
+pub fn filename_from_content_disposition(header: &str) -> Option<String> {
+    let params: Vec<(String, &str)> = header
+        .split(';')
+        .filter_map(|param| {
+            let (key, value) = param.split_once('=')?;
+            Some((key.trim().to_ascii_lowercase(), value.trim()))
+        })
+        .collect();
+    let param = |key: &str| params.iter().find(|(k, _)| k == key).map(|(_, v)| *v);
+
+    let name = param("filename*")
+        .and_then(|value| {
+            let (charset, encoded) = value.split_once("''")?;
+            charset
+                .eq_ignore_ascii_case("utf-8")
+                .then(|| percent_decode(encoded))
+                .flatten()
+        })
+        .or_else(|| param("filename").map(|value| value.trim_matches('"').to_string()))?;
+
+    let name = name.rsplit(['/', '\\']).next()?.trim();
+    (!name.is_empty() && name != "." && name != "..").then(|| name.to_string())
+}
+
+fn percent_decode(value: &str) -> Option<String> {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = value.get(i + 1..i + 3)?;
+            decoded.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(decoded).ok()
+}
+
+// Encode everything but RFC 3986 unreserved characters, so the value is safe anywhere in a URL
+fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+/// Substitute the URL-encoded `selection` for every `{selection}` in `template`.
+pub fn expand_url_template(template: &str, selection: &str) -> Result<Url, String> {
+    if !template.contains("{selection}") {
+        return Err(format!("URL template has no {{selection}}: {}", template));
+    }
+    let url = template.replace("{selection}", &percent_encode(selection.trim()));
+    Url::parse(&url).map_err(|e| format!("Invalid lookup URL {}: {}", url, e))
+}
+
+/// Resolve `href` against `base` the way a link on that page would: absolute
+/// hrefs stand alone and `#fragment` keeps the page. The result has to stay on
+/// `allowed_host` (subdomains included) over http(s) or `base`'s own scheme.
+pub fn resolve_href(base: &Url, href: &str, allowed_host: &str) -> Result<Url, String> {
+    let href = href.trim();
+    if href.is_empty() {
+        return Err("href is empty".to_string());
+    }
+    let url = base
+        .join(href)
+        .map_err(|e| format!("Invalid href {}: {}", href, e))?;
+    if !matches!(url.scheme(), "http" | "https") && url.scheme() != base.scheme() {
+        return Err(format!("Unsupported URL scheme: {}", url.scheme()));
+    }
+    match url.host_str() {
+        Some(host) if host_matches(host, allowed_host) => Ok(url),
+        _ => Err(format!("{} is not on {}", url, allowed_host)),
+    }
+}
+
 // Check if the file exists, if it exists, add a number to file name
 pub fn check_file_or_append(file_path: &str) -> String {
     let mut new_path = PathBuf::from(file_path);
@@ -128,3 +782,300 @@ pub fn check_file_or_append(file_path: &str) -> String {
 
     new_path.to_string_lossy().into_owned()
 }
+
+/// Captures log records so tests can check what was logged.
+#[cfg(test)]
+pub mod test_logger {
+    use std::sync::Mutex;
+
+    static RECORDS: Mutex<Vec<(log::Level, String)>> = Mutex::new(Vec::new());
+
+    struct TestLogger;
+
+    impl log::Log for TestLogger {
+        fn enabled(&self, _metadata: &log::Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, record: &log::Record) {
+            RECORDS
+                .lock()
+                .unwrap()
+                .push((record.level(), record.args().to_string()));
+        }
+
+        fn flush(&self) {}
+    }
+
+    pub fn init() {
+        if log::set_logger(&TestLogger).is_ok() {
+            log::set_max_level(log::LevelFilter::Trace);
+        }
+    }
+
+    pub fn logged(level: log::Level, message: &str) -> bool {
+        RECORDS
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|(logged_level, logged)| *logged_level == level && logged == message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_six_digit_hex() {
+        assert_eq!(parse_hex_color("#1e90ff"), Some((0x1e, 0x90, 0xff)));
+        assert_eq!(parse_hex_color("1E90FF"), Some((0x1e, 0x90, 0xff)));
+        assert_eq!(parse_hex_color("  #000000 "), Some((0, 0, 0)));
+    }
+
+    #[test]
+    fn parses_three_digit_hex() {
+        assert_eq!(parse_hex_color("#fff"), Some((255, 255, 255)));
+        assert_eq!(parse_hex_color("a1c"), Some((0xaa, 0x11, 0xcc)));
+    }
+
+    #[test]
+    fn rejects_invalid_hex() {
+        for input in [
+            "",
+            "#",
+            "#12",
+            "#1234",
+            "#1234567",
+            "zzz",
+            "#12345g",
+            "rgb(0,0,0)",
+        ] {
+            assert_eq!(parse_hex_color(input), None, "{input}");
+        }
+    }
+
+    #[test]
+    fn dark_color_threshold() {
+        assert!(is_dark_color((0, 0, 0)));
+        assert!(is_dark_color((0x1e, 0x1e, 0x1e)));
+        assert!(!is_dark_color((255, 255, 255)));
+        assert!(!is_dark_color((0xff, 0xd7, 0x00)));
+        // Mid grays either side of the 0.179 luminance cut-off
+        assert!(is_dark_color((117, 117, 117)));
+        assert!(!is_dark_color((118, 118, 118)));
+    }
+
+    fn sample_version_info() -> VersionInfo {
+        VersionInfo {
+            app_name: "Mail".to_string(),
+            app_version: "1.2.0".to_string(),
+            pake_version: "3.4.0".to_string(),
+            tauri_version: "2.9.0".to_string(),
+            os: "macos".to_string(),
+            os_version: "15.1".to_string(),
+            arch: "aarch64".to_string(),
+            webview_engine: "WKWebView".to_string(),
+            webview_version: "620.1.16".to_string(),
+        }
+    }
+
+    #[test]
+    fn version_summary_lists_every_component() {
+        assert_eq!(
+            sample_version_info().summary(),
+            "App: Mail 1.2.0\nPake: 3.4.0\nTauri: 2.9.0\nOS: macos 15.1 (aarch64)\nWebview: WKWebView 620.1.16\n"
+        );
+    }
+
+    #[test]
+    fn version_info_serializes_snake_case_fields() {
+        let value = serde_json::to_value(sample_version_info()).unwrap();
+        assert_eq!(value["app_name"], "Mail");
+        assert_eq!(value["pake_version"], "3.4.0");
+        assert_eq!(value["webview_engine"], "WKWebView");
+        assert_eq!(value.as_object().unwrap().len(), 9);
+    }
+
+    fn temp_store(name: &str) -> PathBuf {
+        env::temp_dir()
+            .join(format!("pake-store-test-{}", std::process::id()))
+            .join(format!("{name}.json"))
+    }
+
+    #[test]
+    fn store_round_trips_values() {
+        let path = temp_store("round-trip");
+        update_store_file(&path, "theme", "dark".into()).unwrap();
+        update_store_file(&path, "zoom", serde_json::json!({ "pake": 120 })).unwrap();
+
+        let mut store = read_store(Some(path.clone()));
+        assert_eq!(store.remove("theme"), Some("dark".into()));
+        assert_eq!(
+            store.remove("zoom"),
+            Some(serde_json::json!({ "pake": 120 }))
+        );
+        assert!(!path.with_extension("json.tmp").exists());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn store_null_deletes_the_key() {
+        let path = temp_store("null-deletes");
+        update_store_file(&path, "kept", true.into()).unwrap();
+        update_store_file(&path, "removed", 1.into()).unwrap();
+        update_store_file(&path, "removed", Value::Null).unwrap();
+        update_store_file(&path, "never_set", Value::Null).unwrap();
+
+        let store = read_store(Some(path.clone()));
+        assert_eq!(store.len(), 1);
+        assert_eq!(store.get("kept"), Some(&Value::Bool(true)));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn store_keeps_keys_written_concurrently() {
+        let path = temp_store("concurrent");
+        let writers: Vec<_> = (0..8)
+            .map(|i| {
+                let path = path.clone();
+                std::thread::spawn(move || update_store_file(&path, &format!("key{i}"), i.into()))
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap().unwrap();
+        }
+
+        let store = read_store(Some(path.clone()));
+        for i in 0..8 {
+            assert_eq!(store.get(&format!("key{i}")), Some(&Value::from(i)));
+        }
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn missing_or_corrupt_store_reads_empty() {
+        assert!(read_store(None).is_empty());
+        let path = temp_store("corrupt");
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, "{ not json").unwrap();
+        assert!(read_store(Some(path.clone())).is_empty());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn app_id_suffix_is_appended_after_the_separator() {
+        assert_eq!(
+            with_app_id_suffix("com.pake.mail", Some("work"), '.'),
+            "com.pake.mail.work"
+        );
+        assert_eq!(with_app_id_suffix("Mail", Some("work"), '-'), "Mail-work");
+    }
+
+    #[test]
+    fn app_id_suffix_is_sanitized() {
+        assert_eq!(
+            with_app_id_suffix("com.pake.mail", Some("my work_2"), '.'),
+            "com.pake.mail.my-work-2"
+        );
+        assert_eq!(
+            with_app_id_suffix("com.pake.mail", Some("café.prod"), '.'),
+            "com.pake.mail.caf--prod"
+        );
+    }
+
+    #[test]
+    fn app_id_suffix_trims_edge_dashes() {
+        assert_eq!(
+            with_app_id_suffix("com.pake.mail", Some("--work--"), '.'),
+            "com.pake.mail.work"
+        );
+        assert_eq!(
+            with_app_id_suffix("com.pake.mail", Some(" work "), '.'),
+            "com.pake.mail.work"
+        );
+    }
+
+    #[test]
+    fn empty_app_id_suffix_leaves_the_base() {
+        for suffix in [None, Some(""), Some("-"), Some("  "), Some("!!")] {
+            assert_eq!(
+                with_app_id_suffix("com.pake.mail", suffix, '.'),
+                "com.pake.mail",
+                "{suffix:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn broadcast_reaches_main_and_pake_windows_only() {
+        let labels = [
+            "pake-2",
+            "devtools",
+            "pake",
+            "pakeish",
+            "pake-external-1",
+            "updater",
+        ]
+        .map(String::from);
+        assert_eq!(
+            broadcast_labels(labels),
+            ["pake", "pake-2", "pake-external-1"]
+        );
+        assert!(broadcast_labels(Vec::new()).is_empty());
+    }
+
+    #[test]
+    fn expands_home() {
+        let Ok(home) = env::var("HOME").or_else(|_| env::var("USERPROFILE")) else {
+            return;
+        };
+        assert_eq!(expand_path("~").unwrap(), PathBuf::from(&home));
+        assert_eq!(
+            expand_path("~/pake").unwrap(),
+            PathBuf::from(format!("{home}/pake"))
+        );
+    }
+
+    #[test]
+    fn expands_env_vars() {
+        let root = env::temp_dir();
+        env::set_var("PAKE_TEST_EXPAND_ROOT", &root);
+        assert_eq!(
+            expand_path("$PAKE_TEST_EXPAND_ROOT/pake").unwrap(),
+            root.join("pake")
+        );
+        env::set_var("PAKE_TEST_EXPAND_NAME", "pake");
+        assert_eq!(
+            expand_path("${PAKE_TEST_EXPAND_ROOT}/${PAKE_TEST_EXPAND_NAME}data").unwrap(),
+            root.join("pakedata")
+        );
+        assert_eq!(
+            expand_path("${PAKE_TEST_EXPAND_ROOT}/a$/b").unwrap(),
+            root.join("a$/b")
+        );
+    }
+
+    #[test]
+    fn rejects_unset_and_unclosed_vars() {
+        let err = expand_path("$PAKE_TEST_EXPAND_UNSET/data").unwrap_err();
+        assert!(err.contains("PAKE_TEST_EXPAND_UNSET"), "{err}");
+        assert!(expand_path("${PAKE_TEST_EXPAND_UNSET").is_err());
+    }
+
+    #[test]
+    fn resolves_relative_paths_next_to_the_executable() {
+        let exe_dir = env::current_exe().unwrap().parent().unwrap().to_path_buf();
+        assert_eq!(expand_path("data").unwrap(), exe_dir.join("data"));
+    }
+
+    #[test]
+    fn creates_and_probes_data_dir() {
+        let root = env::temp_dir().join(format!("pake-data-dir-test-{}", std::process::id()));
+        let dir = root.join("nested");
+        ensure_writable_dir(&dir).unwrap();
+        assert!(dir.is_dir());
+        assert!(!dir.join(".pake-write-test").exists());
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}